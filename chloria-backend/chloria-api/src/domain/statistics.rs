@@ -0,0 +1,93 @@
+use std::{collections::HashSet, sync::LazyLock};
+
+use encoding_rs::SHIFT_JIS;
+use kanji::exam_lists;
+
+// Same sets as the ones the job reports its coverage with
+// Jōyō kanji are the ones of the Kanji Kentei up to level 2
+static JOYO_KANJI: LazyLock<HashSet<char>> = LazyLock::new(|| {
+    [
+        exam_lists::LEVEL_10,
+        exam_lists::LEVEL_09,
+        exam_lists::LEVEL_08,
+        exam_lists::LEVEL_07,
+        exam_lists::LEVEL_06,
+        exam_lists::LEVEL_05,
+        exam_lists::LEVEL_04,
+        exam_lists::LEVEL_03,
+        exam_lists::LEVEL_02_PRE,
+        exam_lists::LEVEL_02,
+    ]
+    .iter()
+    .flat_map(|l| l.chars().filter(|c| kanji::is_kanji(*c)))
+    .collect()
+});
+
+// Numbers of kanji in each level of JIS X 0208
+const JIS_LEVEL_1_SIZE: usize = 2965;
+const JIS_LEVEL_2_SIZE: usize = 3390;
+
+// Level of the kanji in JIS X 0208, if it is one
+fn jis_kanji_level(c: char) -> Option<u8> {
+    let mut buffer = [0; 4];
+    let (bytes, _, had_errors) = SHIFT_JIS.encode(c.encode_utf8(&mut buffer));
+    let [lead, trail] = bytes[..] else {
+        return None;
+    };
+    if had_errors {
+        return None;
+    }
+    // Ref: https://en.wikipedia.org/wiki/Shift_JIS#Shift_JIS_byte_map
+    let row = match lead {
+        0x81..=0x9F => (lead - 0x81) * 2 + 1,
+        _ => (lead - 0xC1) * 2 + 1,
+    } + (trail >= 0x9F) as u8;
+    match row {
+        16..=47 => Some(1),
+        48..=84 => Some(2),
+        _ => None,
+    }
+}
+
+// Shares of each set whose characters appear at least once, from 0 to 1
+pub(crate) struct CharacterCoverage {
+    pub(crate) joyo_kanji_ratio: f64,
+    pub(crate) jis_level_1_ratio: f64,
+    pub(crate) jis_level_2_ratio: f64,
+}
+
+impl CharacterCoverage {
+    pub(crate) fn new<'c>(distinct_characters: impl Iterator<Item = &'c str>) -> Self {
+        let (mut joyo_kanji_count, mut jis_level_1_count, mut jis_level_2_count) = (0, 0, 0);
+        for c in distinct_characters.filter_map(|c| c.chars().next()) {
+            if JOYO_KANJI.contains(&c) {
+                joyo_kanji_count += 1;
+            }
+            match jis_kanji_level(c) {
+                Some(1) => jis_level_1_count += 1,
+                Some(2) => jis_level_2_count += 1,
+                _ => {}
+            }
+        }
+        Self {
+            joyo_kanji_ratio: joyo_kanji_count as f64 / JOYO_KANJI.len() as f64,
+            jis_level_1_ratio: jis_level_1_count as f64 / JIS_LEVEL_1_SIZE as f64,
+            jis_level_2_ratio: jis_level_2_count as f64 / JIS_LEVEL_2_SIZE as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CharacterCoverage, JOYO_KANJI};
+
+    #[test]
+    fn cover_characters() {
+        assert_eq!(JOYO_KANJI.len(), 2136);
+        // `亜` is in the Jōyō list and the level 1, `弌` in the level 2, `𠮟` outside JIS X 0208
+        let coverage = CharacterCoverage::new(["亜", "弌", "𠮟", "あ", "A"].into_iter());
+        assert_eq!(coverage.joyo_kanji_ratio, 1.0 / 2136.0);
+        assert_eq!(coverage.jis_level_1_ratio, 1.0 / 2965.0);
+        assert_eq!(coverage.jis_level_2_ratio, 1.0 / 3390.0);
+    }
+}
@@ -1,6 +1,8 @@
 pub(crate) mod authenticate;
 pub(crate) mod create_news_insight;
 pub(crate) mod read_news;
+pub(crate) mod read_news_characters;
+pub(crate) mod read_news_sentences;
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -9,7 +9,7 @@ use tokio_util::io::ReaderStream;
 
 use super::{
     super::{
-        ports::repository::{Repository, SelectNewsInput},
+        ports::repository::{NewsQuality, NewsSplit, Repository, SelectNewsInput},
         workshop::Workshop,
     },
     Case,
@@ -17,6 +17,11 @@ use super::{
 
 pub(crate) struct ReadNewsCaseInput {
     pub(crate) date: NaiveDate,
+    pub(crate) quality: Option<NewsQuality>,
+    pub(crate) deduplicate: bool,
+    pub(crate) min_difficulty: Option<f32>,
+    pub(crate) max_difficulty: Option<f32>,
+    pub(crate) split: Option<NewsSplit>,
 }
 
 pub(crate) struct ReadNewsCaseOutput {
@@ -46,7 +51,14 @@ impl Case for ReadNewsCase {
         let mut writer = Writer::from_writer(vec![]);
         for select_news_output in self
             .repository
-            .select_news(SelectNewsInput { date: self.input.date })
+            .select_news(SelectNewsInput {
+                date: self.input.date,
+                quality: self.input.quality,
+                deduplicate: self.input.deduplicate,
+                min_difficulty: self.input.min_difficulty,
+                max_difficulty: self.input.max_difficulty,
+                split: self.input.split,
+            })
             .await?
         {
             if let Err(error) = writer.serialize(select_news_output) {
@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+
+use super::{
+    super::{
+        ports::repository::{NewsSplit, Repository, SelectCharacterStatisticsInput, SelectCharacterStatisticsOutput},
+        workshop::Workshop,
+    },
+    Case,
+};
+
+pub(crate) struct ReadNewsCharactersCaseInput {
+    pub(crate) source_name: Option<String>,
+    pub(crate) date_from: Option<NaiveDate>,
+    pub(crate) date_to: Option<NaiveDate>,
+    pub(crate) split: Option<NewsSplit>,
+    pub(crate) top_characters_num: i64,
+}
+
+struct ReadNewsCharactersCase {
+    repository: Arc<dyn Repository>,
+    input: ReadNewsCharactersCaseInput,
+}
+
+impl Workshop {
+    pub(crate) async fn execute_read_news_characters_case(
+        &self,
+        input: ReadNewsCharactersCaseInput,
+    ) -> Result<SelectCharacterStatisticsOutput> {
+        let case = ReadNewsCharactersCase {
+            repository: Arc::clone(&self.repository),
+            input,
+        };
+        self.run_case(case).await
+    }
+}
+
+#[async_trait]
+impl Case for ReadNewsCharactersCase {
+    type Output = SelectCharacterStatisticsOutput;
+
+    async fn execute(self) -> Result<Self::Output> {
+        self.repository
+            .select_character_statistics(SelectCharacterStatisticsInput {
+                source_name: self.input.source_name,
+                date_from: self.input.date_from,
+                date_to: self.input.date_to,
+                split: self.input.split,
+                top_characters_num: self.input.top_characters_num,
+            })
+            .await
+    }
+}
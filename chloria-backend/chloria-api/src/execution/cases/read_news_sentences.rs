@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+
+use super::{
+    super::{
+        ports::repository::{NewsSplit, Repository, SelectSentenceOutput, SelectSentencesInput},
+        workshop::Workshop,
+    },
+    Case,
+};
+
+pub(crate) struct ReadNewsSentencesCaseInput {
+    pub(crate) source_name: Option<String>,
+    pub(crate) date_from: Option<NaiveDate>,
+    pub(crate) date_to: Option<NaiveDate>,
+    pub(crate) min_length: Option<i32>,
+    pub(crate) max_length: Option<i32>,
+    pub(crate) split: Option<NewsSplit>,
+    pub(crate) limit: i64,
+}
+
+struct ReadNewsSentencesCase {
+    repository: Arc<dyn Repository>,
+    input: ReadNewsSentencesCaseInput,
+}
+
+impl Workshop {
+    pub(crate) async fn execute_read_news_sentences_case(
+        &self,
+        input: ReadNewsSentencesCaseInput,
+    ) -> Result<Vec<SelectSentenceOutput>> {
+        let case = ReadNewsSentencesCase {
+            repository: Arc::clone(&self.repository),
+            input,
+        };
+        self.run_case(case).await
+    }
+}
+
+#[async_trait]
+impl Case for ReadNewsSentencesCase {
+    type Output = Vec<SelectSentenceOutput>;
+
+    async fn execute(self) -> Result<Self::Output> {
+        self.repository
+            .select_sentences(SelectSentencesInput {
+                source_name: self.input.source_name,
+                date_from: self.input.date_from,
+                date_to: self.input.date_to,
+                min_length: self.input.min_length,
+                max_length: self.input.max_length,
+                split: self.input.split,
+                limit: self.input.limit,
+            })
+            .await
+    }
+}
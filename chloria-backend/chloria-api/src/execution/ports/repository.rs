@@ -4,8 +4,27 @@ use chrono::NaiveDate;
 use mockall::automock;
 use serde::Serialize;
 
+#[derive(Clone, Copy)]
+pub(crate) enum NewsQuality {
+    Passed,  // News passing every quality rule
+    Flagged, // News saved despite failing some quality rules
+}
+
+// Split of the dataset news are assigned to by the job, news not assigned yet belong to none
+#[derive(Clone, Copy)]
+pub(crate) enum NewsSplit {
+    Train,
+    Validation,
+    Test,
+}
+
 pub(crate) struct SelectNewsInput {
     pub(crate) date: NaiveDate,
+    pub(crate) quality: Option<NewsQuality>, // News of any quality are selected if not given
+    pub(crate) deduplicate: bool, // Whether to select only the first matching news of each near-duplicate cluster
+    pub(crate) min_difficulty: Option<f32>, // News without a difficulty are excluded if either bound is given
+    pub(crate) max_difficulty: Option<f32>,
+    pub(crate) split: Option<NewsSplit>, // News of any split, assigned or not, are selected if not given
 }
 
 #[derive(Serialize)]
@@ -14,7 +33,74 @@ pub(crate) struct SelectNewsOutput {
     pub(crate) article_id: String,
     pub(crate) title: Option<String>,
     pub(crate) text: Option<String>,
+    pub(crate) document: Option<String>, // Structured body which `text` is rendered from, serialized as JSON
     pub(crate) image_path: Option<String>,
+    pub(crate) image_width: Option<i32>,
+    pub(crate) image_height: Option<i32>,
+    pub(crate) image_mime_type: Option<String>,
+    pub(crate) image_byte_size: Option<i64>,
+    pub(crate) image_sha256: Option<String>,
+    pub(crate) image_perceptual_hash: Option<String>,
+    pub(crate) quality_reasons: String, // Comma-separated reasons why the news fails quality rules
+    pub(crate) cluster_id: Option<i32>, // ID of the first news among near-duplicates of this news
+    pub(crate) difficulty: Option<f32>, // Kanji difficulty of `text`, from 0 (no kanji) to 1
+    pub(crate) max_kanji_grade: Option<i32>, // Highest school grade of the kanji of `text`, 10 for non-Jōyō kanji
+}
+
+pub(crate) struct SelectCharacterStatisticsInput {
+    pub(crate) source_name: Option<String>, // Characters of news of every source are counted if not given
+    pub(crate) date_from: Option<NaiveDate>, // Only count characters of news created on or after this date
+    pub(crate) date_to: Option<NaiveDate>,  // Only count characters of news created before this date
+    pub(crate) split: Option<NewsSplit>,
+    pub(crate) top_characters_num: i64,
+}
+
+// Counts of characters summed over news, whitespace excluded
+#[derive(Serialize)]
+pub(crate) struct SelectCharacterStatisticsOutput {
+    pub(crate) news_count: i64,
+    pub(crate) character_count: i64,
+    pub(crate) kanji_count: i64,
+    pub(crate) hiragana_count: i64,
+    pub(crate) katakana_count: i64,
+    pub(crate) latin_count: i64,
+    pub(crate) digit_count: i64,
+    pub(crate) other_count: i64,
+    pub(crate) joyo_kanji_count: i64,                           // Kanji in the Jōyō list
+    pub(crate) jis_level_1_count: i64,                          // Kanji of the level 1 of JIS X 0208
+    pub(crate) jis_level_2_count: i64,                          // Kanji of the level 2 of JIS X 0208
+    pub(crate) non_jis_count: i64,                              // Characters outside JIS X 0201 and JIS X 0208
+    pub(crate) joyo_kanji_coverage: f64,                        // Share of the Jōyō kanji which appear at least once
+    pub(crate) jis_level_1_coverage: f64, // Share of the kanji of the level 1 of JIS X 0208 which appear at least once
+    pub(crate) jis_level_2_coverage: f64, // Share of the kanji of the level 2 of JIS X 0208 which appear at least once
+    pub(crate) top_characters: Vec<SelectCharacterCountOutput>, // The most frequent first
+}
+
+#[derive(Serialize)]
+pub(crate) struct SelectCharacterCountOutput {
+    pub(crate) character: String,
+    pub(crate) count: i64,
+}
+
+pub(crate) struct SelectSentencesInput {
+    pub(crate) source_name: Option<String>, // Sentences of news of every source are sampled if not given
+    pub(crate) date_from: Option<NaiveDate>, // Only sample sentences of news created on or after this date
+    pub(crate) date_to: Option<NaiveDate>,  // Only sample sentences of news created before this date
+    pub(crate) min_length: Option<i32>,     // In characters, inclusive
+    pub(crate) max_length: Option<i32>,     // In characters, inclusive
+    pub(crate) split: Option<NewsSplit>,
+    pub(crate) limit: i64,
+}
+
+#[derive(Serialize)]
+pub(crate) struct SelectSentenceOutput {
+    pub(crate) source_name: String,
+    pub(crate) article_id: String,
+    pub(crate) start_offset: i32, // Offset in characters from the start of the text of the news
+    pub(crate) length: i32,       // Number of characters
+    pub(crate) text: String,
+    pub(crate) difficulty: Option<f32>,
+    pub(crate) max_kanji_grade: Option<i32>,
 }
 
 pub(crate) struct InsertNewsInsightInput {
@@ -29,4 +115,10 @@ pub(crate) trait Repository: Send + Sync {
     async fn select_client_api_secret(&self, api_key_input: &str) -> Result<Option<String>>;
     async fn select_news(&self, input: SelectNewsInput) -> Result<Vec<SelectNewsOutput>>;
     async fn insert_news_insight(&self, input: InsertNewsInsightInput) -> Result<()>;
+    async fn select_character_statistics(
+        &self,
+        input: SelectCharacterStatisticsInput,
+    ) -> Result<SelectCharacterStatisticsOutput>;
+    // Sample sentences at random
+    async fn select_sentences(&self, input: SelectSentencesInput) -> Result<Vec<SelectSentenceOutput>>;
 }
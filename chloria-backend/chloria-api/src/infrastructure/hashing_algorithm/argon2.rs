@@ -15,7 +15,7 @@ impl Argon2Tool {
 impl HashingAlgorithm for Argon2Tool {
     fn verify(&self, secret: &str, hashed_secret: &str) -> Result<bool> {
         Ok(Argon2::default()
-            .verify_password(secret.as_bytes(), &PasswordHash::new(&hashed_secret)?)
+            .verify_password(secret.as_bytes(), &PasswordHash::new(hashed_secret)?)
             .is_ok())
     }
 }
@@ -1,21 +1,33 @@
+use std::collections::HashSet;
+
 use anyhow::Result;
 use async_trait::async_trait;
-use chrono::{DateTime, Duration, Local, NaiveTime};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime};
 use diesel::{
+    dsl::{count_star, TablesampleDsl},
+    pg::Pg,
     prelude::*,
     r2d2::{ConnectionManager, Pool},
     PgConnection,
 };
 
 use crate::{
-    execution::ports::repository::{InsertNewsInsightInput, Repository, SelectNewsInput, SelectNewsOutput},
+    domain::statistics::CharacterCoverage,
+    execution::ports::repository::{
+        InsertNewsInsightInput, NewsQuality, NewsSplit, Repository, SelectCharacterCountOutput,
+        SelectCharacterStatisticsInput, SelectCharacterStatisticsOutput, SelectNewsInput, SelectNewsOutput,
+        SelectSentenceOutput, SelectSentencesInput,
+    },
     schema::{
         client_credentials::dsl::*,
         news::{self, dsl::*},
-        news_insights,
+        news_characters, news_images, news_insights, news_sentences, news_splits, news_statistics,
     },
 };
 
+// Percentages of sentences sampled before sorting them at random, the last one being all of them
+const SENTENCE_SAMPLE_PORTIONS: [i16; 3] = [1, 10, 100];
+
 pub(crate) struct PostgresqlClient {
     pool: Pool<ConnectionManager<PgConnection>>,
 }
@@ -27,8 +39,56 @@ impl PostgresqlClient {
             .build(ConnectionManager::<PgConnection>::new(database_url))?;
         Ok(Self { pool })
     }
+
+    // News of the source and split created in the range, whose bounds are optional
+    fn select_ranged_news(
+        source_name_value: Option<&str>,
+        date_from: Option<NaiveDate>,
+        date_to: Option<NaiveDate>,
+        split: Option<NewsSplit>,
+    ) -> news::BoxedQuery<'static, Pg> {
+        let to_local =
+            |date: NaiveDate| -> DateTime<Local> { DateTime::from(date.and_time(NaiveTime::default()).and_utc()) };
+        let mut query = news.into_boxed();
+        if let Some(source_name_value) = source_name_value {
+            query = query.filter(source_name.eq(source_name_value.to_string()));
+        }
+        if let Some(date_from) = date_from {
+            query = query.filter(news::created_at.ge(to_local(date_from)));
+        }
+        if let Some(date_to) = date_to {
+            query = query.filter(news::created_at.lt(to_local(date_to)));
+        }
+        if let Some(split) = split {
+            query = query.filter(news::id.eq_any(Self::select_split_news_ids(split)));
+        }
+        query
+    }
+
+    fn select_split_news_ids(split: NewsSplit) -> news_splits::BoxedQuery<'static, Pg, diesel::sql_types::Integer> {
+        let split_value = match split {
+            NewsSplit::Train => "train",
+            NewsSplit::Validation => "validation",
+            NewsSplit::Test => "test",
+        };
+        news_splits::table
+            .filter(news_splits::split.eq(split_value))
+            .select(news_splits::id)
+            .into_boxed()
+    }
+
+    fn select_counted_news(input: &SelectCharacterStatisticsInput) -> news::BoxedQuery<'static, Pg> {
+        Self::select_ranged_news(
+            input.source_name.as_deref(),
+            input.date_from,
+            input.date_to,
+            input.split,
+        )
+    }
 }
 
+define_sql_function!(fn random() -> Double);
+
 #[derive(Insertable, AsChangeset)]
 #[diesel(table_name = news_insights)]
 struct InsertNewsInsightValue {
@@ -51,18 +111,109 @@ impl Repository for PostgresqlClient {
     async fn select_news(&self, input: SelectNewsInput) -> Result<Vec<SelectNewsOutput>> {
         let date: DateTime<Local> = DateTime::from(input.date.and_time(NaiveTime::default()).and_utc());
         let next_date = date + Duration::days(1);
-        let mut outputs = vec![];
-        for (source_name_value, article_id_value, title_value, long_text_value, image_path_value) in news
+        let mut query = news
+            .left_join(
+                news_images::table.on(news_images::news_id
+                    .eq(news::id)
+                    .and(news::image_path.eq(news_images::path.nullable()))),
+            )
             .filter(news::created_at.ge(date).and(news::created_at.lt(next_date)))
-            .select((source_name, article_id, title, long_text, image_path))
-            .get_results(&mut self.pool.get()?)?
+            .into_boxed();
+        query = match input.quality {
+            Some(NewsQuality::Passed) => query.filter(quality_reasons.eq(Vec::<String>::new())),
+            Some(NewsQuality::Flagged) => query.filter(quality_reasons.ne(Vec::<String>::new())),
+            None => query,
+        };
+        if let Some(min_difficulty) = input.min_difficulty {
+            query = query.filter(difficulty.ge(min_difficulty));
+        }
+        if let Some(max_difficulty) = input.max_difficulty {
+            query = query.filter(difficulty.le(max_difficulty));
+        }
+        if let Some(split) = input.split {
+            query = query.filter(news::id.eq_any(Self::select_split_news_ids(split)));
+        }
+        let mut outputs = vec![];
+        // Clusters whose news has already been output, since the first news of a cluster may be out of range
+        let mut output_cluster_ids = HashSet::new();
+        for (
+            news_id,
+            source_name_value,
+            article_id_value,
+            title_value,
+            long_text_value,
+            document_value,
+            image_path_value,
+            image_value,
+            quality_reasons_value,
+            cluster_id_value,
+            difficulty_value,
+            max_kanji_grade_value,
+        ) in query
+            .order(news::id)
+            .select((
+                news::id,
+                source_name,
+                article_id,
+                title,
+                long_text,
+                document,
+                image_path,
+                (
+                    news_images::width,
+                    news_images::height,
+                    news_images::mime_type,
+                    news_images::byte_size,
+                    news_images::sha256,
+                    news_images::perceptual_hash,
+                )
+                    .nullable(),
+                quality_reasons,
+                cluster_id,
+                difficulty,
+                max_kanji_grade,
+            ))
+            .get_results::<(
+                i32,
+                String,
+                String,
+                Option<String>,
+                Option<String>,
+                Option<String>,
+                Option<String>,
+                Option<(i32, i32, String, i64, String, String)>,
+                Vec<String>,
+                Option<i32>,
+                Option<f32>,
+                Option<i32>,
+            )>(&mut self.pool.get()?)?
         {
+            // News which have not been clustered have no near-duplicates
+            if input.deduplicate && !output_cluster_ids.insert(cluster_id_value.unwrap_or(news_id)) {
+                continue;
+            }
+            let (image_width, image_height, image_mime_type, image_byte_size, image_sha256, image_perceptual_hash) =
+                match image_value {
+                    Some((w, h, m, b, s, p)) => (Some(w), Some(h), Some(m), Some(b), Some(s), Some(p)),
+                    None => (None, None, None, None, None, None),
+                };
             outputs.push(SelectNewsOutput {
                 source_name: source_name_value,
                 article_id: article_id_value,
                 title: title_value,
                 text: long_text_value,
+                document: document_value,
                 image_path: image_path_value,
+                image_width,
+                image_height,
+                image_mime_type,
+                image_byte_size,
+                image_sha256,
+                image_perceptual_hash,
+                quality_reasons: quality_reasons_value.join(","),
+                cluster_id: cluster_id_value,
+                difficulty: difficulty_value,
+                max_kanji_grade: max_kanji_grade_value,
             });
         }
         Ok(outputs)
@@ -86,4 +237,162 @@ impl Repository for PostgresqlClient {
             .execute(&mut self.pool.get()?)?;
         Ok(())
     }
+
+    async fn select_character_statistics(
+        &self,
+        input: SelectCharacterStatisticsInput,
+    ) -> Result<SelectCharacterStatisticsOutput> {
+        let connection = &mut self.pool.get()?;
+        let (
+            news_count,
+            character_count,
+            kanji_count,
+            hiragana_count,
+            katakana_count,
+            latin_count,
+            digit_count,
+            other_count,
+            joyo_kanji_count,
+            jis_level_1_count,
+            jis_level_2_count,
+            non_jis_count,
+        ) = news_statistics::table
+            .filter(news_statistics::id.eq_any(Self::select_counted_news(&input).select(news::id)))
+            .select((
+                count_star(),
+                diesel::dsl::sum(news_statistics::character_count),
+                diesel::dsl::sum(news_statistics::kanji_count),
+                diesel::dsl::sum(news_statistics::hiragana_count),
+                diesel::dsl::sum(news_statistics::katakana_count),
+                diesel::dsl::sum(news_statistics::latin_count),
+                diesel::dsl::sum(news_statistics::digit_count),
+                diesel::dsl::sum(news_statistics::other_count),
+                diesel::dsl::sum(news_statistics::joyo_kanji_count),
+                diesel::dsl::sum(news_statistics::jis_level_1_count),
+                diesel::dsl::sum(news_statistics::jis_level_2_count),
+                diesel::dsl::sum(news_statistics::non_jis_count),
+            ))
+            .get_result::<(
+                i64,
+                Option<i64>,
+                Option<i64>,
+                Option<i64>,
+                Option<i64>,
+                Option<i64>,
+                Option<i64>,
+                Option<i64>,
+                Option<i64>,
+                Option<i64>,
+                Option<i64>,
+                Option<i64>,
+            )>(connection)?;
+        let distinct_characters = news_characters::table
+            .filter(news_characters::news_id.eq_any(Self::select_counted_news(&input).select(news::id)))
+            .select(news_characters::character)
+            .distinct()
+            .get_results::<String>(connection)?;
+        let coverage = CharacterCoverage::new(distinct_characters.iter().map(|c| c.as_str()));
+        let top_characters = news_characters::table
+            .filter(news_characters::news_id.eq_any(Self::select_counted_news(&input).select(news::id)))
+            .group_by(news_characters::character)
+            .select((news_characters::character, diesel::dsl::sum(news_characters::count)))
+            .order((
+                diesel::dsl::sum(news_characters::count).desc(),
+                news_characters::character,
+            ))
+            .limit(input.top_characters_num)
+            .get_results::<(String, Option<i64>)>(connection)?
+            .into_iter()
+            .map(|(character, count)| SelectCharacterCountOutput {
+                character,
+                count: count.unwrap_or_default(),
+            })
+            .collect();
+        Ok(SelectCharacterStatisticsOutput {
+            news_count,
+            character_count: character_count.unwrap_or_default(),
+            kanji_count: kanji_count.unwrap_or_default(),
+            hiragana_count: hiragana_count.unwrap_or_default(),
+            katakana_count: katakana_count.unwrap_or_default(),
+            latin_count: latin_count.unwrap_or_default(),
+            digit_count: digit_count.unwrap_or_default(),
+            other_count: other_count.unwrap_or_default(),
+            joyo_kanji_count: joyo_kanji_count.unwrap_or_default(),
+            jis_level_1_count: jis_level_1_count.unwrap_or_default(),
+            jis_level_2_count: jis_level_2_count.unwrap_or_default(),
+            non_jis_count: non_jis_count.unwrap_or_default(),
+            joyo_kanji_coverage: coverage.joyo_kanji_ratio,
+            jis_level_1_coverage: coverage.jis_level_1_ratio,
+            jis_level_2_coverage: coverage.jis_level_2_ratio,
+            top_characters,
+        })
+    }
+
+    async fn select_sentences(&self, input: SelectSentencesInput) -> Result<Vec<SelectSentenceOutput>> {
+        let connection = &mut self.pool.get()?;
+        // Sample a small share of the rows first so that not every sentence is joined and sorted,
+        // and a larger one if too few sentences match the filters
+        let mut sampled_sentences = vec![];
+        for portion in SENTENCE_SAMPLE_PORTIONS {
+            let ranged_news_ids = Self::select_ranged_news(
+                input.source_name.as_deref(),
+                input.date_from,
+                input.date_to,
+                input.split,
+            )
+            .select(news::id);
+            let mut query = news_sentences::table
+                .tablesample_bernoulli(portion)
+                .inner_join(news)
+                .filter(news_sentences::news_id.eq_any(ranged_news_ids))
+                .into_boxed();
+            if let Some(min_length) = input.min_length {
+                query = query.filter(news_sentences::length.ge(min_length));
+            }
+            if let Some(max_length) = input.max_length {
+                query = query.filter(news_sentences::length.le(max_length));
+            }
+            sampled_sentences = query
+                .order(random())
+                .limit(input.limit)
+                .select((
+                    source_name,
+                    article_id,
+                    news_sentences::start_offset,
+                    news_sentences::length,
+                    news_sentences::text,
+                    news_sentences::difficulty,
+                    news_sentences::max_kanji_grade,
+                ))
+                .get_results::<(String, String, i32, i32, String, Option<f32>, Option<i32>)>(connection)?;
+            if sampled_sentences.len() as i64 >= input.limit {
+                break;
+            }
+        }
+        let outputs = sampled_sentences
+            .into_iter()
+            .map(
+                |(
+                    source_name_value,
+                    article_id_value,
+                    start_offset,
+                    length,
+                    text,
+                    difficulty_value,
+                    max_kanji_grade_value,
+                )| {
+                    SelectSentenceOutput {
+                        source_name: source_name_value,
+                        article_id: article_id_value,
+                        start_offset,
+                        length,
+                        text,
+                        difficulty: difficulty_value,
+                        max_kanji_grade: max_kanji_grade_value,
+                    }
+                },
+            )
+            .collect();
+        Ok(outputs)
+    }
 }
@@ -9,11 +9,23 @@ use chrono::NaiveDate;
 use serde::Deserialize;
 
 use super::{super::state::RouterState, ErrorResponse};
-use crate::execution::cases::{create_news_insight::CreateNewsInsightCaseInput, read_news::ReadNewsCaseInput};
+use crate::execution::{
+    cases::{
+        create_news_insight::CreateNewsInsightCaseInput, read_news::ReadNewsCaseInput,
+        read_news_characters::ReadNewsCharactersCaseInput, read_news_sentences::ReadNewsSentencesCaseInput,
+    },
+    ports::repository::{NewsQuality, NewsSplit, SelectCharacterStatisticsOutput, SelectSentenceOutput},
+};
 
 #[derive(Deserialize)]
 pub(in super::super) struct ReadNewsRequest {
     date: String,
+    quality: Option<String>, // Either `passed` or `flagged`
+    #[serde(default)]
+    deduplicate: bool, // Whether to return only one news per near-duplicate cluster
+    min_difficulty: Option<f32>,
+    max_difficulty: Option<f32>,
+    split: Option<String>, // One of `train`, `validation` and `test`
 }
 
 pub(in super::super) async fn read_news(
@@ -22,9 +34,25 @@ pub(in super::super) async fn read_news(
 ) -> Result<([(HeaderName, &'static str); 2], Body), (StatusCode, Json<ErrorResponse>)> {
     let date = NaiveDate::parse_from_str(&request.date, "%Y-%m-%d")
         .map_err(|e| (StatusCode::BAD_REQUEST, Json(e.to_string().into())))?;
+    let quality = match request.quality.as_deref() {
+        Some("passed") => Some(NewsQuality::Passed),
+        Some("flagged") => Some(NewsQuality::Flagged),
+        Some(quality) => {
+            let message = format!("Unknown quality: {}", quality);
+            return Err((StatusCode::BAD_REQUEST, Json(message.into())));
+        }
+        None => None,
+    };
     let output = state
         .workshop
-        .execute_read_news_case(ReadNewsCaseInput { date })
+        .execute_read_news_case(ReadNewsCaseInput {
+            date,
+            quality,
+            deduplicate: request.deduplicate,
+            min_difficulty: request.min_difficulty,
+            max_difficulty: request.max_difficulty,
+            split: parse_split(request.split)?,
+        })
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string().into())))?;
     let body = Body::from_stream(output.articles_stream);
@@ -35,6 +63,77 @@ pub(in super::super) async fn read_news(
     Ok((headers, body))
 }
 
+#[derive(Deserialize)]
+pub(in super::super) struct ReadNewsCharactersRequest {
+    source_name: Option<String>,
+    from: Option<String>,  // Inclusive
+    to: Option<String>,    // Exclusive
+    split: Option<String>, // One of `train`, `validation` and `test`
+    top: Option<i64>,      // Number of the most frequent characters to return
+}
+
+pub(in super::super) async fn read_news_characters(
+    State(state): State<RouterState>,
+    Query(request): Query<ReadNewsCharactersRequest>,
+) -> Result<Json<SelectCharacterStatisticsOutput>, (StatusCode, Json<ErrorResponse>)> {
+    const DEFAULT_TOP_CHARACTERS_NUM: i64 = 100;
+    let parse_date = |date: Option<String>| {
+        date.map(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d"))
+            .transpose()
+            .map_err(|e| (StatusCode::BAD_REQUEST, Json(e.to_string().into())))
+    };
+    let output = state
+        .workshop
+        .execute_read_news_characters_case(ReadNewsCharactersCaseInput {
+            source_name: request.source_name,
+            date_from: parse_date(request.from)?,
+            date_to: parse_date(request.to)?,
+            split: parse_split(request.split)?,
+            top_characters_num: request.top.unwrap_or(DEFAULT_TOP_CHARACTERS_NUM).max(0),
+        })
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string().into())))?;
+    Ok(Json(output))
+}
+
+#[derive(Deserialize)]
+pub(in super::super) struct ReadNewsSentencesRequest {
+    source_name: Option<String>,
+    from: Option<String>,    // Inclusive
+    to: Option<String>,      // Exclusive
+    min_length: Option<i32>, // In characters, inclusive
+    max_length: Option<i32>, // In characters, inclusive
+    split: Option<String>,   // One of `train`, `validation` and `test`
+    limit: Option<i64>,      // Number of sentences to sample
+}
+
+pub(in super::super) async fn read_news_sentences(
+    State(state): State<RouterState>,
+    Query(request): Query<ReadNewsSentencesRequest>,
+) -> Result<Json<Vec<SelectSentenceOutput>>, (StatusCode, Json<ErrorResponse>)> {
+    const DEFAULT_LIMIT: i64 = 100;
+    const MAX_LIMIT: i64 = 10000;
+    let parse_date = |date: Option<String>| {
+        date.map(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d"))
+            .transpose()
+            .map_err(|e| (StatusCode::BAD_REQUEST, Json(e.to_string().into())))
+    };
+    let output = state
+        .workshop
+        .execute_read_news_sentences_case(ReadNewsSentencesCaseInput {
+            source_name: request.source_name,
+            date_from: parse_date(request.from)?,
+            date_to: parse_date(request.to)?,
+            min_length: request.min_length,
+            max_length: request.max_length,
+            split: parse_split(request.split)?,
+            limit: request.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+        })
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string().into())))?;
+    Ok(Json(output))
+}
+
 #[derive(Deserialize)]
 pub(in super::super) struct CreateNewsInsightRequest {
     source_name: String,
@@ -57,3 +156,16 @@ pub(in super::super) async fn create_news_insight(
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string().into())))?;
     Ok(())
 }
+
+fn parse_split(split: Option<String>) -> Result<Option<NewsSplit>, (StatusCode, Json<ErrorResponse>)> {
+    match split.as_deref() {
+        Some("train") => Ok(Some(NewsSplit::Train)),
+        Some("validation") => Ok(Some(NewsSplit::Validation)),
+        Some("test") => Ok(Some(NewsSplit::Test)),
+        Some(split) => {
+            let message = format!("Unknown split: {}", split);
+            Err((StatusCode::BAD_REQUEST, Json(message.into())))
+        }
+        None => Ok(None),
+    }
+}
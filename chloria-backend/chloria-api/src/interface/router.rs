@@ -9,7 +9,7 @@ use tower::ServiceBuilder;
 use super::{
     adapters::{
         auth::{authenticate, authorize},
-        news::{create_news_insight, read_news},
+        news::{create_news_insight, read_news, read_news_characters, read_news_sentences},
     },
     state::{RouterState, RouterStateJwt},
 };
@@ -34,9 +34,10 @@ pub(crate) fn new(config: RouterConfig, workshop: Workshop) -> Router {
         .with_state(state.clone());
     let authorized_router = Router::new()
         .route("/news", get(read_news))
+        .route("/news/characters", get(read_news_characters))
+        .route("/news/sentences", get(read_news_sentences))
         .route("/news_insight", post(create_news_insight))
         .route_layer(ServiceBuilder::new().layer(middleware::from_fn_with_state(state.clone(), authorize)))
         .with_state(state.clone());
-    let router = Router::new().merge(public_router).merge(authorized_router);
-    router
+    Router::new().merge(public_router).merge(authorized_router)
 }
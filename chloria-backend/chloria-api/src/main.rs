@@ -1,3 +1,4 @@
+mod domain;
 mod execution;
 mod infrastructure;
 mod interface;
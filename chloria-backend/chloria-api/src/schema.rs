@@ -20,6 +20,25 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    dataset_news (dataset_id, news_id) {
+        dataset_id -> Int4,
+        news_id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    datasets (id) {
+        id -> Int4,
+        name -> Text,
+        pinned -> Bool,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     news (id) {
         id -> Int4,
@@ -33,6 +52,108 @@ diesel::table! {
         published_time -> Nullable<Timestamptz>,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        images_expired_at -> Nullable<Timestamptz>,
+        raw_html_path -> Nullable<Text>,
+        raw_feed_path -> Nullable<Text>,
+        extractor_version -> Nullable<Int4>,
+        document -> Nullable<Text>,
+        original_title -> Nullable<Text>,
+        original_short_text -> Nullable<Text>,
+        original_long_text -> Nullable<Text>,
+        quality_reasons -> Array<Text>,
+        simhash -> Nullable<Int8>,
+        simhash_band_0 -> Nullable<Int4>,
+        simhash_band_1 -> Nullable<Int4>,
+        simhash_band_2 -> Nullable<Int4>,
+        simhash_band_3 -> Nullable<Int4>,
+        cluster_id -> Nullable<Int4>,
+        difficulty -> Nullable<Float4>,
+        max_kanji_grade -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    news_characters (news_id, character) {
+        news_id -> Int4,
+        character -> Text,
+        count -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    news_images (id) {
+        id -> Int4,
+        news_id -> Int4,
+        path -> Text,
+        width -> Int4,
+        height -> Int4,
+        mime_type -> Text,
+        byte_size -> Int8,
+        sha256 -> Text,
+        perceptual_hash -> Text,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        position -> Int4,
+        caption -> Nullable<Text>,
+        alt_text -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    news_rubies (id) {
+        id -> Int4,
+        news_id -> Int4,
+        start_offset -> Int4,
+        end_offset -> Int4,
+        reading -> Text,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    news_sentences (id) {
+        id -> Int4,
+        news_id -> Int4,
+        start_offset -> Int4,
+        length -> Int4,
+        text -> Text,
+        difficulty -> Nullable<Float4>,
+        max_kanji_grade -> Nullable<Int4>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    news_splits (id) {
+        id -> Int4,
+        split -> Text,
+        group_key -> Text,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    news_statistics (id) {
+        id -> Int4,
+        character_count -> Int4,
+        kanji_count -> Int4,
+        hiragana_count -> Int4,
+        katakana_count -> Int4,
+        latin_count -> Int4,
+        digit_count -> Int4,
+        other_count -> Int4,
+        joyo_kanji_count -> Int4,
+        jis_level_1_count -> Int4,
+        jis_level_2_count -> Int4,
+        non_jis_count -> Int4,
+        rare_characters -> Text,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
     }
 }
 
@@ -45,7 +166,50 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    synthetic_samples (id) {
+        id -> Int4,
+        news_sentence_id -> Nullable<Int4>,
+        news_id -> Nullable<Int4>,
+        text -> Text,
+        image_path -> Text,
+        width -> Int4,
+        height -> Int4,
+        render_params -> Text,
+        seed -> Int8,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        background_image_path -> Nullable<Text>,
+        annotation -> Nullable<Text>,
+        augmentations -> Nullable<Text>,
+    }
+}
+
 diesel::joinable!(client_credentials -> clients (id));
+diesel::joinable!(dataset_news -> datasets (dataset_id));
+diesel::joinable!(dataset_news -> news (news_id));
+diesel::joinable!(news_characters -> news (news_id));
+diesel::joinable!(news_images -> news (news_id));
 diesel::joinable!(news_insights -> news (id));
+diesel::joinable!(news_rubies -> news (news_id));
+diesel::joinable!(news_sentences -> news (news_id));
+diesel::joinable!(news_splits -> news (id));
+diesel::joinable!(news_statistics -> news (id));
+diesel::joinable!(synthetic_samples -> news (news_id));
+diesel::joinable!(synthetic_samples -> news_sentences (news_sentence_id));
 
-diesel::allow_tables_to_appear_in_same_query!(client_credentials, clients, news, news_insights,);
+diesel::allow_tables_to_appear_in_same_query!(
+    client_credentials,
+    clients,
+    dataset_news,
+    datasets,
+    news,
+    news_characters,
+    news_images,
+    news_insights,
+    news_rubies,
+    news_sentences,
+    news_splits,
+    news_statistics,
+    synthetic_samples,
+);
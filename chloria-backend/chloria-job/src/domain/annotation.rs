@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+
+// Share of the bounding box of a character or a line which must be hidden for it to be flagged as occluded
+const OCCLUDED_AREA_RATIO: f32 = 0.5;
+
+// Corners in pixels, clockwise from the top left corner of the text as it is read,
+// which is the top right corner of the box around a vertical line
+pub(crate) type Polygon = Vec<[f32; 2]>;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ReadingDirection {
+    #[default]
+    LeftToRight,
+    TopToBottom,
+}
+
+// Where the ground-truth text is in a sample image
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct TextAnnotation {
+    pub(crate) lines: Vec<LineAnnotation>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub(crate) struct LineAnnotation {
+    pub(crate) text: String,
+    pub(crate) polygon: Polygon,
+    pub(crate) characters: Vec<CharacterAnnotation>,
+    #[serde(default)]
+    pub(crate) direction: ReadingDirection, // Order of the characters in the line
+    #[serde(default)]
+    pub(crate) occluded: bool, // Its text cannot be fully read from the image
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub(crate) struct CharacterAnnotation {
+    pub(crate) character: char,
+    pub(crate) polygon: Polygon,
+    #[serde(default)]
+    pub(crate) occluded: bool, // Mostly hidden by something drawn over the image
+}
+
+impl TextAnnotation {
+    // Applies the function to every point of every polygon
+    pub(crate) fn map_points(&mut self, f: impl Fn([f32; 2]) -> [f32; 2]) {
+        for line in &mut self.lines {
+            line.polygon.iter_mut().for_each(|p| *p = f(*p));
+            for character in &mut line.characters {
+                character.polygon.iter_mut().for_each(|p| *p = f(*p));
+            }
+        }
+    }
+
+    // Flags what is mostly inside the rectangle, given as `[x_min, y_min, x_max, y_max]` in pixels,
+    // and lines as soon as one of their characters is
+    pub(crate) fn flag_occluded(&mut self, rectangle: [f32; 4]) {
+        for line in &mut self.lines {
+            for character in &mut line.characters {
+                character.occluded |= covered_ratio(&character.polygon, rectangle) > OCCLUDED_AREA_RATIO;
+            }
+            line.occluded |= covered_ratio(&line.polygon, rectangle) > OCCLUDED_AREA_RATIO
+                || line.characters.iter().any(|c| c.occluded);
+        }
+    }
+
+    // Splits every edge of line polygons into the given number of segments, so that they can bend
+    pub(crate) fn densify_lines(&mut self, segments_num: usize) {
+        for line in &mut self.lines {
+            let polygon = &line.polygon;
+            line.polygon = (0..polygon.len())
+                .flat_map(|i| {
+                    let (start, end) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+                    (0..segments_num.max(1)).map(move |j| {
+                        let t = j as f32 / segments_num.max(1) as f32;
+                        [start[0] + (end[0] - start[0]) * t, start[1] + (end[1] - start[1]) * t]
+                    })
+                })
+                .collect();
+        }
+    }
+}
+
+// Axis-aligned rectangle, as a polygon
+pub(crate) fn rectangle(x_min: f32, y_min: f32, x_max: f32, y_max: f32) -> Polygon {
+    vec![[x_min, y_min], [x_max, y_min], [x_max, y_max], [x_min, y_max]]
+}
+
+// Axis-aligned bounding box of the polygon, as `[x_min, y_min, x_max, y_max]`
+pub(crate) fn bounds(polygon: &Polygon) -> [f32; 4] {
+    polygon.iter().fold(
+        [f32::INFINITY, f32::INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY],
+        |[x_min, y_min, x_max, y_max], [x, y]| [x_min.min(*x), y_min.min(*y), x_max.max(*x), y_max.max(*y)],
+    )
+}
+
+// Share of the bounding box of the polygon inside the rectangle, given as `[x_min, y_min, x_max, y_max]`
+fn covered_ratio(polygon: &Polygon, rectangle: [f32; 4]) -> f32 {
+    let [x_min, y_min, x_max, y_max] = bounds(polygon);
+    let area = (x_max - x_min) * (y_max - y_min);
+    if area <= 0.0 {
+        return 0.0;
+    }
+    let width = (x_max.min(rectangle[2]) - x_min.max(rectangle[0])).max(0.0);
+    let height = (y_max.min(rectangle[3]) - y_min.max(rectangle[1])).max(0.0);
+    width * height / area
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bounds, rectangle, CharacterAnnotation, LineAnnotation, ReadingDirection, TextAnnotation};
+
+    #[test]
+    fn map_and_densify_polygons() {
+        let mut annotation = TextAnnotation {
+            lines: vec![LineAnnotation {
+                text: "日本".to_string(),
+                polygon: rectangle(0.0, 0.0, 20.0, 10.0),
+                characters: vec![
+                    CharacterAnnotation {
+                        character: '日',
+                        polygon: rectangle(0.0, 0.0, 10.0, 10.0),
+                        occluded: false,
+                    },
+                    CharacterAnnotation {
+                        character: '本',
+                        polygon: rectangle(10.0, 0.0, 20.0, 10.0),
+                        occluded: false,
+                    },
+                ],
+                direction: ReadingDirection::LeftToRight,
+                occluded: false,
+            }],
+        };
+        assert_eq!(bounds(&annotation.lines[0].polygon), [0.0, 0.0, 20.0, 10.0]);
+        annotation.map_points(|[x, y]| [x * 2.0 + 1.0, y + 5.0]);
+        assert_eq!(annotation.lines[0].polygon, rectangle(1.0, 5.0, 41.0, 15.0));
+        assert_eq!(
+            annotation.lines[0].characters[1].polygon,
+            rectangle(21.0, 5.0, 41.0, 15.0)
+        );
+        annotation.densify_lines(2);
+        assert_eq!(
+            annotation.lines[0].polygon,
+            [
+                [1.0, 5.0],
+                [21.0, 5.0],
+                [41.0, 5.0],
+                [41.0, 10.0],
+                [41.0, 15.0],
+                [21.0, 15.0],
+                [1.0, 15.0],
+                [1.0, 10.0]
+            ]
+        );
+        // Characters keep their corners
+        assert_eq!(annotation.lines[0].characters[0].polygon.len(), 4);
+        // Only the second character is mostly hidden, which is enough for the line
+        annotation.flag_occluded([0.0, 0.0, 13.0, 12.0]);
+        assert!(!annotation.lines[0].occluded);
+        annotation.flag_occluded([24.0, 0.0, 50.0, 20.0]);
+        assert!(!annotation.lines[0].characters[0].occluded);
+        assert!(annotation.lines[0].characters[1].occluded);
+        assert!(annotation.lines[0].occluded);
+    }
+}
@@ -0,0 +1,403 @@
+use std::f32::consts::PI;
+
+use anyhow::Result;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{annotation::TextAnnotation, synthesis::check_range};
+
+// Points added along each edge of line polygons before curving them, so that they follow the curve
+const CURVE_EDGE_SEGMENTS_NUM: usize = 8;
+
+// One step of the pipeline, with everything needed to apply it again the same way
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum Augmentation {
+    // Corners moved towards the inside, clockwise from the top left, relative to the image size
+    Perspective {
+        offsets: [[f32; 2]; 4],
+    },
+    // Around the center of the image, which grows to keep its corners
+    Rotation {
+        angle: f32,
+    }, // In degrees, clockwise
+    // Rows shifted along a sine wave, the image growing to keep them
+    Curve {
+        amplitude: f32, // Relative to the image height
+        period: f32,    // Relative to the image width
+        phase: f32,     // In radians
+    },
+    // Rectangle of a single color put over the image
+    Occlusion {
+        rectangle: [f32; 4], // Left, top, width and height, relative to the image size
+        color: [u8; 3],
+    },
+    // Brightness changing linearly across the image
+    Lighting {
+        angle: f32,    // In degrees, clockwise from the right, towards where the image gets brighter
+        strength: f32, // Brightness is multiplied by `1 - strength` on one side and by `1 + strength` on the other
+    },
+    GaussianBlur {
+        sigma: f32,
+    }, // In pixels
+    MotionBlur {
+        length: f32,
+        angle: f32,
+    }, // In pixels and degrees, clockwise from the right
+    Noise {
+        sigma: f32,
+        seed: u64,
+    }, // Standard deviation of the noise added to each channel, from 0 to 255
+    Jpeg {
+        quality: u8,
+    }, // From 1 to 100
+}
+
+// Probabilities of each augmentation, and inclusive ranges which their parameters are drawn uniformly from
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub(crate) struct AugmentationConfig {
+    pub(crate) perspective_probability: f32,
+    pub(crate) perspective_offsets: [f32; 2],
+    pub(crate) rotation_probability: f32,
+    pub(crate) rotation_angles: [f32; 2],
+    pub(crate) curve_probability: f32,
+    pub(crate) curve_amplitudes: [f32; 2],
+    pub(crate) curve_periods: [f32; 2],
+    pub(crate) occlusion_probability: f32,
+    pub(crate) occlusion_sizes: [f32; 2], // Relative to the image size
+    pub(crate) lighting_probability: f32,
+    pub(crate) lighting_strengths: [f32; 2],
+    pub(crate) gaussian_blur_probability: f32,
+    pub(crate) gaussian_blur_sigmas: [f32; 2],
+    pub(crate) motion_blur_probability: f32,
+    pub(crate) motion_blur_lengths: [f32; 2],
+    pub(crate) noise_probability: f32,
+    pub(crate) noise_sigmas: [f32; 2],
+    pub(crate) jpeg_probability: f32,
+    pub(crate) jpeg_qualities: [u8; 2],
+}
+
+impl Default for AugmentationConfig {
+    fn default() -> Self {
+        Self {
+            perspective_probability: 0.3,
+            perspective_offsets: [0.0, 0.1],
+            rotation_probability: 0.3,
+            rotation_angles: [-8.0, 8.0],
+            curve_probability: 0.2,
+            curve_amplitudes: [0.02, 0.1],
+            curve_periods: [0.8, 2.0],
+            occlusion_probability: 0.1,
+            occlusion_sizes: [0.05, 0.2],
+            lighting_probability: 0.3,
+            lighting_strengths: [0.1, 0.4],
+            gaussian_blur_probability: 0.3,
+            gaussian_blur_sigmas: [0.3, 1.5],
+            motion_blur_probability: 0.2,
+            motion_blur_lengths: [2.0, 8.0],
+            noise_probability: 0.3,
+            noise_sigmas: [2.0, 12.0],
+            jpeg_probability: 0.3,
+            jpeg_qualities: [20, 80],
+        }
+    }
+}
+
+impl AugmentationConfig {
+    pub(crate) fn validate(&self) -> Result<()> {
+        check_range("perspective_offsets", self.perspective_offsets)?;
+        check_range("rotation_angles", self.rotation_angles)?;
+        check_range("curve_amplitudes", self.curve_amplitudes)?;
+        check_range("curve_periods", self.curve_periods)?;
+        check_range("occlusion_sizes", self.occlusion_sizes)?;
+        check_range("lighting_strengths", self.lighting_strengths)?;
+        check_range("gaussian_blur_sigmas", self.gaussian_blur_sigmas)?;
+        check_range("motion_blur_lengths", self.motion_blur_lengths)?;
+        check_range("noise_sigmas", self.noise_sigmas)?;
+        check_range("jpeg_qualities", self.jpeg_qualities)
+    }
+
+    // Geometric augmentations come first, then the ones which happen when the photo is taken and saved
+    pub(crate) fn sample(&self, rng: &mut impl Rng) -> Vec<Augmentation> {
+        let mut augmentations = vec![];
+        if rng.random::<f32>() < self.perspective_probability {
+            let mut offset = || rng.random_range(self.perspective_offsets[0]..=self.perspective_offsets[1]);
+            augmentations.push(Augmentation::Perspective {
+                offsets: [
+                    [offset(), offset()],
+                    [-offset(), offset()],
+                    [-offset(), -offset()],
+                    [offset(), -offset()],
+                ],
+            });
+        }
+        if rng.random::<f32>() < self.rotation_probability {
+            augmentations.push(Augmentation::Rotation {
+                angle: rng.random_range(self.rotation_angles[0]..=self.rotation_angles[1]),
+            });
+        }
+        if rng.random::<f32>() < self.curve_probability {
+            augmentations.push(Augmentation::Curve {
+                amplitude: rng.random_range(self.curve_amplitudes[0]..=self.curve_amplitudes[1]),
+                period: rng.random_range(self.curve_periods[0]..=self.curve_periods[1]),
+                phase: rng.random_range(0.0..2.0 * PI),
+            });
+        }
+        if rng.random::<f32>() < self.occlusion_probability {
+            let (width, height) = (
+                rng.random_range(self.occlusion_sizes[0]..=self.occlusion_sizes[1]),
+                rng.random_range(self.occlusion_sizes[0]..=self.occlusion_sizes[1]),
+            );
+            augmentations.push(Augmentation::Occlusion {
+                rectangle: [
+                    rng.random_range(0.0..=1.0 - width),
+                    rng.random_range(0.0..=1.0 - height),
+                    width,
+                    height,
+                ],
+                color: rng.random(),
+            });
+        }
+        if rng.random::<f32>() < self.lighting_probability {
+            augmentations.push(Augmentation::Lighting {
+                angle: rng.random_range(0.0..360.0),
+                strength: rng.random_range(self.lighting_strengths[0]..=self.lighting_strengths[1]),
+            });
+        }
+        if rng.random::<f32>() < self.gaussian_blur_probability {
+            augmentations.push(Augmentation::GaussianBlur {
+                sigma: rng.random_range(self.gaussian_blur_sigmas[0]..=self.gaussian_blur_sigmas[1]),
+            });
+        }
+        if rng.random::<f32>() < self.motion_blur_probability {
+            augmentations.push(Augmentation::MotionBlur {
+                length: rng.random_range(self.motion_blur_lengths[0]..=self.motion_blur_lengths[1]),
+                angle: rng.random_range(0.0..180.0),
+            });
+        }
+        if rng.random::<f32>() < self.noise_probability {
+            augmentations.push(Augmentation::Noise {
+                sigma: rng.random_range(self.noise_sigmas[0]..=self.noise_sigmas[1]),
+                seed: rng.random(),
+            });
+        }
+        if rng.random::<f32>() < self.jpeg_probability {
+            augmentations.push(Augmentation::Jpeg {
+                quality: rng.random_range(self.jpeg_qualities[0]..=self.jpeg_qualities[1]),
+            });
+        }
+        augmentations
+    }
+}
+
+impl Augmentation {
+    // Whether it moves pixels, and therefore polygons
+    pub(crate) fn is_geometric(&self) -> bool {
+        matches!(
+            self,
+            Self::Perspective { .. } | Self::Rotation { .. } | Self::Curve { .. }
+        )
+    }
+
+    // Size of the image after the augmentation, from its size before it
+    pub(crate) fn output_size(&self, size: [u32; 2]) -> [u32; 2] {
+        let [width, height] = size.map(|s| s as f32);
+        match self {
+            Self::Rotation { angle } => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                [
+                    (width * cos.abs() + height * sin.abs()).ceil() as u32,
+                    (width * sin.abs() + height * cos.abs()).ceil() as u32,
+                ]
+            }
+            Self::Curve { amplitude, .. } => [size[0], (height + 2.0 * amplitude * height).ceil() as u32],
+            _ => size,
+        }
+    }
+
+    // Where a point of the image before the augmentation ends up after it, given the size before it
+    pub(crate) fn map_point(&self, point: [f32; 2], size: [u32; 2]) -> [f32; 2] {
+        let [width, height] = size.map(|s| s as f32);
+        match self {
+            Self::Perspective { offsets } => {
+                let corners = corners(width, height);
+                let moved_corners = moved_corners(offsets, width, height);
+                project(&homography(&corners, &moved_corners), point)
+            }
+            Self::Rotation { angle } => {
+                let [output_width, output_height] = self.output_size(size).map(|s| s as f32);
+                rotate(
+                    point,
+                    [width / 2.0, height / 2.0],
+                    [output_width / 2.0, output_height / 2.0],
+                    *angle,
+                )
+            }
+            Self::Curve { .. } => [point[0], point[1] + self.curve_shift(point[0], width, height)],
+            _ => point,
+        }
+    }
+
+    // Where a point of the image after the augmentation comes from, the inverse of `map_point`
+    pub(crate) fn inverse_map_point(&self, point: [f32; 2], size: [u32; 2]) -> [f32; 2] {
+        let [width, height] = size.map(|s| s as f32);
+        match self {
+            Self::Perspective { offsets } => {
+                let corners = corners(width, height);
+                let moved_corners = moved_corners(offsets, width, height);
+                project(&homography(&moved_corners, &corners), point)
+            }
+            Self::Rotation { angle } => {
+                let [output_width, output_height] = self.output_size(size).map(|s| s as f32);
+                rotate(
+                    point,
+                    [output_width / 2.0, output_height / 2.0],
+                    [width / 2.0, height / 2.0],
+                    -angle,
+                )
+            }
+            Self::Curve { .. } => [point[0], point[1] - self.curve_shift(point[0], width, height)],
+            _ => point,
+        }
+    }
+
+    fn curve_shift(&self, x: f32, width: f32, height: f32) -> f32 {
+        let Self::Curve {
+            amplitude,
+            period,
+            phase,
+        } = self
+        else {
+            return 0.0;
+        };
+        let amplitude = amplitude * height;
+        amplitude + amplitude * (2.0 * PI * x / (period * width).max(1.0) + phase).sin()
+    }
+}
+
+// Moves the polygons of the annotation along with the pixels and flags what gets occluded,
+// returning the size of the augmented image
+pub(crate) fn transform_annotation(
+    annotation: &mut TextAnnotation,
+    augmentations: &[Augmentation],
+    size: [u32; 2],
+) -> [u32; 2] {
+    if augmentations.iter().any(|a| matches!(a, Augmentation::Curve { .. })) {
+        annotation.densify_lines(CURVE_EDGE_SEGMENTS_NUM);
+    }
+    augmentations.iter().fold(size, |size, augmentation| {
+        if augmentation.is_geometric() {
+            annotation.map_points(|point| augmentation.map_point(point, size));
+        }
+        if let Augmentation::Occlusion { rectangle, .. } = augmentation {
+            let [width, height] = size.map(|s| s as f32);
+            annotation.flag_occluded([
+                rectangle[0] * width,
+                rectangle[1] * height,
+                (rectangle[0] + rectangle[2]) * width,
+                (rectangle[1] + rectangle[3]) * height,
+            ]);
+        }
+        augmentation.output_size(size)
+    })
+}
+
+fn corners(width: f32, height: f32) -> [[f32; 2]; 4] {
+    [[0.0, 0.0], [width, 0.0], [width, height], [0.0, height]]
+}
+
+fn moved_corners(offsets: &[[f32; 2]; 4], width: f32, height: f32) -> [[f32; 2]; 4] {
+    let mut corners = corners(width, height);
+    for (corner, offset) in corners.iter_mut().zip(offsets) {
+        *corner = [corner[0] + offset[0] * width, corner[1] + offset[1] * height];
+    }
+    corners
+}
+
+// Rotates the point around the center, then moves the center to the new one
+fn rotate(point: [f32; 2], center: [f32; 2], new_center: [f32; 2], angle: f32) -> [f32; 2] {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let (dx, dy) = (point[0] - center[0], point[1] - center[1]);
+    [new_center[0] + dx * cos - dy * sin, new_center[1] + dx * sin + dy * cos]
+}
+
+// Matrix of the projective transformation mapping the source corners to the destination ones, row by row,
+// solved with Gaussian elimination, or the identity if the corners are degenerate
+// Ref: https://en.wikipedia.org/wiki/Homography#Mathematical_definition
+fn homography(source: &[[f32; 2]; 4], destination: &[[f32; 2]; 4]) -> [f64; 9] {
+    let mut rows = [[0.0f64; 9]; 8];
+    for (i, (s, d)) in source.iter().zip(destination).enumerate() {
+        let ([x, y], [u, v]) = (s.map(|c| c as f64), d.map(|c| c as f64));
+        rows[2 * i] = [x, y, 1.0, 0.0, 0.0, 0.0, -u * x, -u * y, u];
+        rows[2 * i + 1] = [0.0, 0.0, 0.0, x, y, 1.0, -v * x, -v * y, v];
+    }
+    for column in 0..8 {
+        let pivot = (column..8)
+            .max_by(|a, b| rows[*a][column].abs().total_cmp(&rows[*b][column].abs()))
+            .unwrap_or(column);
+        if rows[pivot][column].abs() < 1e-12 {
+            return [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+        }
+        rows.swap(column, pivot);
+        let pivot_row = rows[column];
+        for (i, row) in rows.iter_mut().enumerate() {
+            if i != column {
+                let factor = row[column] / pivot_row[column];
+                for (value, pivot_value) in row.iter_mut().zip(pivot_row).skip(column) {
+                    *value -= factor * pivot_value;
+                }
+            }
+        }
+    }
+    let h: Vec<f64> = (0..8).map(|i| rows[i][8] / rows[i][i]).collect();
+    [h[0], h[1], h[2], h[3], h[4], h[5], h[6], h[7], 1.0]
+}
+
+fn project(matrix: &[f64; 9], point: [f32; 2]) -> [f32; 2] {
+    let [x, y] = point.map(|c| c as f64);
+    let w = matrix[6] * x + matrix[7] * y + matrix[8];
+    [
+        ((matrix[0] * x + matrix[1] * y + matrix[2]) / w) as f32,
+        ((matrix[3] * x + matrix[4] * y + matrix[5]) / w) as f32,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{Augmentation, AugmentationConfig};
+
+    #[test]
+    fn sample_and_map_points() {
+        let config = AugmentationConfig::default();
+        assert_eq!(
+            config.sample(&mut StdRng::seed_from_u64(1)),
+            config.sample(&mut StdRng::seed_from_u64(1))
+        );
+        let augmentations = [
+            Augmentation::Perspective {
+                offsets: [[0.1, 0.05], [-0.02, 0.1], [-0.1, -0.1], [0.05, -0.02]],
+            },
+            Augmentation::Rotation { angle: 30.0 },
+            Augmentation::Curve {
+                amplitude: 0.1,
+                period: 1.5,
+                phase: 1.0,
+            },
+        ];
+        let size = [200, 100];
+        for augmentation in &augmentations {
+            for point in [[0.0, 0.0], [37.0, 81.0], [200.0, 100.0]] {
+                let mapped = augmentation.map_point(point, size);
+                let [x, y] = augmentation.inverse_map_point(mapped, size);
+                assert!((x - point[0]).abs() < 1e-2 && (y - point[1]).abs() < 1e-2);
+            }
+        }
+        assert_eq!(
+            augmentations[0].map_point([200.0, 100.0], size).map(|c| c.round()),
+            [180.0, 90.0]
+        );
+    }
+}
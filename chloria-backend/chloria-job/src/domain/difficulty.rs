@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+
+use super::statistics::{Script, JOYO_KANJI_GRADES};
+
+// Grade given to kanji outside the Jōyō list, above the secondary school grade
+pub(crate) const NON_JOYO_KANJI_GRADE: i32 = 10;
+
+// JLPT has published no official kanji list since 2010, so the bundled lists are the unofficial ones commonly used
+// (Ref: http://www.tanos.co.uk/jlpt/), where N1 takes the rest of the Jōyō kanji, and they can be completed by a table
+const JLPT_N5_KANJI: &str = "\
+一七万三上下中九二五人今休会何先入八六円出分前北十千午半南友口古右名四国土外多大天女子学安小少山川左年店後手新日時\
+書月木本来東校母毎気水火父生男白百目社空立耳聞花行西見言話語読買足車週道金長間雨電食飲駅高魚";
+const JLPT_N4_KANJI: &str = "\
+悪安暗医意以引院員運英映遠屋音歌夏家画海回開界楽館漢寒顔帰起究急牛去強教京業近銀区計兄軽犬研県建験元工広考光好\
+合黒菜作産紙思姉止市仕死使始試私字自事持室質写者借弱首主秋集習終住重春所暑場乗色森心親真進図青正声世赤夕切説洗\
+早走送足族村体待貸台代題短知地池茶着昼注町鳥朝通弟低転田都度答冬頭同動堂働特肉売発飯病品不風服物文別勉便歩方妹\
+味民明門問夜野薬有曜用洋理旅料力林";
+const JLPT_N3_KANJI: &str = "\
+政議民連対部合市内相定回選米実関決全表戦経最現調化当約首法性要制治務成期取都和機平加受続進数記初指権支産点報済\
+活原共得解交資予向際勝面告反判認参利組信在件側任引求所次昨論官増係感情投示変打直両式確果容必演歳争談能位置流格\
+疑過局放常状球職与供役構割費付由説難優夫収断石違消神番規術備宅害配警育席訪乗残想声念助労例然限追商葉伝働形景落\
+好退頭負渡失差末守若種美命福望非観察段横深申様財港識呼達良候程満敗値突光路科積他処太客否師登易速存飛殺号単座破\
+除完降責捕危給苦迎園具辞因馬愛富彼未舞亡冷適婦寄込顔類余王返妻背熱宿薬険頼覚船途許抜便留罪努精散静婚喜浮絶幸押\
+倒等老曲払庭徒勤遅居雑招困欠更刻賛抱犯恐息遠戻願絵越欲痛笑互束似列探逃遊迷夢君閉緒折草暮酒悲晴掛到寝暗盗吸陽御\
+歯忘雪吹娘誤洗慣礼窓昔貧怒泳祖杯疲皆鳴腹煙眠怖耳頂箱晩寒髪忙才靴恥偶偉猫幾";
+const JLPT_N2_KANJI: &str = "\
+党協総区領県設改府査委軍団各島革村勢減再税営比防補境導副算輸述線農州武象域額欧担準賞辺造被技低復移個門課脳極含\
+蔵量型況針専谷史階管兵接細効丸湾録省旧橋岸周材戸央券編捜竹超並療採森競介根販歴将幅般貿講林装諸劇河航鉄児禁印逆\
+換久短油暴輪占植清倍均億圧芸署伸停爆陸玉波帯延羽固則乱普測豊厚齢囲卒略承順岩練軽了庁城患層版令角絡損募裏仏績築\
+貨混昇池血温季星永著誌庫刊像香坂底布寺宇巨震希触依籍汚枚複郵仲栄札板骨傾届巻燃跡包駐弱紹雇替預焼簡章臓律贈照薄\
+群秒奥詰双刺純翌快片敬悩泉皮漁荒貯硬埋柱祭袋筆訓浴童宝封胸砂塩賢腕兆床毛緑尊祝柔殿濃液衣肩零幼荷泊黄甘臣浅掃雲\
+掘捨軟沈凍乳恋紅郊腰炭踊冊勇械菜珍卵湖喫干虫刷湯溶鉱涙匹孫鋭枝塗軒毒叫拝氷乾棒祈拾粉糸綿汗銅湿瓶咲召缶隻脂蒸肌\
+耕鈍泥隅灯辛磨麦姓筒鼻粒詞胃畳机膚濯塔沸灰菓帽枯涼舟貝符憎皿肯燥畜挟曇滴伺";
+
+#[derive(Clone)]
+pub(crate) struct DifficultyScorer {
+    jlpt_levels: HashMap<char, i32>, // JLPT level of each kanji, from 5 for N5 to 1 for N1
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Difficulty {
+    pub(crate) score: f32, // From 0 for text without kanji to 1 for text only made of kanji outside the Jōyō list
+    pub(crate) max_kanji_grade: Option<i32>, // Highest school grade of the kanji, if there are any
+}
+
+impl Default for DifficultyScorer {
+    fn default() -> Self {
+        Self {
+            // Kanji listed at several levels keep the easiest one
+            jlpt_levels: JOYO_KANJI_GRADES
+                .keys()
+                .map(|c| (*c, 1))
+                .chain(JLPT_N2_KANJI.chars().map(|c| (c, 2)))
+                .chain(JLPT_N3_KANJI.chars().map(|c| (c, 3)))
+                .chain(JLPT_N4_KANJI.chars().map(|c| (c, 4)))
+                .chain(JLPT_N5_KANJI.chars().map(|c| (c, 5)))
+                .collect(),
+        }
+    }
+}
+
+impl DifficultyScorer {
+    // One kanji per line, with its level (e.g. `3` for N3) separated by a tab, overriding the bundled lists
+    pub(crate) fn with_jlpt_table(table: &str) -> Result<Self> {
+        let mut scorer = Self::default();
+        for line in table.lines().filter(|l| !l.trim().is_empty()) {
+            let Some((kanji, level)) = line.split_once('\t') else {
+                bail!("Invalid JLPT table line: {}", line);
+            };
+            let mut kanji = kanji.chars();
+            let (Some(kanji), None, Ok(level @ 1..=5)) = (kanji.next(), kanji.next(), level.trim().parse()) else {
+                bail!("Invalid JLPT table line: {}", line);
+            };
+            scorer.jlpt_levels.insert(kanji, level);
+        }
+        Ok(scorer)
+    }
+
+    // Each kanji weighs by how late it is learned, either at school or for the JLPT, and other characters weigh nothing
+    // Returns nothing if the text has no characters (whitespace excluded)
+    pub(crate) fn score(&self, text: &str) -> Option<Difficulty> {
+        let mut character_count = 0;
+        let mut total_weight = 0.0;
+        let mut max_kanji_grade = None;
+        for c in text.chars().filter(|c| !c.is_whitespace()) {
+            character_count += 1;
+            if Script::of(c) != Script::Kanji {
+                continue;
+            }
+            let grade = JOYO_KANJI_GRADES.get(&c).copied().unwrap_or(NON_JOYO_KANJI_GRADE);
+            let grade_weight = grade as f32 / NON_JOYO_KANJI_GRADE as f32;
+            let weight = match self.jlpt_levels.get(&c) {
+                // From 0.1 for N5 to 0.9 for N1
+                Some(level) => grade_weight.min((5 - level) as f32 * 0.2 + 0.1),
+                None => grade_weight,
+            };
+            total_weight += weight;
+            max_kanji_grade = max_kanji_grade.max(Some(grade));
+        }
+        match character_count {
+            0 => None,
+            _ => Some(Difficulty {
+                score: total_weight / character_count as f32,
+                max_kanji_grade,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DifficultyScorer, JLPT_N5_KANJI};
+    use crate::domain::statistics::JOYO_KANJI_GRADES;
+
+    #[test]
+    fn score_difficulty() {
+        assert_eq!(JLPT_N5_KANJI.chars().count(), 103);
+        let scorer = DifficultyScorer::default();
+        assert!(JOYO_KANJI_GRADES.keys().all(|c| scorer.jlpt_levels.contains_key(c)));
+        assert_eq!(
+            ['日', '悪', '政', '党', '璽'].map(|c| scorer.jlpt_levels[&c]),
+            [5, 4, 3, 2, 1]
+        );
+        for table in ["経 3", "経済\t3", "経\tN3", "経\t0", "経\t6"] {
+            assert!(DifficultyScorer::with_jlpt_table(table).is_err());
+        }
+        let scorer = DifficultyScorer::with_jlpt_table("経\t5\n\n蕎\t1\n").unwrap();
+        assert_eq!(scorer.jlpt_levels[&'経'], 5);
+        let easy = scorer.score("今日は雨です。").unwrap();
+        let hard = scorer.score("経済の先行きに懸念が広がる。").unwrap();
+        assert_eq!(easy.max_kanji_grade, Some(2));
+        assert_eq!(hard.max_kanji_grade, Some(8));
+        assert_eq!(scorer.score("蕎麦").unwrap().max_kanji_grade, Some(10));
+        assert!(easy.score < hard.score);
+        assert_eq!(scorer.score("ひらがな").unwrap().score, 0.0);
+        assert_eq!(scorer.score(" "), None);
+    }
+}
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+use super::text::TextNormalizer;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum DocumentBlockKind {
+    Heading,
+    Paragraph,
+    ListItem,
+    Quote,
+    Caption,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub(crate) enum DocumentSpanStyle {
+    Bold,
+    Italic,
+    Link { href: String },
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct DocumentSpan {
+    pub(crate) style: DocumentSpanStyle,
+    pub(crate) start: usize, // Offset of the first character in the text of the block, counted in characters
+    pub(crate) end: usize,   // Offset right after the last character, counted in characters
+}
+
+// Reading of a part of the text (e.g. furigana of `<ruby>` elements), which is not included in the text itself
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct DocumentRuby {
+    pub(crate) start: usize, // Offset of the first character of the base text, counted in characters
+    pub(crate) end: usize,   // Offset right after the last character of the base text, counted in characters
+    pub(crate) reading: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct DocumentBlock {
+    pub(crate) kind: DocumentBlockKind,
+    pub(crate) text: String, // Plain text without any markup, lines are separated by `\n`
+    pub(crate) spans: Vec<DocumentSpan>, // Inline styles applied to parts of the text
+    #[serde(default)]
+    pub(crate) rubies: Vec<DocumentRuby>, // Readings of parts of the text
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct DocumentEntity {
+    pub(crate) blocks: Vec<DocumentBlock>, // In order of appearance
+}
+
+impl DocumentEntity {
+    // Text of every block, one block per line
+    pub(crate) fn plain_text(&self) -> String {
+        self.blocks
+            .iter()
+            .map(|b| b.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // Normalize the text of every block, moving styles and readings along with it
+    pub(crate) fn normalize(self, normalizer: &TextNormalizer) -> Self {
+        let mut blocks = vec![];
+        for mut block in self.blocks {
+            let normalized = normalizer.normalize(&block.text);
+            for span in &mut block.spans {
+                span.start = normalized.offsets[span.start];
+                span.end = normalized.offsets[span.end];
+            }
+            block.spans.retain(|s| s.end > s.start);
+            for ruby in &mut block.rubies {
+                ruby.start = normalized.offsets[ruby.start];
+                ruby.end = normalized.offsets[ruby.end];
+                ruby.reading = normalizer.normalize(&ruby.reading).text;
+            }
+            block.rubies.retain(|r| r.end > r.start);
+            block.text = normalized.text;
+            if !block.text.is_empty() {
+                blocks.push(block);
+            }
+        }
+        Self { blocks }
+    }
+
+    // Readings of every block, with offsets counted from the start of the plain text
+    pub(crate) fn plain_text_rubies(&self) -> Vec<DocumentRuby> {
+        let mut rubies = vec![];
+        let mut block_offset = 0;
+        for block in &self.blocks {
+            for ruby in &block.rubies {
+                rubies.push(DocumentRuby {
+                    start: block_offset + ruby.start,
+                    end: block_offset + ruby.end,
+                    reading: ruby.reading.clone(),
+                });
+            }
+            block_offset += block.text.chars().count() + 1; // Including the line break between blocks
+        }
+        rubies
+    }
+}
@@ -0,0 +1,314 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::{
+    annotation::{bounds, rectangle, LineAnnotation, Polygon, TextAnnotation},
+    split::Split,
+};
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SampleKind {
+    Synthetic, // Rendered by us, with polygons of every line and character
+    Real,      // Representative images of news, with the text provided by their insights
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum AnnotationFormat {
+    CocoText,          // Ref: https://bgshih.github.io/cocotext/
+    Icdar2015,         // Ref: https://rrc.cvc.uab.es/?ch=4&com=tasks
+    PaddleDetection,   // Ref: https://github.com/PaddlePaddle/PaddleOCR/blob/main/docs/datasets/ocr_datasets.en.md
+    PaddleRecognition, // Same as the recognition list, but with one cropped image per line
+    RecognitionList,   // One whole image per row, followed by its text after a tab
+}
+
+// Text in the representative image of a news, as written in the fields of its insight
+#[derive(Deserialize)]
+pub(crate) struct ImageTextInsight {
+    pub(crate) image_text: String, // Lines separated by line breaks
+    #[serde(default)]
+    pub(crate) image_text_polygons: Vec<Polygon>, // One per line in pixels of the image, if known
+}
+
+impl ImageTextInsight {
+    // Returns nothing if the fields are not a JSON object with the text of the image
+    pub(crate) fn parse(fields: &str) -> Option<Self> {
+        serde_json::from_str::<Self>(fields)
+            .ok()
+            .filter(|i| !i.image_text.trim().is_empty())
+    }
+
+    // Lines are only located if there is a polygon for each of them
+    pub(crate) fn annotation(&self) -> TextAnnotation {
+        let lines: Vec<&str> = self.image_text.lines().collect();
+        if lines.len() != self.image_text_polygons.len() {
+            return TextAnnotation::default();
+        }
+        TextAnnotation {
+            lines: lines
+                .into_iter()
+                .zip(&self.image_text_polygons)
+                .map(|(text, polygon)| LineAnnotation {
+                    text: text.to_string(),
+                    polygon: polygon.clone(),
+                    characters: vec![],
+                    direction: Default::default(),
+                    occluded: false,
+                })
+                .collect(),
+        }
+    }
+}
+
+pub(crate) struct ExportedSample {
+    pub(crate) name: String,       // Unique in the export, e.g. `synthetic_42`
+    pub(crate) image_file: String, // Relative to the root of the export
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) text: String, // With line breaks between lines
+    pub(crate) annotation: TextAnnotation,
+    pub(crate) split: Option<Split>, // Of the news the text comes from, if assigned
+}
+
+// Line cut out of a sample, for recognition
+pub(crate) struct ExportedCrop {
+    pub(crate) image_file: String,
+    pub(crate) text: String,
+}
+
+impl AnnotationFormat {
+    // Samples whose lines are not located cannot be used for detection
+    pub(crate) fn needs_polygons(&self) -> bool {
+        !matches!(self, Self::RecognitionList)
+    }
+
+    pub(crate) fn needs_crops(&self) -> bool {
+        matches!(self, Self::PaddleRecognition)
+    }
+
+    // Whole images are labeled with their text, which must then be readable everywhere
+    pub(crate) fn needs_legible_text(&self) -> bool {
+        matches!(self, Self::RecognitionList)
+    }
+
+    // Label files to write at the root of the export, with their content
+    pub(crate) fn label_files(&self, samples: &[ExportedSample], crops: &[ExportedCrop]) -> Vec<(String, String)> {
+        match self {
+            Self::CocoText => vec![("cocotext.json".to_string(), coco_text(samples).to_string())],
+            Self::Icdar2015 => samples
+                .iter()
+                .map(|s| (format!("labels/gt_{}.txt", s.name), icdar2015_label(&s.annotation)))
+                .collect(),
+            Self::PaddleDetection => {
+                let rows: Vec<String> = samples.iter().map(paddle_detection_row).collect();
+                vec![("det_label.txt".to_string(), rows.concat())]
+            }
+            Self::PaddleRecognition => {
+                let rows: Vec<String> = crops.iter().map(|c| recognition_row(&c.image_file, &c.text)).collect();
+                vec![("rec_label.txt".to_string(), rows.concat())]
+            }
+            Self::RecognitionList => {
+                let rows: Vec<String> = samples
+                    .iter()
+                    .map(|s| recognition_row(&s.image_file, &s.text))
+                    .collect();
+                vec![("labels.tsv".to_string(), rows.concat())]
+            }
+        }
+    }
+}
+
+// Lines are annotated as texts, the way COCO-Text annotates words
+fn coco_text(samples: &[ExportedSample]) -> Value {
+    let (mut imgs, mut anns, mut img_to_anns) = (Map::new(), Map::new(), Map::new());
+    let mut ann_id = 0;
+    for (i, sample) in samples.iter().enumerate() {
+        let image_id = i + 1;
+        imgs.insert(
+            image_id.to_string(),
+            json!({
+                "id": image_id,
+                "file_name": sample.image_file,
+                "width": sample.width,
+                "height": sample.height,
+                // COCO-Text names its validation set `val`, and samples without a split are left to the reader
+                "set": match sample.split {
+                    Some(Split::Train) => "train",
+                    Some(Split::Validation) => "val",
+                    Some(Split::Test) => "test",
+                    None => "unassigned",
+                },
+            }),
+        );
+        let mut ann_ids = vec![];
+        for line in &sample.annotation.lines {
+            ann_id += 1;
+            let [x_min, y_min, x_max, y_max] = bounds(&line.polygon);
+            anns.insert(
+                ann_id.to_string(),
+                json!({
+                    "id": ann_id,
+                    "image_id": image_id,
+                    "bbox": [x_min, y_min, x_max - x_min, y_max - y_min],
+                    "mask": line.polygon.concat(),
+                    "area": area(&line.polygon),
+                    "utf8_string": line.text,
+                    "legibility": match line.occluded {
+                        true => "illegible",
+                        false => "legible",
+                    },
+                    "class": "machine printed",
+                    "language": match line.text.is_ascii() {
+                        true => "english",
+                        false => "not english",
+                    },
+                }),
+            );
+            ann_ids.push(ann_id);
+        }
+        img_to_anns.insert(image_id.to_string(), json!(ann_ids));
+    }
+    json!({
+        "info": {"description": "Exported by Chloria"},
+        "cats": {},
+        "imgs": imgs,
+        "anns": anns,
+        "imgToAnns": img_to_anns,
+    })
+}
+
+// Quadrilaterals clockwise from the top left corner, replaced by their bounding boxes for polygons of other shapes
+fn icdar2015_label(annotation: &TextAnnotation) -> String {
+    annotation
+        .lines
+        .iter()
+        .map(|line| {
+            let [x_min, y_min, x_max, y_max] = bounds(&line.polygon);
+            let points = match line.polygon.len() {
+                4 => clockwise_from_top_left(&line.polygon),
+                _ => rectangle(x_min, y_min, x_max, y_max),
+            };
+            let coordinates: Vec<String> = points.concat().iter().map(|c| c.round().to_string()).collect();
+            format!("{},{}\n", coordinates.join(","), transcription(line))
+        })
+        .collect()
+}
+
+// Polygons of vertical lines start at their top right corner, and augmentations may turn them around,
+// so the points are sorted by their angle around the center, starting from the one nearest to the top left
+fn clockwise_from_top_left(polygon: &Polygon) -> Polygon {
+    let n = polygon.len() as f32;
+    let center = polygon
+        .iter()
+        .fold([0.0, 0.0], |[cx, cy], [x, y]| [cx + x / n, cy + y / n]);
+    let mut points = polygon.clone();
+    // The y axis points down, so increasing angles go clockwise
+    points.sort_by(|a, b| {
+        let angle = |p: &[f32; 2]| (p[1] - center[1]).atan2(p[0] - center[0]);
+        angle(a).total_cmp(&angle(b))
+    });
+    let start = (0..points.len())
+        .min_by(|i, j| (points[*i][0] + points[*i][1]).total_cmp(&(points[*j][0] + points[*j][1])))
+        .unwrap_or_default();
+    points.rotate_left(start);
+    points
+}
+
+fn paddle_detection_row(sample: &ExportedSample) -> String {
+    let lines: Vec<Value> = sample
+        .annotation
+        .lines
+        .iter()
+        .map(|line| {
+            let points: Vec<[i64; 2]> = line.polygon.iter().map(|p| p.map(|c| c.round() as i64)).collect();
+            json!({"transcription": transcription(line), "points": points})
+        })
+        .collect();
+    format!("{}\t{}\n", sample.image_file, Value::Array(lines))
+}
+
+// Occluded lines are marked as regions to ignore, the way ICDAR 2015 and PaddleOCR do
+fn transcription(line: &LineAnnotation) -> &str {
+    match line.occluded {
+        true => "###",
+        false => &line.text,
+    }
+}
+
+// Tabs and line breaks would break the row, so they are escaped
+fn recognition_row(image_file: &str, text: &str) -> String {
+    let text = text.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n");
+    format!("{}\t{}\n", image_file, text)
+}
+
+// Shoelace formula
+// Ref: https://en.wikipedia.org/wiki/Shoelace_formula
+fn area(polygon: &Polygon) -> f32 {
+    let sum: f32 = (0..polygon.len())
+        .map(|i| {
+            let ([x1, y1], [x2, y2]) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+            x1 * y2 - x2 * y1
+        })
+        .sum();
+    sum.abs() / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnnotationFormat, ExportedCrop, ExportedSample, ImageTextInsight, Split};
+    use crate::domain::annotation::rectangle;
+
+    #[test]
+    fn write_label_files() {
+        let insight = ImageTextInsight::parse(
+            r#"{"image_text": "東京\n大阪", "image_text_polygons": [[[0, 0], [40, 0], [40, 20], [0, 20]], [[0, 30], [40, 30], [40, 50], [0, 50]]]}"#,
+        )
+        .unwrap();
+        let mut annotation = insight.annotation();
+        // Starting at the top right, like vertical lines
+        annotation.lines[0].polygon.rotate_left(1);
+        assert_eq!(annotation.lines.len(), 2);
+        assert!(ImageTextInsight::parse(r#"{"summary": "..."}"#).is_none());
+        annotation.lines[1].polygon = rectangle(0.0, 30.0, 40.0, 50.0);
+        annotation.lines[1].polygon.push([20.0, 60.0]);
+        annotation.lines[1].occluded = true;
+        let sample = ExportedSample {
+            name: "news_1".to_string(),
+            image_file: "images/news_1.jpg".to_string(),
+            width: 100,
+            height: 80,
+            text: insight.image_text.clone(),
+            annotation,
+            split: Some(Split::Validation),
+        };
+        let crop = ExportedCrop {
+            image_file: "crops/news_1_0.png".to_string(),
+            text: "東京".to_string(),
+        };
+        let samples = [sample];
+        let label_file = |format: AnnotationFormat| format.label_files(&samples, std::slice::from_ref(&crop)).remove(0);
+        assert_eq!(
+            label_file(AnnotationFormat::Icdar2015),
+            (
+                "labels/gt_news_1.txt".to_string(),
+                "0,0,40,0,40,20,0,20,東京\n0,30,40,30,40,60,0,60,###\n".to_string()
+            )
+        );
+        assert_eq!(
+            label_file(AnnotationFormat::RecognitionList).1,
+            "images/news_1.jpg\t東京\\n大阪\n"
+        );
+        assert_eq!(
+            label_file(AnnotationFormat::PaddleRecognition).1,
+            "crops/news_1_0.png\t東京\n"
+        );
+        assert!(label_file(AnnotationFormat::PaddleDetection)
+            .1
+            .starts_with(r#"images/news_1.jpg	[{"points":[[40,0],[40,20],[0,20],[0,0]],"transcription":"東京"}"#));
+        let coco_text: serde_json::Value = serde_json::from_str(&label_file(AnnotationFormat::CocoText).1).unwrap();
+        assert_eq!(coco_text["anns"]["1"]["area"], 800.0);
+        assert_eq!(coco_text["anns"]["2"]["legibility"], "illegible");
+        assert_eq!(coco_text["imgToAnns"]["1"], serde_json::json!([1, 2]));
+        assert_eq!(coco_text["imgs"]["1"]["set"], "val");
+    }
+}
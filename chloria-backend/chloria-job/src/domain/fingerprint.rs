@@ -0,0 +1,92 @@
+// Number of 16-bit bands a fingerprint is split into to look up near-duplicate candidates
+pub(crate) const SIMHASH_BANDS_NUM: usize = 4;
+// Near-duplicates differ in fewer bits than bands, so that they share at least one band
+const MAX_NEAR_DUPLICATE_DISTANCE: u32 = SIMHASH_BANDS_NUM as u32 - 1;
+// Length of the overlapping character n-grams hashed into the fingerprint
+const SHINGLE_LENGTH: usize = 3;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) struct SimHash(pub(crate) u64);
+
+impl SimHash {
+    // Returns nothing if the text is too short to be compared with others
+    // Ref: https://www.cs.princeton.edu/courses/archive/spr04/cos598B/bib/CharikarEstim.pdf
+    pub(crate) fn new(text: &str) -> Option<Self> {
+        let chars: Vec<char> = text
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .flat_map(|c| c.to_lowercase())
+            .collect();
+        if chars.len() < SHINGLE_LENGTH {
+            return None;
+        }
+        let mut weights = [0i32; 64];
+        for shingle in chars.windows(SHINGLE_LENGTH) {
+            let hash = mix(fnv1a(shingle));
+            for (bit, weight) in weights.iter_mut().enumerate() {
+                match hash >> bit & 1 {
+                    1 => *weight += 1,
+                    _ => *weight -= 1,
+                }
+            }
+        }
+        let hash = weights
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| **w > 0)
+            .fold(0u64, |hash, (bit, _)| hash | 1 << bit);
+        Some(Self(hash))
+    }
+
+    pub(crate) fn bands(&self) -> [u16; SIMHASH_BANDS_NUM] {
+        std::array::from_fn(|i| (self.0 >> (16 * i)) as u16)
+    }
+
+    pub(crate) fn distance(&self, other: &Self) -> u32 {
+        (self.0 ^ other.0).count_ones()
+    }
+
+    pub(crate) fn is_near_duplicate(&self, other: &Self) -> bool {
+        self.distance(other) <= MAX_NEAR_DUPLICATE_DISTANCE
+    }
+}
+
+// Stable across builds and platforms, unlike the hasher of the standard library
+fn fnv1a(chars: &[char]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    chars
+        .iter()
+        .flat_map(|c| (*c as u32).to_le_bytes())
+        .fold(OFFSET_BASIS, |hash, byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+}
+
+// Spreads every input bit over the whole hash, which FNV alone does poorly for short inputs
+// Ref: https://prng.di.unimi.it/splitmix64.c
+fn mix(mut hash: u64) -> u64 {
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d049bb133111eb);
+    hash ^ (hash >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SimHash;
+
+    #[test]
+    fn detect_near_duplicates() {
+        let text = "政府は１０日、新たな経済対策を閣議決定した。物価高に対応するため、低所得世帯への給付金や電気・ガス料金の補助を盛り込んだ。\
+            事業規模は約３９兆円となり、財政支出は２１兆円を超える。首相は記者団に対し「国民の暮らしを守るため、あらゆる手段を講じる」と述べた。\
+            与党内では規模の拡大を求める声が相次いでいたが、財政規律を重視する立場から慎重論も根強かった。\
+            政府は今月中に補正予算案を国会に提出し、年内の成立を目指す。野党は給付の対象や時期について説明を求める構えだ。";
+        // The same story delivered by another provider, with a slightly edited sentence and its own credit line
+        let edited_text = text.replace("述べた", "強調した") + "（共同）";
+        let other_text = "日本銀行は金融政策決定会合で、短期金利の誘導目標を据え置くことを決めた。賃金と物価の動向を慎重に見極める必要があると判断した。\
+            総裁は会見で、米国の関税政策が国内経済に与える影響について「不確実性が極めて高い」との認識を示した。\
+            市場では追加利上げの時期を巡る観測が交錯しており、次回会合での判断に注目が集まっている。";
+        let simhash = SimHash::new(text).unwrap();
+        assert!(simhash.is_near_duplicate(&SimHash::new(&edited_text).unwrap()));
+        assert!(!simhash.is_near_duplicate(&SimHash::new(other_text).unwrap()));
+        assert_eq!(SimHash::new("短い"), None);
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+// Words found in family names of each style class, checked in order since some names contain both
+const GOTHIC_KEYWORDS: [&str; 7] = ["gothic", "ゴシック", "sans", "kaku", "maru", "丸", "hei"];
+const MINCHO_KEYWORDS: [&str; 6] = ["mincho", "明朝", "serif", "song", "ming", "kyokasho"];
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum StyleClass {
+    Mincho, // Serif, with thin horizontal strokes and triangular ends
+    Gothic, // Sans-serif, with strokes of even width
+    Other,  // Handwriting, brush and display fonts
+}
+
+impl StyleClass {
+    // Latin keywords are matched against whole words, so that e.g. `hei` does not match `Heisei`,
+    // and Japanese ones anywhere, since Japanese names are not split into words
+    pub(crate) fn guess(family: &str) -> Self {
+        let words = family_words(family);
+        let matches = |keyword: &&str| match keyword.is_ascii() {
+            true => words.iter().any(|w| w == keyword),
+            false => family.contains(keyword),
+        };
+        if GOTHIC_KEYWORDS.iter().any(matches) {
+            Self::Gothic
+        } else if MINCHO_KEYWORDS.iter().any(matches) {
+            Self::Mincho
+        } else {
+            Self::Other
+        }
+    }
+}
+
+// Lowercase words of the family name, split at other characters than letters and digits and where the case changes,
+// e.g. `IPAexGothic` into `ipaex` and `gothic`
+fn family_words(family: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let mut previous_lowercase = false;
+    for c in family.chars() {
+        if (!c.is_alphanumeric() || (previous_lowercase && c.is_uppercase())) && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        if c.is_alphanumeric() {
+            word.extend(c.to_lowercase());
+        }
+        previous_lowercase = c.is_lowercase();
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub(crate) struct FontMetadata {
+    pub(crate) name: String, // Unique, which render parameters refer to the font by
+    pub(crate) family: String,
+    pub(crate) weight: u16, // From 100 (thin) to 900 (black)
+    pub(crate) style_class: StyleClass,
+}
+
+// A font along with the characters its cmap has glyphs for
+#[derive(Clone, Debug)]
+pub(crate) struct IndexedFont {
+    pub(crate) metadata: FontMetadata,
+    pub(crate) characters: BTreeSet<char>,
+}
+
+// Fonts to render text with, so that no character of the text is drawn as a missing glyph (tofu)
+pub(crate) struct FontRegistry {
+    fonts: BTreeMap<String, IndexedFont>, // By name
+}
+
+impl FontRegistry {
+    pub(crate) fn new(fonts: Vec<IndexedFont>) -> Self {
+        Self {
+            fonts: fonts.into_iter().map(|f| (f.metadata.name.clone(), f)).collect(),
+        }
+    }
+
+    pub(crate) fn metadata(&self, name: &str) -> Option<FontMetadata> {
+        self.fonts.get(name).map(|f| f.metadata.clone())
+    }
+
+    pub(crate) fn fonts(&self) -> impl Iterator<Item = &IndexedFont> {
+        self.fonts.values()
+    }
+
+    // Names of the fonts which have a glyph for every character of the text, whitespace aside
+    pub(crate) fn covering_font_names(&self, text: &str) -> Vec<String> {
+        self.fonts
+            .values()
+            .filter(|f| text.chars().all(|c| c.is_whitespace() || f.characters.contains(&c)))
+            .map(|f| f.metadata.name.clone())
+            .collect()
+    }
+
+    // Characters of the text which no font has a glyph for
+    pub(crate) fn uncovered_characters(&self, text: &str) -> BTreeSet<char> {
+        text.chars()
+            .filter(|c| !c.is_whitespace() && !self.fonts.values().any(|f| f.characters.contains(c)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FontMetadata, FontRegistry, IndexedFont, StyleClass};
+
+    #[test]
+    fn cover_text() {
+        assert_eq!(StyleClass::guess("Noto Serif JP"), StyleClass::Mincho);
+        assert_eq!(StyleClass::guess("Noto Sans JP"), StyleClass::Gothic);
+        assert_eq!(StyleClass::guess("IPAex明朝"), StyleClass::Mincho);
+        assert_eq!(StyleClass::guess("Heisei Mincho"), StyleClass::Mincho);
+        assert_eq!(StyleClass::guess("Zen Maru Gothic"), StyleClass::Gothic);
+        assert_eq!(StyleClass::guess("SimHei"), StyleClass::Gothic);
+        assert_eq!(StyleClass::guess("IPAexGothic"), StyleClass::Gothic);
+        assert_eq!(StyleClass::guess("Klee One"), StyleClass::Other);
+        let font = |name: &str, characters: &str| IndexedFont {
+            metadata: FontMetadata {
+                name: name.to_string(),
+                family: name.to_string(),
+                weight: 400,
+                style_class: StyleClass::Other,
+            },
+            characters: characters.chars().collect(),
+        };
+        let registry = FontRegistry::new(vec![font("a", "日本語の"), font("b", "日本")]);
+        assert_eq!(registry.covering_font_names("日本 語"), ["a"]);
+        assert_eq!(registry.covering_font_names("日本"), ["a", "b"]);
+        assert!(registry.covering_font_names("日本人").is_empty());
+        assert_eq!(registry.uncovered_characters("日本人\n"), ['人'].into());
+    }
+}
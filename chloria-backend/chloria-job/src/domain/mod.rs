@@ -1 +1,16 @@
+pub(crate) mod annotation;
+pub(crate) mod augmentation;
+pub(crate) mod difficulty;
+pub(crate) mod document;
+pub(crate) mod export;
+pub(crate) mod fingerprint;
+pub(crate) mod font;
 pub(crate) mod news;
+pub(crate) mod quality;
+pub(crate) mod sentence;
+pub(crate) mod split;
+pub(crate) mod statistics;
+pub(crate) mod synthesis;
+pub(crate) mod text;
+pub(crate) mod vertical;
+pub(crate) mod webdataset;
@@ -0,0 +1,136 @@
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum QualityField {
+    Title,
+    ShortText,
+    LongText,
+}
+
+impl QualityField {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::ShortText => "short_text",
+            Self::LongText => "long_text",
+        }
+    }
+}
+
+// Rules are read from the configuration, e.g. `[{"type": "min_length", "field": "long_text", "length": 100}]`
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub(crate) enum QualityRule {
+    Required { field: QualityField },
+    MinLength { field: QualityField, length: usize }, // Counted in characters, only checked if the field is given
+    TitleBodyDuplication,                             // The body is nothing more than the title
+    BannedPhrase { phrase: String },                  // Checked in every field
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum QualityAction {
+    Reject, // Do not save failing articles
+    #[default]
+    Flag, // Save failing articles along with the reasons
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct QualityRuleSet {
+    pub(crate) rules: Vec<QualityRule>,
+    pub(crate) action: QualityAction,
+}
+
+pub(crate) struct QualityInput<'a> {
+    pub(crate) title: Option<&'a str>,
+    pub(crate) short_text: Option<&'a str>,
+    pub(crate) long_text: Option<&'a str>,
+}
+
+impl QualityInput<'_> {
+    fn get(&self, field: QualityField) -> Option<&str> {
+        match field {
+            QualityField::Title => self.title,
+            QualityField::ShortText => self.short_text,
+            QualityField::LongText => self.long_text,
+        }
+        .filter(|t| !t.trim().is_empty())
+    }
+}
+
+impl QualityRuleSet {
+    // Returns the reasons why the article fails, which is empty if it passes every rule
+    pub(crate) fn evaluate(&self, input: &QualityInput) -> Vec<String> {
+        let mut reasons = vec![];
+        for rule in &self.rules {
+            let reason = match rule {
+                QualityRule::Required { field } => match input.get(*field) {
+                    Some(_) => None,
+                    None => Some(format!("missing_{}", field.name())),
+                },
+                QualityRule::MinLength { field, length } => match input.get(*field) {
+                    Some(text) if text.trim().chars().count() < *length => Some(format!("short_{}", field.name())),
+                    _ => None,
+                },
+                QualityRule::TitleBodyDuplication => {
+                    let body = input.get(QualityField::LongText).or(input.get(QualityField::ShortText));
+                    match (input.get(QualityField::Title), body) {
+                        (Some(title), Some(body)) if title.trim() == body.trim() => {
+                            Some("title_body_duplication".to_string())
+                        }
+                        _ => None,
+                    }
+                }
+                QualityRule::BannedPhrase { phrase } => {
+                    let fields = [QualityField::Title, QualityField::ShortText, QualityField::LongText];
+                    match fields.iter().any(|f| input.get(*f).is_some_and(|t| t.contains(phrase))) {
+                        true => Some(format!("banned_phrase:{}", phrase)),
+                        false => None,
+                    }
+                }
+            };
+            if let Some(reason) = reason.filter(|r| !reasons.contains(r)) {
+                reasons.push(reason);
+            }
+        }
+        reasons
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QualityField, QualityInput, QualityRule, QualityRuleSet};
+
+    #[test]
+    fn evaluate_rules() {
+        let rule_set = QualityRuleSet {
+            rules: vec![
+                QualityRule::Required {
+                    field: QualityField::Title,
+                },
+                QualityRule::MinLength {
+                    field: QualityField::LongText,
+                    length: 20,
+                },
+                QualityRule::TitleBodyDuplication,
+                QualityRule::BannedPhrase {
+                    phrase: "無断転載".to_string(),
+                },
+            ],
+            ..Default::default()
+        };
+        let reasons = rule_set.evaluate(&QualityInput {
+            title: Some("速報"),
+            short_text: None,
+            long_text: Some(" 速報 "),
+        });
+        assert_eq!(reasons, vec!["short_long_text", "title_body_duplication"]);
+        let reasons = rule_set.evaluate(&QualityInput {
+            title: Some(" "),
+            short_text: Some("記事の無断転載を禁じます。"),
+            long_text: None,
+        });
+        assert_eq!(reasons, vec!["missing_title", "banned_phrase:無断転載"]);
+    }
+}
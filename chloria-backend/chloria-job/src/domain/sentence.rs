@@ -0,0 +1,154 @@
+// Single quotation marks are left out, since `’` is mostly used as an apostrophe
+const OPENING_BRACKETS: &str = "「『（(【〔［[｛{〈《“";
+const CLOSING_BRACKETS: &str = "」』）)】〕］]｝}〉》”";
+// Marks ending a sentence, wherever they appear outside brackets
+const TERMINATORS: &str = "。！？!?";
+// Abbreviations followed by a period which does not end a sentence
+const ABBREVIATIONS: [&str; 12] = [
+    "Mr", "Mrs", "Ms", "Dr", "Prof", "St", "No", "vs", "etc", "Inc", "Co", "Ltd",
+];
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Sentence {
+    pub(crate) start: usize,  // Offset in characters from the start of the text
+    pub(crate) length: usize, // Number of characters
+    pub(crate) text: String,
+}
+
+// Sentences end at line breaks and at terminators outside brackets and quotes, with the terminators that follow them
+// Periods only end a sentence if they are not part of a number, an abbreviation or an initial
+pub(crate) fn split_sentences(text: &str) -> Vec<Sentence> {
+    let chars: Vec<char> = text.chars().collect();
+    let is_matched = match_brackets(&chars);
+    let mut sentences = vec![];
+    let mut start = 0;
+    let mut brackets = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+        let is_end = match c {
+            '\n' => {
+                brackets.clear();
+                true
+            }
+            _ if OPENING_BRACKETS.contains(c) && is_matched[i - 1] => {
+                brackets.push(c);
+                false
+            }
+            _ if CLOSING_BRACKETS.contains(c) && is_matched[i - 1] => {
+                brackets.pop();
+                false
+            }
+            _ if !brackets.is_empty() => false,
+            _ if TERMINATORS.contains(c) => {
+                while i < chars.len() && (TERMINATORS.contains(chars[i]) || chars[i] == '…') {
+                    i += 1;
+                }
+                true
+            }
+            '.' | '．' => is_period_end(&chars, i - 1),
+            _ => false,
+        };
+        if is_end {
+            push_sentence(&mut sentences, &chars, start, i);
+            start = i;
+        }
+    }
+    push_sentence(&mut sentences, &chars, start, chars.len());
+    sentences
+}
+
+// Whether each bracket is closed on the same line, so that an opening bracket left alone
+// (e.g. a quote cut by the source) does not keep the rest of the line in a single sentence
+fn match_brackets(chars: &[char]) -> Vec<bool> {
+    let mut is_matched = vec![false; chars.len()];
+    let mut opening_positions = vec![];
+    for (position, c) in chars.iter().enumerate() {
+        match c {
+            '\n' => opening_positions.clear(),
+            _ if OPENING_BRACKETS.contains(*c) => opening_positions.push(position),
+            _ if CLOSING_BRACKETS.contains(*c) => {
+                if let Some(opening_position) = opening_positions.pop() {
+                    is_matched[opening_position] = true;
+                    is_matched[position] = true;
+                }
+            }
+            _ => {}
+        }
+    }
+    is_matched
+}
+
+fn is_period_end(chars: &[char], position: usize) -> bool {
+    let next = chars.get(position + 1);
+    if next.is_some_and(|c| c.is_ascii_digit() || ('０'..='９').contains(c)) {
+        return false;
+    }
+    // Full-width periods are used instead of `。` in some texts, half-width ones are followed by a space
+    if chars[position] == '.' && next.is_some_and(|c| !c.is_whitespace()) {
+        return false;
+    }
+    let word: String = chars[..position]
+        .iter()
+        .rev()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    word.chars().count() != 1 && !ABBREVIATIONS.contains(&word.as_str())
+}
+
+fn push_sentence(sentences: &mut Vec<Sentence>, chars: &[char], start: usize, end: usize) {
+    let leading_whitespace = chars[start..end].iter().take_while(|c| c.is_whitespace()).count();
+    let trailing_whitespace = chars[start..end].iter().rev().take_while(|c| c.is_whitespace()).count();
+    if leading_whitespace == end - start {
+        return;
+    }
+    let start = start + leading_whitespace;
+    let end = end - trailing_whitespace;
+    sentences.push(Sentence {
+        start,
+        length: end - start,
+        text: chars[start..end].iter().collect(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_sentences;
+
+    #[test]
+    fn split_japanese_sentences() {
+        let text = "首相は「国民を守る。あらゆる手段を講じる」と述べた。支出は3.5兆円だ！？\n\
+            Mr. Smith visited the U.S. yesterday. 内容（詳細は後日。）を発表へ";
+        let sentences: Vec<String> = split_sentences(text).into_iter().map(|s| s.text).collect();
+        assert_eq!(
+            sentences,
+            [
+                "首相は「国民を守る。あらゆる手段を講じる」と述べた。",
+                "支出は3.5兆円だ！？",
+                "Mr. Smith visited the U.S. yesterday.",
+                "内容（詳細は後日。）を発表へ",
+            ]
+        );
+        let sentence = &split_sentences(text)[2];
+        assert_eq!(
+            text.chars()
+                .skip(sentence.start)
+                .take(sentence.length)
+                .collect::<String>(),
+            sentence.text
+        );
+        // Neither an unclosed quote nor an apostrophe holds back the end of a sentence
+        let sentences: Vec<String> = split_sentences("「引用は閉じられない。次の文だ。\nIt’s over. 終わり。")
+            .into_iter()
+            .map(|s| s.text)
+            .collect();
+        assert_eq!(
+            sentences,
+            ["「引用は閉じられない。", "次の文だ。", "It’s over.", "終わり。"]
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Split {
+    Train,
+    Validation,
+    Test,
+}
+
+impl Split {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Train => "train",
+            Self::Validation => "validation",
+            Self::Test => "test",
+        }
+    }
+
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "train" => Some(Self::Train),
+            "validation" => Some(Self::Validation),
+            "test" => Some(Self::Test),
+            _ => None,
+        }
+    }
+}
+
+// What news are grouped by, every news of a group being assigned to the same split
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum SplitGrouping {
+    Article, // Each article on its own
+    Source,  // Every article of a source, to evaluate on sources never seen in training
+    Cluster, // Near-duplicates together, so that a model is never tested on what it was trained on
+}
+
+// Assigns news to splits by hashing the key of their group, so that the same group always lands in the same split
+pub(crate) struct SplitAssigner {
+    pub(crate) grouping: SplitGrouping,
+    pub(crate) ratios: [f64; 3], // Of train, validation and test, relative to their sum
+}
+
+impl Default for SplitAssigner {
+    fn default() -> Self {
+        Self {
+            grouping: SplitGrouping::Cluster,
+            ratios: [0.8, 0.1, 0.1],
+        }
+    }
+}
+
+impl SplitAssigner {
+    // News which have not been clustered are their own cluster
+    pub(crate) fn group_key(&self, source_name: &str, article_id: &str, cluster_id: i32) -> String {
+        match self.grouping {
+            SplitGrouping::Article => format!("article:{}:{}", source_name, article_id),
+            SplitGrouping::Source => format!("source:{}", source_name),
+            SplitGrouping::Cluster => format!("cluster:{}", cluster_id),
+        }
+    }
+
+    // SHA-256 rather than the hasher of the standard library, whose output may change between releases
+    pub(crate) fn assign(&self, group_key: &str) -> Split {
+        let digest = Sha256::digest(group_key.as_bytes());
+        let mut head = [0; 8];
+        head.copy_from_slice(&digest[..8]);
+        // Uniform in [0, 1)
+        let position = (u64::from_be_bytes(head) >> 11) as f64 / (1u64 << 53) as f64;
+        let total: f64 = self.ratios.iter().sum();
+        let mut threshold = 0.0;
+        for (split, ratio) in [Split::Train, Split::Validation].into_iter().zip(self.ratios) {
+            threshold += ratio / total;
+            if position < threshold {
+                return split;
+            }
+        }
+        Split::Test
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Split, SplitAssigner, SplitGrouping};
+
+    #[test]
+    fn assign_splits() {
+        let assigner = SplitAssigner::default();
+        assert_eq!(assigner.group_key("Yahoo", "a1", 42), "cluster:42");
+        let splits: Vec<Split> = (0..1000).map(|i| assigner.assign(&format!("cluster:{}", i))).collect();
+        let train_count = splits.iter().filter(|s| **s == Split::Train).count();
+        assert!((750..850).contains(&train_count));
+        assert!(splits.contains(&Split::Validation) && splits.contains(&Split::Test));
+        // The same key always lands in the same split, whatever the other keys are
+        assert_eq!(assigner.assign("cluster:7"), splits[7]);
+        let assigner = SplitAssigner {
+            grouping: SplitGrouping::Source,
+            ratios: [0.0, 0.0, 1.0],
+        };
+        assert_eq!(assigner.group_key("Yahoo", "a1", 42), "source:Yahoo");
+        assert_eq!(assigner.assign("source:Yahoo"), Split::Test);
+        assert_eq!(Split::parse(Split::Validation.as_str()), Some(Split::Validation));
+    }
+}
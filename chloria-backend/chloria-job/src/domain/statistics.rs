@@ -0,0 +1,216 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::LazyLock,
+};
+
+use encoding_rs::SHIFT_JIS;
+use kanji::exam_lists;
+
+// School grade of each Jōyō kanji, following KANJIDIC: 1 to 6 for elementary school and 8 for secondary school
+// Jōyō kanji are the ones of the Kanji Kentei up to level 2, whose levels 10 to 5 match the elementary grades
+pub(super) static JOYO_KANJI_GRADES: LazyLock<HashMap<char, i32>> = LazyLock::new(|| {
+    [
+        (exam_lists::LEVEL_10, 1),
+        (exam_lists::LEVEL_09, 2),
+        (exam_lists::LEVEL_08, 3),
+        (exam_lists::LEVEL_07, 4),
+        (exam_lists::LEVEL_06, 5),
+        (exam_lists::LEVEL_05, 6),
+        (exam_lists::LEVEL_04, 8),
+        (exam_lists::LEVEL_03, 8),
+        (exam_lists::LEVEL_02_PRE, 8),
+        (exam_lists::LEVEL_02, 8),
+    ]
+    .iter()
+    .flat_map(|(l, grade)| l.chars().filter(|c| kanji::is_kanji(*c)).map(|c| (c, *grade)))
+    .collect()
+});
+
+// Numbers of kanji in each level of JIS X 0208
+const JIS_LEVEL_1_SIZE: usize = 2965;
+const JIS_LEVEL_2_SIZE: usize = 3390;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum Script {
+    Kanji,
+    Hiragana,
+    Katakana,
+    Latin,
+    Digit,
+    Other, // Punctuation, symbols and other scripts
+}
+
+impl Script {
+    pub(crate) fn of(c: char) -> Self {
+        match c {
+            '々' | '〆' | 'ヶ' => Self::Kanji,
+            _ if kanji::is_kanji(c) => Self::Kanji,
+            _ if kanji::is_hiragana(c) => Self::Hiragana,
+            // Including half-width katakana and phonetic extensions
+            _ if kanji::is_katakana(c) || matches!(c, '\u{FF66}'..='\u{FF9F}' | '\u{31F0}'..='\u{31FF}') => {
+                Self::Katakana
+            }
+            '0'..='9' | '０'..='９' => Self::Digit,
+            'Ａ'..='Ｚ' | 'ａ'..='ｚ' => Self::Latin,
+            _ if c.is_alphabetic() && c < '\u{0250}' => Self::Latin,
+            _ => Self::Other,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum JisLevel {
+    NonKanji, // Characters of JIS X 0201 or the non-kanji rows of JIS X 0208
+    One,      // Rows 16 to 47 of JIS X 0208
+    Two,      // Rows 48 to 84 of JIS X 0208
+}
+
+impl JisLevel {
+    // Returns nothing for characters outside JIS X 0201 and JIS X 0208 (including vendor extensions)
+    pub(crate) fn of(c: char) -> Option<Self> {
+        let mut buffer = [0; 4];
+        let (bytes, _, had_errors) = SHIFT_JIS.encode(c.encode_utf8(&mut buffer));
+        match (had_errors, &bytes[..]) {
+            (true, _) => None,
+            (false, [_]) => Some(Self::NonKanji),
+            (false, [lead, trail]) => {
+                // Ref: https://en.wikipedia.org/wiki/Shift_JIS#Shift_JIS_byte_map
+                let row = match lead {
+                    0x81..=0x9F => (lead - 0x81) * 2 + 1,
+                    _ => (lead - 0xC1) * 2 + 1,
+                } + (*trail >= 0x9F) as u8;
+                match row {
+                    1..=15 => Some(Self::NonKanji),
+                    16..=47 => Some(Self::One),
+                    48..=84 => Some(Self::Two),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+// Whitespace is not counted
+#[derive(Default)]
+pub(crate) struct CharacterStatistics {
+    pub(crate) character_count: usize,
+    pub(crate) kanji_count: usize,
+    pub(crate) hiragana_count: usize,
+    pub(crate) katakana_count: usize,
+    pub(crate) latin_count: usize,
+    pub(crate) digit_count: usize,
+    pub(crate) other_count: usize,
+    pub(crate) joyo_kanji_count: usize, // Kanji in the Jōyō list
+    pub(crate) jis_level_1_count: usize,
+    pub(crate) jis_level_2_count: usize,
+    pub(crate) non_jis_count: usize,    // Characters outside JIS X 0201 and JIS X 0208
+    pub(crate) rare_characters: String, // Distinct kanji outside the Jōyō list and characters outside JIS
+    pub(crate) character_counts: BTreeMap<char, usize>,
+}
+
+impl CharacterStatistics {
+    pub(crate) fn new(text: &str) -> Self {
+        let mut character_counts = BTreeMap::new();
+        for c in text.chars().filter(|c| !c.is_whitespace()) {
+            *character_counts.entry(c).or_default() += 1;
+        }
+        Self::from_character_counts(character_counts)
+    }
+
+    // Used to aggregate statistics of many texts
+    pub(crate) fn from_character_counts(character_counts: BTreeMap<char, usize>) -> Self {
+        let mut statistics = Self {
+            character_counts,
+            ..Default::default()
+        };
+        for (c, count) in &statistics.character_counts {
+            let script = Script::of(*c);
+            let jis_level = JisLevel::of(*c);
+            let is_joyo_kanji = JOYO_KANJI_GRADES.contains_key(c);
+            statistics.character_count += count;
+            *match script {
+                Script::Kanji => &mut statistics.kanji_count,
+                Script::Hiragana => &mut statistics.hiragana_count,
+                Script::Katakana => &mut statistics.katakana_count,
+                Script::Latin => &mut statistics.latin_count,
+                Script::Digit => &mut statistics.digit_count,
+                Script::Other => &mut statistics.other_count,
+            } += count;
+            if is_joyo_kanji {
+                statistics.joyo_kanji_count += count;
+            }
+            match jis_level {
+                Some(JisLevel::One) => statistics.jis_level_1_count += count,
+                Some(JisLevel::Two) => statistics.jis_level_2_count += count,
+                Some(JisLevel::NonKanji) => {}
+                None => statistics.non_jis_count += count,
+            }
+            if (script == Script::Kanji && !is_joyo_kanji) || jis_level.is_none() {
+                statistics.rare_characters.push(*c);
+            }
+        }
+        statistics
+    }
+
+    pub(crate) fn coverage(&self) -> CharacterCoverage {
+        let mut coverage = CharacterCoverage::default();
+        for c in self.character_counts.keys() {
+            if JOYO_KANJI_GRADES.contains_key(c) {
+                coverage.joyo_kanji_count += 1;
+            }
+            match JisLevel::of(*c) {
+                Some(JisLevel::One) => coverage.jis_level_1_count += 1,
+                Some(JisLevel::Two) => coverage.jis_level_2_count += 1,
+                _ => {}
+            }
+        }
+        coverage
+    }
+}
+
+// Numbers of distinct characters of each set which appear at least once
+#[derive(Default)]
+pub(crate) struct CharacterCoverage {
+    pub(crate) joyo_kanji_count: usize,
+    pub(crate) jis_level_1_count: usize,
+    pub(crate) jis_level_2_count: usize,
+}
+
+impl CharacterCoverage {
+    pub(crate) fn joyo_kanji_ratio(&self) -> f64 {
+        self.joyo_kanji_count as f64 / JOYO_KANJI_GRADES.len() as f64
+    }
+
+    pub(crate) fn jis_level_1_ratio(&self) -> f64 {
+        self.jis_level_1_count as f64 / JIS_LEVEL_1_SIZE as f64
+    }
+
+    pub(crate) fn jis_level_2_ratio(&self) -> f64 {
+        self.jis_level_2_count as f64 / JIS_LEVEL_2_SIZE as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CharacterStatistics, JisLevel, JOYO_KANJI_GRADES};
+
+    #[test]
+    fn count_characters() {
+        assert_eq!(JOYO_KANJI_GRADES.len(), 2136);
+        assert_eq!(JisLevel::of('亜'), Some(JisLevel::One));
+        assert_eq!(JisLevel::of('腕'), Some(JisLevel::One));
+        assert_eq!(JisLevel::of('弌'), Some(JisLevel::Two));
+        assert_eq!(JisLevel::of('あ'), Some(JisLevel::NonKanji));
+        assert_eq!(JisLevel::of('𠮟'), None);
+        let statistics = CharacterStatistics::new("東京で2０25年に葛飾北斎展、𠮟咤 ABC");
+        assert_eq!(statistics.character_count, 20);
+        assert_eq!(statistics.kanji_count, 10);
+        assert_eq!(statistics.hiragana_count, 2);
+        assert_eq!(statistics.digit_count, 4);
+        assert_eq!(statistics.latin_count, 3);
+        assert_eq!(statistics.other_count, 1);
+        assert_eq!(statistics.character_counts[&'2'], 2);
+        assert_eq!(statistics.rare_characters, "咤𠮟");
+    }
+}
@@ -0,0 +1,337 @@
+use std::{cmp::Ordering, fmt::Debug};
+
+use anyhow::{bail, Result};
+use rand::{seq::IndexedRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use super::{augmentation::AugmentationConfig, font::FontMetadata};
+
+// Characters which must not start a line (kinsoku shori), kept at the end of the previous line instead
+// Ref: https://www.w3.org/TR/jlreq/#characters_not_starting_a_line
+const LINE_START_PROHIBITED: &str =
+    "、。，．,.：；:;！？!?）」』】〕］}〉》”’ゝゞーぁぃぅぇぉっゃゅょゎァィゥェォッャュョヮヵヶ々…‥・";
+// Attempts to draw colors contrasting enough before falling back to black on white
+const COLOR_SAMPLING_ATTEMPTS_NUM: usize = 100;
+// Distance in pixels between the regions of a background compared when looking for a place to put text
+const TEXTURE_SEARCH_STRIDE: usize = 4;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum LineAlignment {
+    Start,
+    Center,
+    End,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum WritingMode {
+    #[default]
+    Horizontal, // Lines from top to bottom, characters from left to right
+    Vertical, // Lines from right to left, characters from top to bottom (tategaki)
+}
+
+// Drawn under the text with its own color, to keep it readable on busy backgrounds
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum TextEffect {
+    None,
+    Shadow { offset: [f32; 2], color: [u8; 3] }, // Offset in pixels, towards the bottom right
+    Outline { width: f32, color: [u8; 3] },      // Width in pixels
+}
+
+// Everything needed to render the same image again from the same text
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub(crate) struct RenderParams {
+    pub(crate) font_name: String,
+    #[serde(default)]
+    pub(crate) font: Option<FontMetadata>, // Family, weight and style class of the font, to select samples by them
+    pub(crate) font_size: f32, // In pixels
+    pub(crate) text_color: [u8; 3],
+    pub(crate) background_color: [u8; 3],
+    pub(crate) max_line_length: usize, // In characters, lines are wrapped beyond it
+    pub(crate) line_spacing: f32,      // Distance between lines, relative to the font size
+    pub(crate) alignment: LineAlignment,
+    pub(crate) padding: u32, // In pixels, around the text
+    pub(crate) effect: TextEffect,
+    #[serde(default)]
+    pub(crate) position: Option<[u32; 2]>, // Top left corner of the text on the background photo, for scene samples
+    #[serde(default)]
+    pub(crate) writing_mode: WritingMode,
+}
+
+// Ranges are inclusive, and parameters are drawn uniformly from them
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub(crate) struct SynthesisConfig {
+    pub(crate) font_sizes: [f32; 2],
+    pub(crate) max_line_lengths: [usize; 2],
+    pub(crate) line_spacings: [f32; 2],
+    pub(crate) alignments: Vec<LineAlignment>,
+    pub(crate) paddings: [u32; 2],
+    pub(crate) min_contrast_ratio: f32, // Between text and background colors, from 1 to 21
+    pub(crate) shadow_probability: f32,
+    pub(crate) shadow_offsets: [f32; 2],
+    pub(crate) outline_probability: f32, // Only drawn if there is no shadow
+    pub(crate) outline_widths: [f32; 2],
+    pub(crate) vertical_probability: f32,
+    pub(crate) max_background_texture: f32, // Photos are only used where their texture stays under it, from 0 to 510
+    pub(crate) augmentation: AugmentationConfig,
+}
+
+impl Default for SynthesisConfig {
+    fn default() -> Self {
+        Self {
+            font_sizes: [16.0, 64.0],
+            max_line_lengths: [8, 40],
+            line_spacings: [1.2, 1.8],
+            alignments: vec![LineAlignment::Start, LineAlignment::Center, LineAlignment::End],
+            paddings: [4, 32],
+            min_contrast_ratio: 3.0,
+            shadow_probability: 0.2,
+            shadow_offsets: [1.0, 4.0],
+            outline_probability: 0.2,
+            outline_widths: [1.0, 3.0],
+            vertical_probability: 0.3,
+            max_background_texture: 12.0,
+            augmentation: AugmentationConfig::default(),
+        }
+    }
+}
+
+impl SynthesisConfig {
+    // Fails on reversed ranges, which parameters could not be drawn from
+    pub(crate) fn validate(&self) -> Result<()> {
+        check_range("font_sizes", self.font_sizes)?;
+        check_range("max_line_lengths", self.max_line_lengths)?;
+        check_range("line_spacings", self.line_spacings)?;
+        check_range("paddings", self.paddings)?;
+        check_range("shadow_offsets", self.shadow_offsets)?;
+        check_range("outline_widths", self.outline_widths)?;
+        self.augmentation.validate()
+    }
+
+    // Returns nothing if there are no fonts to choose from
+    pub(crate) fn sample(&self, rng: &mut impl Rng, font_names: &[String]) -> Option<RenderParams> {
+        let (text_color, background_color) = (0..COLOR_SAMPLING_ATTEMPTS_NUM)
+            .map(|_| (rng.random(), rng.random()))
+            .find(|(t, b)| contrast_ratio(*t, *b) >= self.min_contrast_ratio)
+            .unwrap_or(([0, 0, 0], [255, 255, 255]));
+        let font_name = font_names.choose(rng)?.clone();
+        let effect = match rng.random::<f32>() {
+            p if p < self.shadow_probability => {
+                let offset = rng.random_range(self.shadow_offsets[0]..=self.shadow_offsets[1]);
+                TextEffect::Shadow {
+                    offset: [offset, offset],
+                    color: effect_color(text_color),
+                }
+            }
+            p if p < self.shadow_probability + self.outline_probability => TextEffect::Outline {
+                width: rng.random_range(self.outline_widths[0]..=self.outline_widths[1]),
+                color: effect_color(text_color),
+            },
+            _ => TextEffect::None,
+        };
+        Some(RenderParams {
+            font_name,
+            font: None, // Only the names of the fonts are known here
+            font_size: rng.random_range(self.font_sizes[0]..=self.font_sizes[1]),
+            text_color,
+            background_color,
+            max_line_length: rng.random_range(self.max_line_lengths[0]..=self.max_line_lengths[1]),
+            line_spacing: rng.random_range(self.line_spacings[0]..=self.line_spacings[1]),
+            alignment: *self.alignments.choose(rng).unwrap_or(&LineAlignment::Start),
+            padding: rng.random_range(self.paddings[0]..=self.paddings[1]),
+            effect,
+            position: None,
+            writing_mode: match rng.random::<f32>() < self.vertical_probability {
+                true => WritingMode::Vertical,
+                false => WritingMode::Horizontal,
+            },
+        })
+    }
+}
+
+// Black or white, whichever stands out more against the color
+pub(crate) fn effect_color(color: [u8; 3]) -> [u8; 3] {
+    let (black, white) = ([0, 0, 0], [255, 255, 255]);
+    match contrast_ratio(color, black) > contrast_ratio(color, white) {
+        true => black,
+        false => white,
+    }
+}
+
+// Keeps the color if it contrasts enough with the background, otherwise falls back to black or white
+pub(crate) fn ensure_contrast(color: [u8; 3], background_color: [u8; 3], min_contrast_ratio: f32) -> [u8; 3] {
+    match contrast_ratio(color, background_color) >= min_contrast_ratio {
+        true => color,
+        false => effect_color(background_color),
+    }
+}
+
+// Finds the region of the given size where the brightness of the grayscale image changes the least,
+// returning its top left corner, or nothing if the image is too small or too textured everywhere
+// Texture is measured as the mean absolute difference between neighboring pixels, from 0 to 510
+pub(crate) fn find_low_texture_region(
+    luma: &[u8],
+    width: usize,
+    height: usize,
+    region_width: usize,
+    region_height: usize,
+    max_texture: f32,
+) -> Option<(usize, usize)> {
+    if region_width == 0 || region_height == 0 || region_width > width || region_height > height {
+        return None;
+    }
+    // Summed-area table of gradients, with an extra row and column of zeros
+    // Ref: https://en.wikipedia.org/wiki/Summed-area_table
+    let stride = width + 1;
+    let mut sums = vec![0u64; stride * (height + 1)];
+    for y in 0..height {
+        for x in 0..width {
+            let value = luma[y * width + x] as i32;
+            let dx = match x + 1 < width {
+                true => (luma[y * width + x + 1] as i32 - value).unsigned_abs(),
+                false => 0,
+            };
+            let dy = match y + 1 < height {
+                true => (luma[(y + 1) * width + x] as i32 - value).unsigned_abs(),
+                false => 0,
+            };
+            sums[(y + 1) * stride + x + 1] =
+                (dx + dy) as u64 + sums[y * stride + x + 1] + sums[(y + 1) * stride + x] - sums[y * stride + x];
+        }
+    }
+    let texture = |x: usize, y: usize| {
+        let (x1, y1) = (x + region_width, y + region_height);
+        let sum = sums[y1 * stride + x1] + sums[y * stride + x] - sums[y * stride + x1] - sums[y1 * stride + x];
+        sum as f32 / (region_width * region_height) as f32
+    };
+    (0..=height - region_height)
+        .step_by(TEXTURE_SEARCH_STRIDE)
+        .flat_map(|y| {
+            (0..=width - region_width)
+                .step_by(TEXTURE_SEARCH_STRIDE)
+                .map(move |x| (x, y))
+        })
+        .map(|(x, y)| (x, y, texture(x, y)))
+        .filter(|(_, _, t)| *t <= max_texture)
+        .min_by(|(_, _, t1), (_, _, t2)| t1.total_cmp(t2))
+        .map(|(x, y, _)| (x, y))
+}
+
+// Contrast ratio of WCAG, from 1 for identical colors to 21 for black and white
+// Ref: https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio
+pub(crate) fn contrast_ratio(color: [u8; 3], other_color: [u8; 3]) -> f32 {
+    let luminance = |color: [u8; 3]| {
+        let [r, g, b] = color.map(|c| {
+            let c = c as f32 / 255.0;
+            match c <= 0.03928 {
+                true => c / 12.92,
+                false => ((c + 0.055) / 1.055).powf(2.4),
+            }
+        });
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    };
+    let (l1, l2) = (luminance(color), luminance(other_color));
+    (l1.max(l2) + 0.05) / (l1.min(l2) + 0.05)
+}
+
+// Japanese text has no spaces to break at, so lines are cut after the given number of characters,
+// except that characters which must not start a line are hung at the end of the previous one
+pub(crate) fn wrap_lines(text: &str, max_line_length: usize) -> Vec<String> {
+    let mut lines = vec![];
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        let mut line_length = 0;
+        for c in paragraph.chars() {
+            if line_length >= max_line_length.max(1) && !LINE_START_PROHIBITED.contains(c) {
+                lines.push(line);
+                line = String::new();
+                line_length = 0;
+            }
+            line.push(c);
+            line_length += 1;
+        }
+        if !line.trim().is_empty() {
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+pub(super) fn check_range<T: PartialOrd + Debug>(name: &str, range: [T; 2]) -> Result<()> {
+    match range[0].partial_cmp(&range[1]) {
+        Some(Ordering::Less | Ordering::Equal) => Ok(()),
+        _ => bail!("Invalid range of {}: {:?}", name, range),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{contrast_ratio, find_low_texture_region, wrap_lines, RenderParams, SynthesisConfig};
+    use crate::domain::font::{FontMetadata, StyleClass};
+
+    #[test]
+    fn wrap_and_sample() {
+        assert_eq!(
+            wrap_lines("政府は新たな経済対策を決定した。", 5),
+            ["政府は新た", "な経済対策", "を決定した。"]
+        );
+        assert_eq!(wrap_lines("「はい」と言った", 3), ["「はい」", "と言っ", "た"]);
+        assert!((contrast_ratio([0, 0, 0], [255, 255, 255]) - 21.0).abs() < 1e-4);
+        let config = SynthesisConfig::default();
+        let font_names = vec!["a".to_string(), "b".to_string()];
+        let params = config.sample(&mut StdRng::seed_from_u64(1), &font_names).unwrap();
+        assert_eq!(
+            config.sample(&mut StdRng::seed_from_u64(1), &font_names),
+            Some(params.clone())
+        );
+        assert!(contrast_ratio(params.text_color, params.background_color) >= config.min_contrast_ratio);
+        assert_eq!(config.sample(&mut StdRng::seed_from_u64(1), &[]), None);
+        // Font metadata is stored along with the parameters, and missing from older ones
+        let mut params = params;
+        params.font = Some(FontMetadata {
+            name: "a".to_string(),
+            family: "Heisei Mincho".to_string(),
+            weight: 700,
+            style_class: StyleClass::Mincho,
+        });
+        let mut value = serde_json::to_value(&params).unwrap();
+        assert_eq!(value["font"]["style_class"], "mincho");
+        assert_eq!(serde_json::from_value::<RenderParams>(value.clone()).unwrap(), params);
+        value.as_object_mut().unwrap().remove("font");
+        assert_eq!(serde_json::from_value::<RenderParams>(value).unwrap().font, None);
+        assert!(config.validate().is_ok());
+        let mut reversed_config = config.clone();
+        reversed_config.font_sizes = [64.0, 16.0];
+        assert!(reversed_config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("font_sizes"));
+        let mut reversed_config = config.clone();
+        reversed_config.augmentation.jpeg_qualities = [80, 20];
+        assert!(reversed_config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("jpeg_qualities"));
+    }
+
+    #[test]
+    fn find_flat_region() {
+        // Noisy on the left half, flat on the right half
+        let luma: Vec<u8> = (0..64 * 32)
+            .map(|i| match i % 64 < 32 {
+                true => (i * 37 % 256) as u8,
+                false => 128,
+            })
+            .collect();
+        let (x, _) = find_low_texture_region(&luma, 64, 32, 16, 16, 10.0).unwrap();
+        assert!(x >= 32);
+        assert_eq!(find_low_texture_region(&luma, 64, 32, 64, 16, 10.0), None);
+    }
+}
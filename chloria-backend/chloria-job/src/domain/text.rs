@@ -0,0 +1,234 @@
+use unicode_normalization::{char::canonical_combining_class, UnicodeNormalization};
+
+// Characters which take no space but are not control characters
+const ZERO_WIDTH_CHARACTERS: [char; 6] = ['\u{200B}', '\u{200C}', '\u{200D}', '\u{2060}', '\u{FEFF}', '\u{00AD}'];
+
+#[derive(Clone)]
+pub(crate) enum NormalizationForm {
+    Nfkc,
+    // Replacements tried from the longest source, e.g. `ｶﾞ` to `ガ` before `ｶ` to `カ`
+    Table(Vec<(String, String)>),
+}
+
+impl NormalizationForm {
+    // One replacement per line, with the source and the target separated by a tab
+    pub(crate) fn parse_table(table: &str) -> Self {
+        let mut replacements: Vec<(String, String)> = table
+            .lines()
+            .filter_map(|l| l.split_once('\t'))
+            .filter(|(source, _)| !source.is_empty())
+            .map(|(source, target)| (source.to_string(), target.to_string()))
+            .collect();
+        replacements.sort_by_key(|(source, _)| std::cmp::Reverse(source.chars().count()));
+        Self::Table(replacements)
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct TextNormalizer {
+    pub(crate) forms: Vec<NormalizationForm>, // Applied in order
+    pub(crate) whitespace: bool,              // Turn every kind of space into ASCII spaces, collapse and trim them
+    pub(crate) line_breaks: bool,             // Turn every kind of line break into `\n` and collapse blank lines
+    pub(crate) control_characters: bool,      // Strip control and zero-width characters
+}
+
+pub(crate) struct NormalizedText {
+    pub(crate) text: String,
+    // Offset in `text` of each character offset of the original text (including the end),
+    // used to move annotations along with the text
+    pub(crate) offsets: Vec<usize>,
+}
+
+// Each character is kept along with the offset of the original character it comes from
+type TracedChars = Vec<(char, usize)>;
+
+impl TextNormalizer {
+    pub(crate) fn is_enabled(&self) -> bool {
+        !self.forms.is_empty() || self.whitespace || self.line_breaks || self.control_characters
+    }
+
+    // Returns the normalized text, along with the original one if normalization has changed it
+    pub(crate) fn normalize_keeping_original(&self, text: Option<String>) -> (Option<String>, Option<String>) {
+        let Some(text) = text else {
+            return (None, None);
+        };
+        if !self.is_enabled() {
+            return (Some(text), None);
+        }
+        let normalized_text = self.normalize(&text).text;
+        match normalized_text == text {
+            true => (Some(text), None),
+            false => (Some(normalized_text), Some(text)),
+        }
+    }
+
+    pub(crate) fn normalize(&self, text: &str) -> NormalizedText {
+        let mut chars: TracedChars = text.chars().enumerate().map(|(i, c)| (c, i)).collect();
+        let original_length = chars.len();
+        if self.line_breaks {
+            chars = Self::unify_line_breaks(chars);
+        }
+        if self.control_characters {
+            chars.retain(|(c, _)| {
+                let is_control = c.is_control() && !matches!(c, '\n' | '\t');
+                !is_control && !ZERO_WIDTH_CHARACTERS.contains(c)
+            });
+        }
+        for form in &self.forms {
+            chars = match form {
+                NormalizationForm::Nfkc => Self::apply_nfkc(chars),
+                NormalizationForm::Table(replacements) => Self::apply_table(chars, replacements),
+            };
+        }
+        if self.whitespace {
+            chars = Self::canonicalize_whitespace(chars);
+        }
+        if self.line_breaks {
+            chars = Self::collapse_line_breaks(chars);
+        }
+        let mut offsets = Vec::with_capacity(original_length + 1);
+        let mut index = 0;
+        for original_index in 0..=original_length {
+            while index < chars.len() && chars[index].1 < original_index {
+                index += 1;
+            }
+            offsets.push(index);
+        }
+        NormalizedText {
+            text: chars.into_iter().map(|(c, _)| c).collect(),
+            offsets,
+        }
+    }
+
+    fn unify_line_breaks(chars: TracedChars) -> TracedChars {
+        let mut unified_chars = Vec::with_capacity(chars.len());
+        let mut iter = chars.into_iter().peekable();
+        while let Some((c, i)) = iter.next() {
+            match c {
+                '\r' => {
+                    iter.next_if(|(c, _)| *c == '\n');
+                    unified_chars.push(('\n', i));
+                }
+                '\u{0085}' | '\u{2028}' | '\u{2029}' | '\u{000B}' | '\u{000C}' => unified_chars.push(('\n', i)),
+                _ => unified_chars.push((c, i)),
+            }
+        }
+        unified_chars
+    }
+
+    fn apply_nfkc(chars: TracedChars) -> TracedChars {
+        // Characters are normalized in clusters, so that marks (e.g. half-width dakuten) are composed with their base
+        let mut clusters: Vec<(String, usize)> = vec![];
+        for (c, i) in chars {
+            let is_mark = c.nfkd().next().is_some_and(|d| canonical_combining_class(d) != 0);
+            match clusters.last_mut() {
+                Some((cluster, _)) if is_mark => cluster.push(c),
+                _ => clusters.push((c.to_string(), i)),
+            }
+        }
+        clusters
+            .into_iter()
+            .flat_map(|(cluster, i)| cluster.nfkc().map(move |c| (c, i)).collect::<Vec<_>>())
+            .collect()
+    }
+
+    fn apply_table(chars: TracedChars, replacements: &[(String, String)]) -> TracedChars {
+        let text: Vec<char> = chars.iter().map(|(c, _)| *c).collect();
+        let mut replaced_chars = Vec::with_capacity(chars.len());
+        let mut index = 0;
+        'outer: while index < chars.len() {
+            for (source, target) in replacements {
+                let source: Vec<char> = source.chars().collect();
+                if text[index..].starts_with(&source) {
+                    replaced_chars.extend(target.chars().map(|c| (c, chars[index].1)));
+                    index += source.len();
+                    continue 'outer;
+                }
+            }
+            replaced_chars.push(chars[index]);
+            index += 1;
+        }
+        replaced_chars
+    }
+
+    fn canonicalize_whitespace(chars: TracedChars) -> TracedChars {
+        let mut canonical_chars: TracedChars = Vec::with_capacity(chars.len());
+        for (c, i) in chars {
+            match c {
+                '\n' => {
+                    // Trim the end of the line
+                    if canonical_chars.last().is_some_and(|(c, _)| *c == ' ') {
+                        canonical_chars.pop();
+                    }
+                    canonical_chars.push((c, i));
+                }
+                _ if c.is_whitespace() => {
+                    // Trim the start of the line and collapse consecutive spaces
+                    if canonical_chars.last().is_some_and(|(c, _)| *c != ' ' && *c != '\n') {
+                        canonical_chars.push((' ', i));
+                    }
+                }
+                _ => canonical_chars.push((c, i)),
+            }
+        }
+        if canonical_chars.last().is_some_and(|(c, _)| *c == ' ') {
+            canonical_chars.pop();
+        }
+        canonical_chars
+    }
+
+    fn collapse_line_breaks(chars: TracedChars) -> TracedChars {
+        let mut collapsed_chars: TracedChars = Vec::with_capacity(chars.len());
+        let mut line_breaks_count = 0;
+        for (c, i) in chars {
+            match c {
+                '\n' => {
+                    line_breaks_count += 1;
+                    // Keep at most one blank line, and none at the start
+                    if line_breaks_count <= 2 && !collapsed_chars.is_empty() {
+                        collapsed_chars.push((c, i));
+                    }
+                }
+                _ => {
+                    line_breaks_count = 0;
+                    collapsed_chars.push((c, i));
+                }
+            }
+        }
+        while collapsed_chars.last().is_some_and(|(c, _)| *c == '\n') {
+            collapsed_chars.pop();
+        }
+        collapsed_chars
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NormalizationForm, TextNormalizer};
+
+    #[test]
+    fn normalize_with_offsets() {
+        let normalizer = TextNormalizer {
+            forms: vec![NormalizationForm::Nfkc],
+            whitespace: true,
+            line_breaks: true,
+            control_characters: true,
+        };
+        let normalized = normalizer.normalize("\u{3000}ＡＢＣ\u{200B}とｶﾞｲﾄﾞ\r\n\r\n\r\n\u{00A0}end ");
+        assert_eq!(normalized.text, "ABCとガイド\n\nend");
+        assert_eq!(normalized.offsets[5], 3); // `と`
+        assert_eq!(normalized.offsets[6], 4); // `ｶ`, composed with the following `ﾞ` into `ガ`
+        assert_eq!(normalized.offsets[7], 5);
+        assert_eq!(normalized.offsets[8], 5); // `ｲ`
+        assert_eq!(normalized.offsets[22], normalized.text.chars().count());
+        // The table is applied to the output of NFKC
+        let normalizer = TextNormalizer {
+            forms: vec![
+                NormalizationForm::Nfkc,
+                NormalizationForm::parse_table("~\t〜\nガイド\tガイダンス\n"),
+            ],
+            ..TextNormalizer::default()
+        };
+        assert_eq!(normalizer.normalize("ｶﾞｲﾄﾞ～").text, "ガイダンス〜");
+    }
+}
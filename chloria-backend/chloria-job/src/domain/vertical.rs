@@ -0,0 +1,91 @@
+// Characters turned 90° clockwise in vertical lines, as their vertical forms are
+// Ref: https://www.w3.org/TR/jlreq/#positioning_of_punctuation_marks_and_brackets_in_vertical_writing_mode
+const ROTATED_CHARACTERS: &str = "ー－―—‐-~〜～…‥「」『』（）()【】〔〕［］[]｛｝{}〈〉《》＝=：:；;";
+// Punctuation marks sit at the top right of their cells in vertical lines instead of the bottom left
+const CORNER_PUNCTUATION_MARKS: &str = "、。，．,.";
+// Small kana move slightly towards the top right of their cells
+const SMALL_KANA: &str = "ぁぃぅぇぉっゃゅょゎァィゥェォッャュョヮヵヶ";
+// Numbers of at most this many digits are written across the line (tate-chū-yoko)
+// Ref: https://www.w3.org/TR/jlreq/#handling_of_tatechuyoko
+const HORIZONTAL_NUMBER_MAX_DIGITS: usize = 2;
+
+// How the characters of a cell are drawn in a vertical line, every cell being a square as wide as the font size
+#[derive(Debug, PartialEq)]
+pub(crate) enum VerticalCell {
+    Upright(char), // Like kanji and kana
+    Rotated(char), // Turned 90° clockwise
+    Shifted {
+        character: char,
+        offset: [f32; 2], // Relative to the font size, towards the top right
+    },
+    Horizontal(String), // Squeezed into a single cell if wider than it
+}
+
+impl VerticalCell {
+    pub(crate) fn text(&self) -> String {
+        match self {
+            Self::Upright(c) | Self::Rotated(c) | Self::Shifted { character: c, .. } => c.to_string(),
+            Self::Horizontal(text) => text.clone(),
+        }
+    }
+}
+
+// Short numbers take a single cell, while the digits of longer ones are laid out like the rest of the line,
+// half-width ones being rotated like other Latin characters
+pub(crate) fn vertical_cells(line: &str) -> Vec<VerticalCell> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut cells = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let digits_num = chars[i..].iter().take_while(|c| is_digit(**c)).count();
+        if digits_num > 0 && digits_num <= HORIZONTAL_NUMBER_MAX_DIGITS {
+            cells.push(VerticalCell::Horizontal(chars[i..i + digits_num].iter().collect()));
+            i += digits_num;
+            continue;
+        }
+        cells.extend(chars[i..i + digits_num.max(1)].iter().map(|c| vertical_cell(*c)));
+        i += digits_num.max(1);
+    }
+    cells
+}
+
+fn vertical_cell(c: char) -> VerticalCell {
+    match c {
+        _ if ROTATED_CHARACTERS.contains(c) || c.is_ascii_alphanumeric() => VerticalCell::Rotated(c),
+        _ if CORNER_PUNCTUATION_MARKS.contains(c) => VerticalCell::Shifted {
+            character: c,
+            offset: [0.6, -0.6],
+        },
+        _ if SMALL_KANA.contains(c) => VerticalCell::Shifted {
+            character: c,
+            offset: [0.1, -0.1],
+        },
+        _ => VerticalCell::Upright(c),
+    }
+}
+
+fn is_digit(c: char) -> bool {
+    c.is_ascii_digit() || ('０'..='９').contains(&c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{vertical_cells, VerticalCell};
+
+    #[test]
+    fn split_vertical_cells() {
+        let cells: Vec<String> = vertical_cells("第12回「コーヒー」、2025年")
+            .iter()
+            .map(|c| c.text())
+            .collect();
+        assert_eq!(
+            cells,
+            ["第", "12", "回", "「", "コ", "ー", "ヒ", "ー", "」", "、", "2", "0", "2", "5", "年"]
+        );
+        let cells = vertical_cells("ーっ。１２３");
+        assert_eq!(cells[0], VerticalCell::Rotated('ー'));
+        assert!(matches!(cells[1], VerticalCell::Shifted { character: 'っ', .. }));
+        assert!(matches!(cells[2], VerticalCell::Shifted { character: '。', .. }));
+        assert_eq!(cells[3], VerticalCell::Upright('１'));
+    }
+}
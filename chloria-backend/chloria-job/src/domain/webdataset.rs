@@ -0,0 +1,175 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use super::{annotation::TextAnnotation, export::SampleKind, split::Split};
+
+// Tar files are made of 512-byte blocks, with a header block before each file and 2 empty blocks at the end
+// Ref: https://www.gnu.org/software/tar/manual/html_node/Standard.html
+const TAR_BLOCK_SIZE: usize = 512;
+const TAR_END_SIZE: usize = 2 * TAR_BLOCK_SIZE;
+
+pub(crate) const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+// What a set of shards has been exported with, so that an interrupted export is only resumed with the same options
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub(crate) struct ShardOptions {
+    pub(crate) kind: SampleKind,
+    pub(crate) source_name: Option<String>,
+    pub(crate) created_from: Option<DateTime<Local>>,
+    pub(crate) created_to: Option<DateTime<Local>>,
+    pub(crate) limit: Option<usize>,
+    #[serde(default)]
+    pub(crate) split: Option<Split>,
+    pub(crate) max_shard_size: usize, // In bytes
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub(crate) struct ShardEntry {
+    pub(crate) name: String, // Relative to the manifest
+    pub(crate) sample_count: usize,
+    pub(crate) byte_size: usize,
+    pub(crate) last_sample_id: i32, // Samples are packed in the order of their IDs
+}
+
+// Written next to the shards after each of them is uploaded, which is what an interrupted export resumes from
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub(crate) struct ShardManifest {
+    pub(crate) options: ShardOptions,
+    pub(crate) shards: Vec<ShardEntry>,
+    pub(crate) complete: bool, // Whether every sample has been packed
+}
+
+impl ShardManifest {
+    pub(crate) fn new(options: ShardOptions) -> Self {
+        Self {
+            options,
+            shards: vec![],
+            complete: false,
+        }
+    }
+
+    pub(crate) fn sample_count(&self) -> usize {
+        self.shards.iter().map(|s| s.sample_count).sum()
+    }
+
+    // Samples up to this ID are already in shards
+    pub(crate) fn last_sample_id(&self) -> i32 {
+        self.shards.last().map(|s| s.last_sample_id).unwrap_or_default()
+    }
+
+    // Zero-padded so that shards sort in order, e.g. `shard-000042.tar`
+    pub(crate) fn next_shard_name(&self) -> String {
+        format!("shard-{:06}.tar", self.shards.len())
+    }
+}
+
+// Label of a sample, stored next to its image under the same key
+#[derive(Serialize)]
+pub(crate) struct SampleLabel<'l> {
+    pub(crate) id: i32,
+    pub(crate) kind: SampleKind,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) text: &'l str, // With line breaks between lines
+    pub(crate) annotation: &'l TextAnnotation,
+    pub(crate) split: Option<Split>, // Of the news the text comes from, if assigned
+}
+
+// Files of the samples of the shard being built, along with the size of its tar file
+pub(crate) struct ShardBuffer {
+    max_size: usize,
+    files: Vec<(String, Vec<u8>)>,
+    size: usize,
+    sample_count: usize,
+    last_sample_id: i32,
+}
+
+impl ShardBuffer {
+    pub(crate) fn new(max_size: usize) -> Self {
+        Self {
+            max_size,
+            files: vec![],
+            size: TAR_END_SIZE,
+            sample_count: 0,
+            last_sample_id: 0,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.sample_count == 0
+    }
+
+    // A sample larger than the maximum size never fits, so it is packed alone rather than dropped
+    pub(crate) fn fits(&self, files: &[(String, Vec<u8>)]) -> bool {
+        self.is_empty()
+            || self.size + files.iter().map(|(_, b)| tar_entry_size(b.len())).sum::<usize>() <= self.max_size
+    }
+
+    // Files of a sample share its key before the first dot, which is how WebDataset groups them back
+    pub(crate) fn push(&mut self, sample_id: i32, files: Vec<(String, Vec<u8>)>) {
+        self.size += files.iter().map(|(_, b)| tar_entry_size(b.len())).sum::<usize>();
+        self.files.extend(files);
+        self.sample_count += 1;
+        self.last_sample_id = sample_id;
+    }
+
+    // Empties the buffer, returning the files to pack and the entry of the shard in the manifest
+    pub(crate) fn take(&mut self, name: String) -> (Vec<(String, Vec<u8>)>, ShardEntry) {
+        let entry = ShardEntry {
+            name,
+            sample_count: self.sample_count,
+            byte_size: self.size,
+            last_sample_id: self.last_sample_id,
+        };
+        let files = std::mem::take(&mut self.files);
+        *self = Self::new(self.max_size);
+        (files, entry)
+    }
+}
+
+fn tar_entry_size(file_size: usize) -> usize {
+    TAR_BLOCK_SIZE + file_size.div_ceil(TAR_BLOCK_SIZE) * TAR_BLOCK_SIZE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ShardBuffer, ShardManifest, ShardOptions};
+    use crate::domain::export::SampleKind;
+
+    #[test]
+    fn fill_shards() {
+        let mut manifest = ShardManifest::new(ShardOptions {
+            kind: SampleKind::Synthetic,
+            source_name: None,
+            created_from: None,
+            created_to: None,
+            limit: None,
+            split: None,
+            max_shard_size: 5120,
+        });
+        let mut buffer = ShardBuffer::new(manifest.options.max_shard_size);
+        let sample = |key: &str, size: usize| {
+            vec![
+                (format!("{}.png", key), vec![0; size]),
+                (format!("{}.json", key), vec![0; 10]),
+            ]
+        };
+        // 1024 bytes at the end, then 512 bytes of header and 512 bytes of content for each of these files
+        buffer.push(1, sample("synthetic_1", 500));
+        assert!(buffer.fits(&sample("synthetic_2", 500)));
+        assert!(!buffer.fits(&sample("synthetic_2", 600)));
+        let (files, entry) = buffer.take(manifest.next_shard_name());
+        assert_eq!(files.len(), 2);
+        assert_eq!(
+            (entry.name.as_str(), entry.byte_size, entry.last_sample_id),
+            ("shard-000000.tar", 3072, 1)
+        );
+        manifest.shards.push(entry);
+        assert!(buffer.is_empty());
+        assert!(buffer.fits(&sample("synthetic_3", 10000)));
+        assert_eq!(manifest.next_shard_name(), "shard-000001.tar");
+        assert_eq!((manifest.sample_count(), manifest.last_sample_id()), (1, 1));
+        let json = serde_json::to_string(&manifest).unwrap();
+        assert_eq!(serde_json::from_str::<ShardManifest>(&json).unwrap(), manifest);
+    }
+}
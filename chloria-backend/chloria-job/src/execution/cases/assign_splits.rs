@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::{
+    super::{
+        ports::repository::{InsertNewsSplitInput, Repository, SelectUnassignedNewsInput},
+        workshop::Workshop,
+    },
+    LocalCase,
+};
+use crate::domain::split::{Split, SplitAssigner};
+
+#[derive(Default)]
+pub(crate) struct AssignSplitsCaseOutput {
+    pub(crate) train_count: usize, // News assigned by this run, per split
+    pub(crate) validation_count: usize,
+    pub(crate) test_count: usize,
+}
+
+struct AssignSplitsCase {
+    repository: Arc<dyn Repository>,
+    split_assigner: Arc<SplitAssigner>,
+    select_batch_size: i64,
+}
+
+impl Workshop {
+    pub(crate) async fn execute_assign_splits_case(&self, select_batch_size: i64) -> Result<AssignSplitsCaseOutput> {
+        let case = AssignSplitsCase {
+            repository: Arc::clone(&self.repository),
+            split_assigner: Arc::clone(&self.split_assigner),
+            select_batch_size,
+        };
+        self.run_local_case(case).await
+    }
+}
+
+#[async_trait(?Send)]
+impl LocalCase for AssignSplitsCase {
+    type Output = AssignSplitsCaseOutput;
+
+    async fn execute(self) -> Result<Self::Output> {
+        let mut output = AssignSplitsCaseOutput::default();
+        let mut after_news_id = 0;
+        loop {
+            let news = self
+                .repository
+                .select_unassigned_news(SelectUnassignedNewsInput {
+                    after_news_id,
+                    limit: self.select_batch_size,
+                })
+                .await?;
+            let Some(last_news) = news.last() else {
+                break;
+            };
+            after_news_id = last_news.news_id;
+            let inputs: Vec<InsertNewsSplitInput> = news
+                .into_iter()
+                .map(|n| {
+                    let group_key =
+                        self.split_assigner
+                            .group_key(&n.source_name, &n.article_id, n.cluster_id.unwrap_or(n.news_id));
+                    InsertNewsSplitInput {
+                        news_id: n.news_id,
+                        split: self.split_assigner.assign(&group_key),
+                        group_key,
+                    }
+                })
+                .collect();
+            for input in &inputs {
+                match input.split {
+                    Split::Train => output.train_count += 1,
+                    Split::Validation => output.validation_count += 1,
+                    Split::Test => output.test_count += 1,
+                }
+            }
+            self.repository.insert_news_splits(inputs).await?;
+        }
+        Ok(output)
+    }
+}
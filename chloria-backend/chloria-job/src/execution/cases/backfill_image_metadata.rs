@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use log::error;
+
+use super::{
+    super::{
+        ports::{
+            file_storage::{DownloadFileInput, FileObjectKind, FileStorage},
+            image_analyzer::ImageAnalyzer,
+            repository::{InsertNewsImageInput, Repository, SelectNewsImagePathsInput},
+        },
+        workshop::Workshop,
+    },
+    LocalCase,
+};
+
+type BackfillImageMetadataCaseOutput = (usize, usize);
+
+struct BackfillImageMetadataCase {
+    image_analyzer: Arc<dyn ImageAnalyzer>,
+    file_storage: Arc<dyn FileStorage>,
+    repository: Arc<dyn Repository>,
+    select_batch_size: i64,
+}
+
+impl Workshop {
+    pub(crate) async fn execute_backfill_image_metadata_case(
+        &self,
+        select_batch_size: i64,
+    ) -> Result<BackfillImageMetadataCaseOutput> {
+        let case = BackfillImageMetadataCase {
+            image_analyzer: Arc::clone(&self.image_analyzer),
+            file_storage: Arc::clone(&self.file_storage),
+            repository: Arc::clone(&self.repository),
+            select_batch_size,
+        };
+        self.run_local_case(case).await
+    }
+}
+
+#[async_trait(?Send)]
+impl LocalCase for BackfillImageMetadataCase {
+    type Output = BackfillImageMetadataCaseOutput;
+
+    async fn execute(self) -> Result<Self::Output> {
+        let mut total_image_count = 0;
+        let mut backfilled_image_count = 0;
+        let mut after_news_id = 0;
+        loop {
+            let outputs = self
+                .repository
+                .select_news_image_paths(SelectNewsImagePathsInput {
+                    after_news_id,
+                    limit: self.select_batch_size,
+                })
+                .await?;
+            let Some(last_output) = outputs.last() else {
+                break;
+            };
+            after_news_id = last_output.news_id;
+            total_image_count += outputs.len();
+            for output in outputs {
+                let metadata = match self
+                    .file_storage
+                    .download_file(DownloadFileInput {
+                        kind: FileObjectKind::Origin,
+                        path: output.image_path.clone(),
+                    })
+                    .await
+                    .and_then(|bytes| self.image_analyzer.analyze(&bytes))
+                {
+                    Ok(metadata) => metadata,
+                    Err(error) => {
+                        error!("news_id={}, error={}", output.news_id, error);
+                        continue;
+                    }
+                };
+                let input = InsertNewsImageInput {
+                    position: 0,
+                    caption: None,
+                    alt_text: None,
+                    path: output.image_path,
+                    width: metadata.width,
+                    height: metadata.height,
+                    mime_type: metadata.mime_type,
+                    byte_size: metadata.byte_size,
+                    sha256: metadata.sha256,
+                    perceptual_hash: metadata.perceptual_hash,
+                };
+                match self.repository.insert_news_images(output.news_id, vec![input]).await {
+                    Ok(()) => backfilled_image_count += 1,
+                    Err(error) => error!("news_id={}, error={}", output.news_id, error),
+                }
+            }
+        }
+        Ok((total_image_count, backfilled_image_count))
+    }
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::future::join_all;
+use log::{error, info};
+
+use super::{
+    super::{
+        ports::{
+            news_fetcher::NewsFetcher,
+            repository::{Repository, SelectNewsLinksInput},
+        },
+        workshop::Workshop,
+    },
+    ImageSaver, LocalCase,
+};
+
+type BackfillMissingImagesCaseOutput = (usize, usize);
+
+struct BackfillMissingImagesCase {
+    news_fetchers: Vec<Arc<dyn NewsFetcher>>,
+    image_saver: ImageSaver,
+    repository: Arc<dyn Repository>,
+    select_batch_size: i64,
+}
+
+impl Workshop {
+    pub(crate) async fn execute_backfill_missing_images_case(
+        &self,
+        select_batch_size: i64,
+    ) -> Result<BackfillMissingImagesCaseOutput> {
+        let case = BackfillMissingImagesCase {
+            news_fetchers: self.news_fetchers.iter().map(Arc::clone).collect(),
+            image_saver: ImageSaver::new(self),
+            repository: Arc::clone(&self.repository),
+            select_batch_size,
+        };
+        self.run_local_case(case).await
+    }
+}
+
+#[async_trait(?Send)]
+impl LocalCase for BackfillMissingImagesCase {
+    type Output = BackfillMissingImagesCaseOutput;
+
+    async fn execute(self) -> Result<Self::Output> {
+        let mut total_news_count = 0;
+        let mut backfilled_news_count = 0;
+        let mut after_news_id = 0;
+        loop {
+            let outputs = self
+                .repository
+                .select_news_links(SelectNewsLinksInput {
+                    after_news_id,
+                    limit: self.select_batch_size,
+                })
+                .await?;
+            let Some(last_output) = outputs.last() else {
+                break;
+            };
+            after_news_id = last_output.news_id;
+            total_news_count += outputs.len();
+            for output in outputs {
+                // Only the fetcher of the same source knows how to scrape its pages
+                let Some(news_fetcher) = self
+                    .news_fetchers
+                    .iter()
+                    .find(|f| f.source_name() == output.source_name)
+                else {
+                    // No fetcher of this source is enabled, so its pages cannot be scraped
+                    info!(
+                        "news_id={}, unfetchable_source_name={}",
+                        output.news_id, output.source_name
+                    );
+                    continue;
+                };
+                let images = match news_fetcher.fetch_images(&output.link).await {
+                    Ok(images) => images,
+                    Err(error) => {
+                        error!("news_id={}, error={}", output.news_id, error);
+                        continue;
+                    }
+                };
+                let images: Vec<_> = join_all(images.into_iter().enumerate().map(|(position, image)| {
+                    self.image_saver
+                        .save(image, position, &output.source_name, &output.article_id)
+                }))
+                .await
+                .into_iter()
+                .flatten()
+                .collect();
+                if images.is_empty() {
+                    continue;
+                }
+                match self.repository.insert_news_images(output.news_id, images).await {
+                    Ok(()) => backfilled_news_count += 1,
+                    Err(error) => error!("news_id={}, error={}", output.news_id, error),
+                }
+            }
+        }
+        Ok((total_news_count, backfilled_news_count))
+    }
+}
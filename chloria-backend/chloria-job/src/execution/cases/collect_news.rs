@@ -2,30 +2,44 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use async_trait::async_trait;
-use chrono::Local;
-use log::error;
+use futures::future::join_all;
+use log::{error, info};
 use tokio::sync::{mpsc, Semaphore};
 
 use super::{
     super::{
         ports::{
-            file_storage::{FileObjectKind, FileStorage, UploadFileInput},
-            http_helper::HttpHelper,
+            article_extractor::{ArticleExtractor, ExtractArticleInput},
+            file_storage::{DeleteFilesInput, FileObjectKind, FileStorage},
             news_fetcher::NewsFetcher,
-            repository::{InsertNewsInput, Repository},
+            repository::{InsertNewsInput, InsertNewsSentenceInput, Repository},
         },
         workshop::Workshop,
     },
-    LocalCase,
+    archive_raw_document, normalize_long_text, ImageSaver, LocalCase,
+};
+use crate::domain::{
+    difficulty::DifficultyScorer,
+    fingerprint::SimHash,
+    news::NewsEntity,
+    quality::{QualityAction, QualityInput, QualityRuleSet},
+    sentence::split_sentences,
+    statistics::CharacterStatistics,
+    text::TextNormalizer,
 };
-use crate::domain::news::NewsEntity;
 
 type CollectNewsCaseOutput = (usize, usize);
 
 struct CollectNewsCase {
     news_fetchers: Vec<Arc<dyn NewsFetcher>>,
-    http_helper: Arc<dyn HttpHelper>,
+    article_extractor: Arc<dyn ArticleExtractor>,
+    text_normalizer: Arc<TextNormalizer>,
+    quality_rule_set: Arc<QualityRuleSet>,
+    difficulty_scorer: Arc<DifficultyScorer>,
+    image_saver: ImageSaver,
     file_storage: Arc<dyn FileStorage>,
+    archive_raw_html: bool,
+    archive_raw_feed: bool,
     repository: Arc<dyn Repository>,
     task_permits_num: usize,
     insert_batch_size: usize,
@@ -38,9 +52,15 @@ impl Workshop {
         insert_batch_size: usize,
     ) -> Result<CollectNewsCaseOutput> {
         let case = CollectNewsCase {
-            news_fetchers: self.news_fetchers.iter().map(|f| Arc::clone(f)).collect(),
-            http_helper: Arc::clone(&self.http_helper),
+            news_fetchers: self.news_fetchers.iter().map(Arc::clone).collect(),
+            article_extractor: Arc::clone(&self.article_extractor),
+            text_normalizer: Arc::clone(&self.text_normalizer),
+            quality_rule_set: Arc::clone(&self.quality_rule_set),
+            difficulty_scorer: Arc::clone(&self.difficulty_scorer),
+            image_saver: ImageSaver::new(self),
             file_storage: Arc::clone(&self.file_storage),
+            archive_raw_html: self.archive_raw_html,
+            archive_raw_feed: self.archive_raw_feed,
             repository: Arc::clone(&self.repository),
             task_permits_num,
             insert_batch_size,
@@ -57,73 +77,161 @@ impl LocalCase for CollectNewsCase {
         const CHANNEL_CAPACITY: usize = 100;
         let (sender, mut receiver) = mpsc::channel(CHANNEL_CAPACITY);
         // Save news to the database
-        let receiver_handle = tokio::spawn(async move {
+        let repository = Arc::clone(&self.repository);
+        let archive_storage = Arc::clone(&self.file_storage);
+        let insert_batch_size = self.insert_batch_size;
+        let receiver_handle = tokio::task::spawn_local(async move {
             let mut insert_news_inputs = vec![];
             let mut inserted_news_count = 0;
             while let Some(input) = receiver.recv().await {
                 insert_news_inputs.push(input);
-                if insert_news_inputs.len() >= self.insert_batch_size {
-                    let inputs = insert_news_inputs.drain(..).collect();
-                    match self.repository.insert_news(inputs).await {
-                        Ok(news_ids) => {
-                            inserted_news_count += news_ids.len();
-                        }
-                        Err(error) => error!("error={}", error),
-                    }
+                if insert_news_inputs.len() >= insert_batch_size {
+                    let inputs = std::mem::take(&mut insert_news_inputs);
+                    inserted_news_count += insert_news(&*repository, &*archive_storage, inputs).await;
                 }
             }
             // Remaining news after the channel closed
-            match self.repository.insert_news(insert_news_inputs).await {
-                Ok(news_ids) => {
-                    inserted_news_count += news_ids.len();
-                }
-                Err(error) => error!("error={}", error),
-            }
+            inserted_news_count += insert_news(&*repository, &*archive_storage, insert_news_inputs).await;
             inserted_news_count
         });
         // Fetch news from providers
         let semaphore = Arc::new(Semaphore::new(self.task_permits_num));
         let mut total_news_count = 0;
         for news_fetcher in self.news_fetchers {
-            let http_helper = Arc::clone(&self.http_helper);
+            let article_extractor = Arc::clone(&self.article_extractor);
+            let text_normalizer = Arc::clone(&self.text_normalizer);
+            let quality_rule_set = Arc::clone(&self.quality_rule_set);
+            let difficulty_scorer = Arc::clone(&self.difficulty_scorer);
+            let image_saver = self.image_saver.clone();
             let file_storage = Arc::clone(&self.file_storage);
+            let (archive_raw_html, archive_raw_feed) = (self.archive_raw_html, self.archive_raw_feed);
             let sender = sender.clone();
             let semaphore = Arc::clone(&semaphore);
+            let image_fetcher = Arc::clone(&news_fetcher);
             let handles = news_fetcher
                 .fetch_news(Arc::new(move |article| {
                     let news = NewsEntity::new(article.id);
-                    let http_helper = Arc::clone(&http_helper);
+                    let article_extractor = Arc::clone(&article_extractor);
+                    let text_normalizer = Arc::clone(&text_normalizer);
+                    let quality_rule_set = Arc::clone(&quality_rule_set);
+                    let difficulty_scorer = Arc::clone(&difficulty_scorer);
+                    let image_saver = image_saver.clone();
                     let file_storage = Arc::clone(&file_storage);
                     let sender = sender.clone();
                     let semaphore = Arc::clone(&semaphore);
+                    let image_fetcher = Arc::clone(&image_fetcher);
                     tokio::task::spawn_local(async move {
                         let _permit = semaphore.acquire().await?;
-                        let image_path = match article.image_url {
-                            Some(image_url) => match http_helper.get(&image_url).await {
-                                Ok(image_bytes) => file_storage
-                                    .upload_file(UploadFileInput {
-                                        kind: FileObjectKind::Origin,
-                                        source_name: article.source_name.clone(),
-                                        key: format!("{}.jpg", news.article_id),
-                                        bytes: image_bytes,
-                                        created_time: Local::now(),
-                                    })
-                                    .await
-                                    .ok(),
-                                Err(_) => None,
+                        let (long_text, document, extractor_version) = match (article.long_text, &article.raw_html) {
+                            (Some(long_text), _) => (Some(long_text), None, None),
+                            (None, Some(raw_html)) => match article_extractor.extract(ExtractArticleInput {
+                                source_name: article.source_name.clone(),
+                                html: raw_html.clone(),
+                            }) {
+                                Ok(output) => (
+                                    output.document.as_ref().map(|d| d.plain_text()),
+                                    output.document,
+                                    Some(article_extractor.version()),
+                                ),
+                                Err(error) => {
+                                    error!("article_id={}, error={}", news.article_id, error);
+                                    (None, None, None)
+                                }
                             },
-                            None => None,
+                            (None, None) => (None, None, None),
+                        };
+                        let (title, original_title) = text_normalizer.normalize_keeping_original(article.title);
+                        let (short_text, original_short_text) =
+                            text_normalizer.normalize_keeping_original(article.short_text);
+                        let (long_text, original_long_text, document) =
+                            normalize_long_text(&text_normalizer, long_text, document);
+                        let quality_reasons = quality_rule_set.evaluate(&QualityInput {
+                            title: title.as_deref(),
+                            short_text: short_text.as_deref(),
+                            long_text: long_text.as_deref(),
+                        });
+                        if !quality_reasons.is_empty() {
+                            info!("article_id={}, quality_reasons={:?}", news.article_id, quality_reasons);
+                            if let QualityAction::Reject = quality_rule_set.action {
+                                return Ok(());
+                            }
+                        }
+                        let simhash = long_text.as_deref().or(short_text.as_deref()).and_then(SimHash::new);
+                        let statistics = long_text.as_deref().map(CharacterStatistics::new);
+                        let difficulty = long_text.as_deref().and_then(|t| difficulty_scorer.score(t));
+                        let sentences = long_text
+                            .as_deref()
+                            .map(split_sentences)
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|sentence| InsertNewsSentenceInput {
+                                difficulty: difficulty_scorer.score(&sentence.text),
+                                sentence,
+                            })
+                            .collect();
+                        // Files are only saved for news which are not rejected
+                        let article_images = match (article.scrape_images, &article.link) {
+                            (true, Some(link)) => image_fetcher.fetch_images(link).await.unwrap_or_else(|error| {
+                                error!("article_id={}, error={}", news.article_id, error);
+                                vec![]
+                            }),
+                            _ => article.images,
+                        };
+                        let images = join_all(article_images.into_iter().enumerate().map(|(position, image)| {
+                            image_saver.save(image, position, &article.source_name, &news.article_id)
+                        }))
+                        .await
+                        .into_iter()
+                        .flatten()
+                        .collect();
+                        let raw_html_path = match archive_raw_html {
+                            true => {
+                                archive_raw_document(
+                                    &*file_storage,
+                                    FileObjectKind::RawHtml,
+                                    &article.source_name,
+                                    &news.article_id,
+                                    article.raw_html,
+                                )
+                                .await
+                            }
+                            false => None,
+                        };
+                        let raw_feed_path = match archive_raw_feed {
+                            true => {
+                                archive_raw_document(
+                                    &*file_storage,
+                                    FileObjectKind::RawFeed,
+                                    &article.source_name,
+                                    &news.article_id,
+                                    article.raw_feed,
+                                )
+                                .await
+                            }
+                            false => None,
                         };
                         sender
                             .send(InsertNewsInput {
                                 source_name: article.source_name,
                                 article_id: news.article_id,
                                 link: article.link,
-                                title: article.title,
-                                short_text: article.short_text,
-                                long_text: article.long_text,
-                                image_path,
+                                title,
+                                short_text,
+                                long_text,
+                                document,
+                                images,
                                 published_time: article.published_time,
+                                raw_html_path,
+                                raw_feed_path,
+                                extractor_version,
+                                original_title,
+                                original_short_text,
+                                original_long_text,
+                                quality_reasons,
+                                simhash,
+                                statistics,
+                                difficulty,
+                                sentences,
                             })
                             .await?;
                         Ok(())
@@ -138,6 +246,53 @@ impl LocalCase for CollectNewsCase {
     }
 }
 
+// Inserts a batch of news, deleting the raw documents archived for news which have not been inserted
+async fn insert_news(
+    repository: &dyn Repository,
+    file_storage: &dyn FileStorage,
+    inputs: Vec<InsertNewsInput>,
+) -> usize {
+    let archived_paths: Vec<(String, String, FileObjectKind, String)> = inputs
+        .iter()
+        .flat_map(|input| {
+            [
+                (FileObjectKind::RawHtml, &input.raw_html_path),
+                (FileObjectKind::RawFeed, &input.raw_feed_path),
+            ]
+            .into_iter()
+            .filter_map(|(kind, path)| {
+                path.clone()
+                    .map(|path| (input.source_name.clone(), input.article_id.clone(), kind, path))
+            })
+        })
+        .collect();
+    let outputs = match repository.insert_news(inputs).await {
+        Ok(outputs) => outputs,
+        Err(error) => {
+            error!("error={}", error);
+            vec![]
+        }
+    };
+    for (source_name, article_id, kind, path) in archived_paths {
+        if outputs
+            .iter()
+            .any(|o| (&o.source_name, &o.article_id) == (&source_name, &article_id))
+        {
+            continue;
+        }
+        if let Err(error) = file_storage
+            .delete_files(DeleteFilesInput {
+                kind,
+                paths: vec![path.clone()],
+            })
+            .await
+        {
+            error!("path={}, error={}", path, error);
+        }
+    }
+    outputs.len()
+}
+
 #[cfg(test)]
 mod tests {
     use std::{sync::Arc, time::Duration};
@@ -148,12 +303,22 @@ mod tests {
 
     use super::super::super::{
         ports::{
+            archiver::MockArchiver,
+            article_extractor::MockArticleExtractor,
             file_storage::MockFileStorage,
             http_helper::MockHttpHelper,
-            news_fetcher::{FetchNewsArticle, FetchNewsHandler, FetchNewsOutput, MockNewsFetcher},
-            repository::MockRepository,
+            image_analyzer::{AnalyzeImageOutput, MockImageAnalyzer},
+            image_augmenter::MockImageAugmenter,
+            news_fetcher::{FetchNewsArticle, FetchNewsHandler, FetchNewsImage, FetchNewsOutput, MockNewsFetcher},
+            repository::{InsertNewsInput, InsertNewsOutput, MockRepository},
+            text_renderer::MockTextRenderer,
         },
-        workshop::{Config, Workshop},
+        workshop::{Config, ImageStorageLayout, Workshop},
+    };
+    use super::insert_news;
+    use crate::domain::{
+        difficulty::DifficultyScorer, quality::QualityRuleSet, split::SplitAssigner, synthesis::SynthesisConfig,
+        text::TextNormalizer,
     };
 
     #[tokio::test]
@@ -167,18 +332,18 @@ mod tests {
         const TASK_PERMITS_NUM: usize = 10;
         const INSERT_BATCH_SIZE: usize = 5;
         // Some assumptions for simpler calculation
-        assert!(CASES_NUM > CASE_PERMITS_NUM);
-        assert!(PAGES_NUM * PAGE_NEWS_NUM > TASK_PERMITS_NUM);
-        assert!(NEWS_LOAD_DURATION > PAGE_LOAD_DURATION);
-        assert!(PAGES_NUM * NEWS_LOAD_DURATION > PAGE_LOAD_DURATION + NEWS_LOAD_DURATION);
+        const { assert!(CASES_NUM > CASE_PERMITS_NUM) };
+        const { assert!(PAGES_NUM * PAGE_NEWS_NUM > TASK_PERMITS_NUM) };
+        const { assert!(NEWS_LOAD_DURATION > PAGE_LOAD_DURATION) };
+        const { assert!(PAGES_NUM * NEWS_LOAD_DURATION > PAGE_LOAD_DURATION + NEWS_LOAD_DURATION) };
         async fn fetch_news(
             finish_fetch_time: Arc<Mutex<Option<DateTime<Local>>>>,
             handler: FetchNewsHandler,
         ) -> Vec<FetchNewsOutput> {
             let mut outputs = vec![];
-            for _ in 0..PAGES_NUM {
+            for page_index in 0..PAGES_NUM {
                 time::sleep(Duration::from_millis(PAGE_LOAD_DURATION as u64)).await;
-                for _ in 0..PAGE_NEWS_NUM {
+                for news_index in 0..PAGE_NEWS_NUM {
                     outputs.push(handler(FetchNewsArticle {
                         source_name: "NewsData".to_string(),
                         id: None,
@@ -186,8 +351,16 @@ mod tests {
                         title: None,
                         short_text: None,
                         long_text: None,
-                        image_url: Some("".to_string()),
+                        images: vec![FetchNewsImage {
+                            // Distinct urls, since the same url is only downloaded once per case
+                            url: format!("{}/{}", page_index, news_index),
+                            caption: None,
+                            alt_text: None,
+                        }],
+                        scrape_images: false,
                         published_time: None,
+                        raw_html: None,
+                        raw_feed: None,
                     }));
                 }
             }
@@ -202,7 +375,7 @@ mod tests {
             time::sleep(Duration::from_millis(NEWS_LOAD_DURATION as u64)).await;
             Ok(vec![])
         }
-        async fn insert_news(start_insert_time: Arc<Mutex<Option<DateTime<Local>>>>) -> Result<Vec<i32>> {
+        async fn insert_news(start_insert_time: Arc<Mutex<Option<DateTime<Local>>>>) -> Result<Vec<InsertNewsOutput>> {
             // Time when the first case starts inserting news
             let mut start_insert_time = start_insert_time.lock().await;
             if start_insert_time.is_none() {
@@ -223,6 +396,21 @@ mod tests {
             .expect_get()
             .times(CASES_NUM * PAGES_NUM * PAGE_NEWS_NUM)
             .returning(|_| Box::pin(get()));
+        let mut mock_image_analyzer = MockImageAnalyzer::new();
+        mock_image_analyzer
+            .expect_analyze()
+            .times(CASES_NUM * PAGES_NUM * PAGE_NEWS_NUM)
+            .returning(|_| {
+                Ok(AnalyzeImageOutput {
+                    width: 0,
+                    height: 0,
+                    mime_type: "".to_string(),
+                    extension: "".to_string(),
+                    byte_size: 0,
+                    sha256: "".to_string(),
+                    perceptual_hash: "".to_string(),
+                })
+            });
         let mut mock_file_storage = MockFileStorage::new();
         mock_file_storage
             .expect_upload_file()
@@ -239,11 +427,26 @@ mod tests {
         }
         let workshop = Workshop::new(
             vec![Arc::new(mock_news_fetcher)],
+            Arc::new(MockArticleExtractor::new()),
             Arc::new(mock_http_helper),
+            Arc::new(mock_image_analyzer),
             Arc::new(mock_file_storage),
             Arc::new(mock_repository),
+            Arc::new(MockTextRenderer::new()),
+            Arc::new(MockImageAugmenter::new()),
+            Arc::new(MockFileStorage::new()),
+            Arc::new(MockArchiver::new()),
             Config {
                 case_permits_num: CASE_PERMITS_NUM,
+                image_storage_layout: ImageStorageLayout::Dated,
+                archive_raw_html: false,
+                archive_raw_feed: false,
+                retention_rules: vec![],
+                text_normalizer: TextNormalizer::default(),
+                quality_rule_set: QualityRuleSet::default(),
+                difficulty_scorer: DifficultyScorer::default(),
+                synthesis_config: SynthesisConfig::default(),
+                split_assigner: SplitAssigner::default(),
             },
         );
         let start_time = Local::now();
@@ -268,4 +471,52 @@ mod tests {
         );
         Ok(())
     }
+
+    #[tokio::test]
+    async fn delete_archives_of_ignored_news() {
+        let input = |article_id: &str| InsertNewsInput {
+            source_name: "Yahoo".to_string(),
+            article_id: article_id.to_string(),
+            link: None,
+            title: None,
+            short_text: None,
+            long_text: None,
+            images: vec![],
+            published_time: None,
+            raw_html_path: Some(format!("{}.html", article_id)),
+            raw_feed_path: None,
+            document: None,
+            extractor_version: None,
+            original_title: None,
+            original_short_text: None,
+            original_long_text: None,
+            quality_reasons: vec![],
+            simhash: None,
+            statistics: None,
+            difficulty: None,
+            sentences: vec![],
+        };
+        let mut mock_repository = MockRepository::new();
+        mock_repository.expect_insert_news().times(1).returning(|_| {
+            Box::pin(async {
+                Ok(vec![InsertNewsOutput {
+                    source_name: "Yahoo".to_string(),
+                    article_id: "new".to_string(),
+                }])
+            })
+        });
+        let mut mock_file_storage = MockFileStorage::new();
+        mock_file_storage
+            .expect_delete_files()
+            .withf(|input| input.paths == ["existing.html"])
+            .times(1)
+            .returning(|_| Box::pin(async { Ok(()) }));
+        let inserted_news_count = insert_news(
+            &mock_repository,
+            &mock_file_storage,
+            vec![input("new"), input("existing")],
+        )
+        .await;
+        assert_eq!(inserted_news_count, 1);
+    }
 }
@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Local;
+use log::{error, info};
+
+use super::{
+    super::{
+        ports::{
+            file_storage::{DeleteFilesInput, FileObjectKind, FileStorage},
+            repository::{DeleteExpiredNewsInput, NewsFileKind, Repository},
+        },
+        workshop::{RetentionRule, RetentionTarget, Workshop},
+    },
+    LocalCase,
+};
+
+type EnforceRetentionCaseOutput = (usize, usize);
+
+struct EnforceRetentionCase {
+    file_storage: Arc<dyn FileStorage>,
+    repository: Arc<dyn Repository>,
+    rules: Vec<RetentionRule>,
+    delete_batch_size: i64,
+}
+
+impl Workshop {
+    pub(crate) async fn execute_enforce_retention_case(
+        &self,
+        delete_batch_size: i64,
+    ) -> Result<EnforceRetentionCaseOutput> {
+        let case = EnforceRetentionCase {
+            file_storage: Arc::clone(&self.file_storage),
+            repository: Arc::clone(&self.repository),
+            rules: self.retention_rules.clone(),
+            delete_batch_size,
+        };
+        self.run_local_case(case).await
+    }
+}
+
+#[async_trait(?Send)]
+impl LocalCase for EnforceRetentionCase {
+    type Output = EnforceRetentionCaseOutput;
+
+    async fn execute(self) -> Result<Self::Output> {
+        let mut expired_news_count = 0;
+        let mut deleted_file_count = 0;
+        for rule in self.rules {
+            // Nothing can be older than the earliest date there is
+            let Some(created_before) = Local::now().checked_sub_signed(rule.max_age) else {
+                continue;
+            };
+            loop {
+                let input = DeleteExpiredNewsInput {
+                    source_name: rule.source_name.clone(),
+                    created_before,
+                    limit: self.delete_batch_size,
+                };
+                // Rows are deleted first, so a failure while deleting files only leaves orphans behind
+                let output = match rule.target {
+                    RetentionTarget::News => self.repository.delete_expired_news(input).await?,
+                    RetentionTarget::File(kind) => {
+                        let kind = match kind {
+                            FileObjectKind::Origin => NewsFileKind::Image,
+                            FileObjectKind::RawHtml => NewsFileKind::RawHtml,
+                            FileObjectKind::RawFeed => NewsFileKind::RawFeed,
+                            FileObjectKind::Synthetic | FileObjectKind::Dataset => break, // Not referenced by news
+                        };
+                        self.repository.delete_expired_news_files(input, kind).await?
+                    }
+                };
+                if output.news_count == 0 {
+                    break;
+                }
+                info!(
+                    "source_name={:?}, news_count={}, unreferenced_paths.len={}",
+                    rule.source_name,
+                    output.news_count,
+                    output.unreferenced_paths.len()
+                );
+                expired_news_count += output.news_count;
+                for (kind, path) in output.unreferenced_paths {
+                    let kind = match kind {
+                        NewsFileKind::Image => FileObjectKind::Origin,
+                        NewsFileKind::RawHtml => FileObjectKind::RawHtml,
+                        NewsFileKind::RawFeed => FileObjectKind::RawFeed,
+                    };
+                    match self
+                        .file_storage
+                        .delete_files(DeleteFilesInput {
+                            kind,
+                            paths: vec![path.clone()],
+                        })
+                        .await
+                    {
+                        Ok(()) => deleted_file_count += 1,
+                        Err(error) => error!("path={}, error={}", path, error),
+                    }
+                }
+            }
+        }
+        Ok((expired_news_count, deleted_file_count))
+    }
+}
@@ -0,0 +1,197 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use log::error;
+
+use super::{
+    super::{
+        ports::{
+            file_storage::{DownloadFileInput, FileObjectKind, FileObjectPath, FileStorage, UploadFileInput},
+            image_analyzer::ImageAnalyzer,
+            image_augmenter::{CropImageInput, ImageAugmenter},
+            repository::{Repository, SelectExportSamplesInput},
+        },
+        workshop::{ExportDestination, ExportFilter, Workshop},
+    },
+    dataset_key, select_export_candidates, ExportCandidate, LocalCase,
+};
+use crate::domain::export::{AnnotationFormat, ExportedCrop, ExportedSample, SampleKind};
+
+pub(crate) struct ExportAnnotationsCaseOutput {
+    pub(crate) sample_count: usize,
+    pub(crate) skipped_sample_count: usize, // Samples without the annotation the format needs, or whose image failed
+    pub(crate) file_count: usize,           // Images, crops and label files written
+}
+
+struct ExportAnnotationsCase {
+    image_analyzer: Arc<dyn ImageAnalyzer>,
+    image_augmenter: Arc<dyn ImageAugmenter>,
+    file_storage: Arc<dyn FileStorage>,
+    local_file_storage: Arc<dyn FileStorage>,
+    repository: Arc<dyn Repository>,
+    format: AnnotationFormat,
+    kind: SampleKind,
+    filter: ExportFilter,
+    destination: ExportDestination,
+    select_batch_size: i64,
+}
+
+impl Workshop {
+    pub(crate) async fn execute_export_annotations_case(
+        &self,
+        format: AnnotationFormat,
+        kind: SampleKind,
+        filter: ExportFilter,
+        destination: ExportDestination,
+        select_batch_size: i64,
+    ) -> Result<ExportAnnotationsCaseOutput> {
+        let case = ExportAnnotationsCase {
+            image_analyzer: Arc::clone(&self.image_analyzer),
+            image_augmenter: Arc::clone(&self.image_augmenter),
+            file_storage: Arc::clone(&self.file_storage),
+            local_file_storage: Arc::clone(&self.local_file_storage),
+            repository: Arc::clone(&self.repository),
+            format,
+            kind,
+            filter,
+            destination,
+            select_batch_size,
+        };
+        self.run_local_case(case).await
+    }
+}
+
+#[async_trait(?Send)]
+impl LocalCase for ExportAnnotationsCase {
+    type Output = ExportAnnotationsCaseOutput;
+
+    async fn execute(self) -> Result<Self::Output> {
+        let mut samples = vec![];
+        let mut crops = vec![];
+        let mut skipped_sample_count = 0;
+        let mut file_count = 0;
+        let mut after_id = 0;
+        'batches: loop {
+            let input = SelectExportSamplesInput {
+                after_id,
+                limit: self.select_batch_size,
+                source_name: self.filter.source_name.clone(),
+                created_from: self.filter.created_from,
+                created_to: self.filter.created_to,
+                split: self.filter.split,
+            };
+            let (last_id, candidates, skipped_count) =
+                select_export_candidates(self.repository.as_ref(), self.kind, input).await?;
+            skipped_sample_count += skipped_count;
+            let Some(last_id) = last_id else {
+                break;
+            };
+            after_id = last_id;
+            for candidate in candidates {
+                if self.filter.limit.is_some_and(|l| samples.len() >= l) {
+                    break 'batches;
+                }
+                let occluded = candidate.annotation.lines.iter().any(|l| l.occluded);
+                if (self.format.needs_polygons() && candidate.annotation.lines.is_empty())
+                    || (self.format.needs_legible_text() && occluded)
+                {
+                    skipped_sample_count += 1;
+                    continue;
+                }
+                match self.export_sample(candidate, &mut crops).await {
+                    Ok((sample, sample_file_count)) => {
+                        samples.push(sample);
+                        file_count += sample_file_count;
+                    }
+                    Err(error) => {
+                        error!("error={}", error);
+                        skipped_sample_count += 1;
+                    }
+                }
+            }
+        }
+        for (path, content) in self.format.label_files(&samples, &crops) {
+            self.write_file(&path, content.into_bytes()).await?;
+            file_count += 1;
+        }
+        Ok(ExportAnnotationsCaseOutput {
+            sample_count: samples.len(),
+            skipped_sample_count,
+            file_count,
+        })
+    }
+}
+
+impl ExportAnnotationsCase {
+    // Copies the image of the sample, and its lines if the format needs crops
+    async fn export_sample(
+        &self,
+        candidate: ExportCandidate,
+        crops: &mut Vec<ExportedCrop>,
+    ) -> Result<(ExportedSample, usize)> {
+        let bytes = self
+            .file_storage
+            .download_file(DownloadFileInput {
+                kind: candidate.image_kind,
+                path: candidate.image_path,
+            })
+            .await?;
+        let metadata = self.image_analyzer.analyze(&bytes)?;
+        let mut sample_crops = vec![];
+        if self.format.needs_crops() {
+            for (i, line) in candidate.annotation.lines.iter().enumerate() {
+                if line.occluded {
+                    continue;
+                }
+                let crop_bytes = self.image_augmenter.crop(CropImageInput {
+                    bytes: bytes.clone(),
+                    polygon: line.polygon.clone(),
+                })?;
+                sample_crops.push((
+                    format!("crops/{}_{}.png", candidate.name, i),
+                    line.text.clone(),
+                    crop_bytes,
+                ));
+            }
+        }
+        let image_file = format!("images/{}.{}", candidate.name, metadata.extension);
+        self.write_file(&image_file, bytes).await?;
+        let file_count = 1 + sample_crops.len();
+        for (crop_file, text, crop_bytes) in sample_crops {
+            self.write_file(&crop_file, crop_bytes).await?;
+            crops.push(ExportedCrop {
+                image_file: crop_file,
+                text,
+            });
+        }
+        let sample = ExportedSample {
+            name: candidate.name,
+            image_file,
+            width: metadata.width,
+            height: metadata.height,
+            text: candidate.text,
+            annotation: candidate.annotation,
+            split: candidate.split,
+        };
+        Ok((sample, file_count))
+    }
+
+    async fn write_file(&self, relative_path: &str, bytes: Vec<u8>) -> Result<()> {
+        let (file_storage, key) = match &self.destination {
+            ExportDestination::Bucket { prefix } => (&self.file_storage, dataset_key(prefix, relative_path)),
+            ExportDestination::Directory { path } => (
+                &self.local_file_storage,
+                Path::new(path).join(relative_path).to_string_lossy().to_string(),
+            ),
+        };
+        file_storage
+            .upload_file(UploadFileInput {
+                kind: FileObjectKind::Dataset,
+                path: FileObjectPath::Keyed { key },
+                bytes,
+            })
+            .await?;
+        Ok(())
+    }
+}
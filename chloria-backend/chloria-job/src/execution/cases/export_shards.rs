@@ -0,0 +1,239 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use log::{error, info};
+
+use super::{
+    super::{
+        ports::{
+            archiver::{ArchiveFile, Archiver},
+            file_storage::{
+                DownloadFileInput, FileObjectKind, FileObjectPath, FileStorage, ListFilesInput, UploadFileInput,
+            },
+            image_analyzer::ImageAnalyzer,
+            repository::{Repository, SelectExportSamplesInput},
+        },
+        workshop::{ExportFilter, Workshop},
+    },
+    dataset_key, select_export_candidates, ExportCandidate, LocalCase,
+};
+use crate::domain::{
+    export::SampleKind,
+    webdataset::{SampleLabel, ShardBuffer, ShardManifest, ShardOptions, MANIFEST_FILE_NAME},
+};
+
+pub(crate) struct ExportShardsCaseOutput {
+    pub(crate) resumed: bool,       // Whether shards of an interrupted export were kept
+    pub(crate) sample_count: usize, // Samples packed by this run
+    pub(crate) shard_count: usize,  // Shards uploaded by this run
+    pub(crate) skipped_sample_count: usize,
+}
+
+struct ExportShardsCase {
+    image_analyzer: Arc<dyn ImageAnalyzer>,
+    archiver: Arc<dyn Archiver>,
+    file_storage: Arc<dyn FileStorage>,
+    repository: Arc<dyn Repository>,
+    options: ShardOptions,
+    prefix: String, // Of the shards and their manifest in the dataset bucket
+    select_batch_size: i64,
+}
+
+impl Workshop {
+    pub(crate) async fn execute_export_shards_case(
+        &self,
+        kind: SampleKind,
+        filter: ExportFilter,
+        prefix: String,
+        max_shard_size: usize,
+        select_batch_size: i64,
+    ) -> Result<ExportShardsCaseOutput> {
+        let case = ExportShardsCase {
+            image_analyzer: Arc::clone(&self.image_analyzer),
+            archiver: Arc::clone(&self.archiver),
+            file_storage: Arc::clone(&self.file_storage),
+            repository: Arc::clone(&self.repository),
+            options: ShardOptions {
+                kind,
+                source_name: filter.source_name,
+                created_from: filter.created_from,
+                created_to: filter.created_to,
+                limit: filter.limit,
+                split: filter.split,
+                max_shard_size,
+            },
+            prefix,
+            select_batch_size,
+        };
+        self.run_local_case(case).await
+    }
+}
+
+#[async_trait(?Send)]
+impl LocalCase for ExportShardsCase {
+    type Output = ExportShardsCaseOutput;
+
+    async fn execute(self) -> Result<Self::Output> {
+        let mut manifest = match self.download_manifest().await? {
+            Some(manifest) if manifest.options != self.options => {
+                bail!("The prefix already has an export with other options: {}", self.prefix)
+            }
+            Some(manifest) => manifest,
+            None => ShardManifest::new(self.options.clone()),
+        };
+        let resumed = !manifest.shards.is_empty();
+        let mut sample_count = 0;
+        let mut shard_count = 0;
+        let mut skipped_sample_count = 0;
+        if manifest.complete {
+            info!("prefix={}, complete=true", self.prefix);
+            return Ok(ExportShardsCaseOutput {
+                resumed,
+                sample_count,
+                shard_count,
+                skipped_sample_count,
+            });
+        }
+        // Samples are selected in the order of their IDs, so the export goes on right after the last uploaded shard
+        let mut after_id = manifest.last_sample_id();
+        let mut total_sample_count = manifest.sample_count();
+        let mut buffer = ShardBuffer::new(self.options.max_shard_size);
+        'batches: loop {
+            let input = SelectExportSamplesInput {
+                after_id,
+                limit: self.select_batch_size,
+                source_name: self.options.source_name.clone(),
+                created_from: self.options.created_from,
+                created_to: self.options.created_to,
+                split: self.options.split,
+            };
+            let (last_id, candidates, skipped_count) =
+                select_export_candidates(self.repository.as_ref(), self.options.kind, input).await?;
+            skipped_sample_count += skipped_count;
+            let Some(last_id) = last_id else {
+                break;
+            };
+            after_id = last_id;
+            for candidate in candidates {
+                if self.options.limit.is_some_and(|l| total_sample_count >= l) {
+                    break 'batches;
+                }
+                let sample_id = candidate.id;
+                let files = match self.sample_files(candidate).await {
+                    Ok(files) => files,
+                    Err(error) => {
+                        error!("sample_id={}, error={}", sample_id, error);
+                        skipped_sample_count += 1;
+                        continue;
+                    }
+                };
+                if !buffer.fits(&files) {
+                    self.upload_shard(&mut manifest, &mut buffer).await?;
+                    shard_count += 1;
+                }
+                buffer.push(sample_id, files);
+                sample_count += 1;
+                total_sample_count += 1;
+            }
+        }
+        if !buffer.is_empty() {
+            self.upload_shard(&mut manifest, &mut buffer).await?;
+            shard_count += 1;
+        }
+        manifest.complete = true;
+        self.upload_manifest(&manifest).await?;
+        Ok(ExportShardsCaseOutput {
+            resumed,
+            sample_count,
+            shard_count,
+            skipped_sample_count,
+        })
+    }
+}
+
+impl ExportShardsCase {
+    async fn download_manifest(&self) -> Result<Option<ShardManifest>> {
+        let key = dataset_key(&self.prefix, MANIFEST_FILE_NAME);
+        let files = self
+            .file_storage
+            .list_files(ListFilesInput {
+                kind: FileObjectKind::Dataset,
+                prefix: Some(key.clone()),
+            })
+            .await?;
+        if !files.iter().any(|f| f.path == key) {
+            return Ok(None);
+        }
+        let bytes = self
+            .file_storage
+            .download_file(DownloadFileInput {
+                kind: FileObjectKind::Dataset,
+                path: key,
+            })
+            .await?;
+        Ok(Some(serde_json::from_slice(&bytes)?))
+    }
+
+    async fn upload_manifest(&self, manifest: &ShardManifest) -> Result<()> {
+        self.upload(MANIFEST_FILE_NAME, serde_json::to_vec_pretty(manifest)?)
+            .await
+    }
+
+    // The manifest is uploaded after the shard, so that it never lists a shard which is not there
+    async fn upload_shard(&self, manifest: &mut ShardManifest, buffer: &mut ShardBuffer) -> Result<()> {
+        let (files, mut entry) = buffer.take(manifest.next_shard_name());
+        let bytes = self.archiver.pack(
+            files
+                .into_iter()
+                .map(|(name, bytes)| ArchiveFile { name, bytes })
+                .collect(),
+        )?;
+        entry.byte_size = bytes.len();
+        self.upload(&entry.name, bytes).await?;
+        info!(
+            "shard_name={}, sample_count={}, byte_size={}",
+            entry.name, entry.sample_count, entry.byte_size
+        );
+        manifest.shards.push(entry);
+        self.upload_manifest(manifest).await
+    }
+
+    // The image and the label of the sample, named after its key
+    async fn sample_files(&self, candidate: ExportCandidate) -> Result<Vec<(String, Vec<u8>)>> {
+        let bytes = self
+            .file_storage
+            .download_file(DownloadFileInput {
+                kind: candidate.image_kind,
+                path: candidate.image_path,
+            })
+            .await?;
+        let metadata = self.image_analyzer.analyze(&bytes)?;
+        let label = serde_json::to_vec(&SampleLabel {
+            id: candidate.id,
+            kind: self.options.kind,
+            width: metadata.width,
+            height: metadata.height,
+            text: &candidate.text,
+            annotation: &candidate.annotation,
+            split: candidate.split,
+        })?;
+        Ok(vec![
+            (format!("{}.{}", candidate.name, metadata.extension), bytes),
+            (format!("{}.json", candidate.name), label),
+        ])
+    }
+
+    async fn upload(&self, relative_path: &str, bytes: Vec<u8>) -> Result<()> {
+        self.file_storage
+            .upload_file(UploadFileInput {
+                kind: FileObjectKind::Dataset,
+                path: FileObjectPath::Keyed {
+                    key: dataset_key(&self.prefix, relative_path),
+                },
+                bytes,
+            })
+            .await?;
+        Ok(())
+    }
+}
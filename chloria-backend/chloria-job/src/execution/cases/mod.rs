@@ -1,7 +1,45 @@
+mod assign_splits;
+mod backfill_image_metadata;
+mod backfill_missing_images;
 mod collect_news;
+mod enforce_retention;
+mod export_annotations;
+mod export_shards;
+mod pin_dataset;
+mod reconcile_storage;
+mod report_characters;
+mod reprocess_news;
+mod synthesize_samples;
+mod unpin_dataset;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
 use anyhow::Result;
 use async_trait::async_trait;
+use chrono::Local;
+use log::error;
+use tokio::sync::OnceCell;
+
+use super::{
+    ports::{
+        file_storage::{FileObjectKind, FileObjectPath, FileStorage, UploadFileInput},
+        http_helper::HttpHelper,
+        image_analyzer::{AnalyzeImageOutput, ImageAnalyzer},
+        news_fetcher::FetchNewsImage,
+        repository::{InsertNewsImageInput, Repository, SelectExportSamplesInput},
+    },
+    workshop::{ImageStorageLayout, Workshop},
+};
+use crate::domain::{
+    annotation::TextAnnotation,
+    document::DocumentEntity,
+    export::{ImageTextInsight, SampleKind},
+    split::Split,
+    text::TextNormalizer,
+};
 
 #[async_trait(?Send)]
 pub(super) trait LocalCase: Send + Sync + 'static {
@@ -9,3 +47,325 @@ pub(super) trait LocalCase: Send + Sync + 'static {
 
     async fn execute(self) -> Result<Self::Output>;
 }
+
+// Path and metadata of a saved image, or `None` if it could not be saved
+type SavedImage = Arc<OnceCell<Option<(String, AnalyzeImageOutput)>>>;
+
+// Downloads images found in articles, then uploads them to the file storage along with their metadata.
+// Each url is only downloaded once per case run, even if several articles (or tasks) ask for it concurrently,
+// unless files are named after articles, in which case it is downloaded once per article.
+#[derive(Clone)]
+struct ImageSaver {
+    http_helper: Arc<dyn HttpHelper>,
+    image_analyzer: Arc<dyn ImageAnalyzer>,
+    file_storage: Arc<dyn FileStorage>,
+    layout: ImageStorageLayout,
+    saved_images: Arc<Mutex<HashMap<String, SavedImage>>>,
+}
+
+impl ImageSaver {
+    fn new(workshop: &Workshop) -> Self {
+        Self {
+            http_helper: Arc::clone(&workshop.http_helper),
+            image_analyzer: Arc::clone(&workshop.image_analyzer),
+            file_storage: Arc::clone(&workshop.file_storage),
+            layout: workshop.image_storage_layout,
+            saved_images: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    async fn save(
+        &self,
+        image: FetchNewsImage,
+        position: usize,
+        source_name: &str,
+        article_id: &str,
+    ) -> Option<InsertNewsImageInput> {
+        let saved_image_key = match self.layout {
+            // Sharing would make an article reference a file named after another article
+            ImageStorageLayout::Dated => format!("{}/{}/{}", source_name, article_id, image.url),
+            ImageStorageLayout::ContentAddressed => image.url.clone(),
+        };
+        let saved_image = {
+            let mut saved_images = self.saved_images.lock().unwrap();
+            Arc::clone(saved_images.entry(saved_image_key).or_default())
+        };
+        let (path, metadata) = saved_image
+            .get_or_init(|| async {
+                match self
+                    .download_and_upload(&image.url, position, source_name, article_id)
+                    .await
+                {
+                    Ok(saved_image) => Some(saved_image),
+                    Err(error) => {
+                        error!("image.url={}, error={}", image.url, error);
+                        None
+                    }
+                }
+            })
+            .await
+            .clone()?;
+        Some(InsertNewsImageInput {
+            position: position as i32,
+            caption: image.caption,
+            alt_text: image.alt_text,
+            path,
+            width: metadata.width,
+            height: metadata.height,
+            mime_type: metadata.mime_type,
+            byte_size: metadata.byte_size,
+            sha256: metadata.sha256,
+            perceptual_hash: metadata.perceptual_hash,
+        })
+    }
+
+    async fn download_and_upload(
+        &self,
+        image_url: &str,
+        position: usize,
+        source_name: &str,
+        article_id: &str,
+    ) -> Result<(String, AnalyzeImageOutput)> {
+        let image_bytes = self.http_helper.get(image_url).await?;
+        // Analyze before uploading so that files which are not images (error pages, etc.) are never stored
+        let metadata = self.image_analyzer.analyze(&image_bytes)?;
+        let path = match self.layout {
+            ImageStorageLayout::Dated => FileObjectPath::Dated {
+                source_name: source_name.to_string(),
+                // The representative image keeps the key used before articles could have multiple images,
+                // which was always `.jpg` (the usual extension of JPEG images) whatever the actual format
+                key: match position {
+                    0 => format!("{}.jpg", article_id),
+                    _ => format!("{}_{}.{}", article_id, position, metadata.extension),
+                },
+                created_time: Local::now(),
+            },
+            ImageStorageLayout::ContentAddressed => FileObjectPath::ContentAddressed {
+                sha256: metadata.sha256.clone(),
+                extension: metadata.extension.clone(),
+            },
+        };
+        let path = self
+            .file_storage
+            .upload_file(UploadFileInput {
+                kind: FileObjectKind::Origin,
+                path,
+                bytes: image_bytes,
+            })
+            .await?;
+        Ok((path, metadata))
+    }
+}
+
+// Uploads a raw document next to the images of the article, returning its path
+async fn archive_raw_document(
+    file_storage: &dyn FileStorage,
+    kind: FileObjectKind,
+    source_name: &str,
+    article_id: &str,
+    document: Option<String>,
+) -> Option<String> {
+    let key = match kind {
+        FileObjectKind::Origin | FileObjectKind::Synthetic | FileObjectKind::Dataset => return None,
+        FileObjectKind::RawHtml => format!("{}.html", article_id),
+        FileObjectKind::RawFeed => format!("{}.json", article_id),
+    };
+    match file_storage
+        .upload_file(UploadFileInput {
+            kind,
+            path: FileObjectPath::Dated {
+                source_name: source_name.to_string(),
+                key,
+                created_time: Local::now(),
+            },
+            bytes: document?.into_bytes(),
+        })
+        .await
+    {
+        Ok(path) => Some(path),
+        Err(error) => {
+            error!("article_id={}, error={}", article_id, error);
+            None
+        }
+    }
+}
+
+// Normalizes the long text of an article (through its document if any),
+// returning the normalized text, the original text if it has been changed, and the normalized document
+fn normalize_long_text(
+    text_normalizer: &TextNormalizer,
+    long_text: Option<String>,
+    document: Option<DocumentEntity>,
+) -> (Option<String>, Option<String>, Option<DocumentEntity>) {
+    match document {
+        Some(document) if text_normalizer.is_enabled() => {
+            let document = document.normalize(text_normalizer);
+            let normalized_long_text = document.plain_text();
+            let original_long_text = long_text.filter(|t| *t != normalized_long_text);
+            (Some(normalized_long_text), original_long_text, Some(document))
+        }
+        Some(document) => (long_text, None, Some(document)),
+        None => {
+            let (long_text, original_long_text) = text_normalizer.normalize_keeping_original(long_text);
+            (long_text, original_long_text, None)
+        }
+    }
+}
+
+// Sample selected for export, before its image is downloaded
+struct ExportCandidate {
+    id: i32, // Of the synthetic sample, or of the news for real samples
+    name: String,
+    image_kind: FileObjectKind,
+    image_path: String,
+    text: String,
+    annotation: TextAnnotation,
+    split: Option<Split>,
+}
+
+// Selects a batch of samples to export, returning the greatest ID of the batch (or `None` if there are no more samples),
+// the candidates and the number of samples skipped because their text is unknown
+async fn select_export_candidates(
+    repository: &dyn Repository,
+    kind: SampleKind,
+    input: SelectExportSamplesInput,
+) -> Result<(Option<i32>, Vec<ExportCandidate>, usize)> {
+    match kind {
+        SampleKind::Synthetic => {
+            let outputs = repository.select_synthetic_samples(input).await?;
+            let last_id = outputs.last().map(|o| o.sample_id);
+            let candidates = outputs
+                .into_iter()
+                .map(|output| ExportCandidate {
+                    id: output.sample_id,
+                    name: format!("synthetic_{}", output.sample_id),
+                    image_kind: FileObjectKind::Synthetic,
+                    image_path: output.image_path,
+                    text: output.text,
+                    annotation: output.annotation,
+                    split: output.split,
+                })
+                .collect();
+            Ok((last_id, candidates, 0))
+        }
+        SampleKind::Real => {
+            let outputs = repository.select_insight_images(input).await?;
+            let last_id = outputs.last().map(|o| o.news_id);
+            let mut candidates = vec![];
+            let mut skipped_count = 0;
+            for output in outputs {
+                // Insights are free-form, and most of them say nothing about the text in the image
+                let Some(insight) = ImageTextInsight::parse(&output.fields) else {
+                    skipped_count += 1;
+                    continue;
+                };
+                candidates.push(ExportCandidate {
+                    id: output.news_id,
+                    name: format!("news_{}", output.news_id),
+                    image_kind: FileObjectKind::Origin,
+                    image_path: output.image_path,
+                    annotation: insight.annotation(),
+                    text: insight.image_text,
+                    split: output.split,
+                });
+            }
+            Ok((last_id, candidates, skipped_count))
+        }
+    }
+}
+
+// Key of a file of an export in the dataset bucket
+fn dataset_key(prefix: &str, relative_path: &str) -> String {
+    match prefix.trim_end_matches('/') {
+        "" => relative_path.to_string(),
+        prefix => format!("{}/{}", prefix, relative_path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use super::{
+        super::{
+            ports::{
+                file_storage::{FileObjectPath, MockFileStorage},
+                http_helper::MockHttpHelper,
+                image_analyzer::{AnalyzeImageOutput, MockImageAnalyzer},
+                news_fetcher::FetchNewsImage,
+            },
+            workshop::ImageStorageLayout,
+        },
+        ImageSaver,
+    };
+
+    #[tokio::test]
+    async fn save_shared_image() {
+        for (layout, downloads_num, expected_paths) in [
+            // The representative image keeps its old key whatever its format
+            (ImageStorageLayout::Dated, 2, ["Yahoo/a1.jpg", "Yahoo/a2_1.png"]),
+            (
+                ImageStorageLayout::ContentAddressed,
+                1,
+                ["sha256/ab.png", "sha256/ab.png"],
+            ),
+        ] {
+            let mut mock_http_helper = MockHttpHelper::new();
+            mock_http_helper
+                .expect_get()
+                .times(downloads_num)
+                .returning(|_| Box::pin(async { Ok(vec![0]) }));
+            let mut mock_image_analyzer = MockImageAnalyzer::new();
+            mock_image_analyzer
+                .expect_analyze()
+                .times(downloads_num)
+                .returning(|_| {
+                    Ok(AnalyzeImageOutput {
+                        width: 1,
+                        height: 1,
+                        mime_type: "image/png".to_string(),
+                        extension: "png".to_string(),
+                        byte_size: 1,
+                        sha256: "ab".to_string(),
+                        perceptual_hash: "0000000000000000".to_string(),
+                    })
+                });
+            let mut mock_file_storage = MockFileStorage::new();
+            mock_file_storage
+                .expect_upload_file()
+                .times(downloads_num)
+                .returning(|input| {
+                    let path = match input.path {
+                        FileObjectPath::Dated { source_name, key, .. } => format!("{}/{}", source_name, key),
+                        FileObjectPath::ContentAddressed { sha256, extension } => {
+                            format!("sha256/{}.{}", sha256, extension)
+                        }
+                        FileObjectPath::Keyed { key } => key,
+                    };
+                    Box::pin(async { Ok(path) })
+                });
+            let image_saver = ImageSaver {
+                http_helper: Arc::new(mock_http_helper),
+                image_analyzer: Arc::new(mock_image_analyzer),
+                file_storage: Arc::new(mock_file_storage),
+                layout,
+                saved_images: Arc::new(Mutex::new(HashMap::new())),
+            };
+            // Two articles of the same source share an image, at different positions
+            let image = || FetchNewsImage {
+                url: "https://example.com/shared.png".to_string(),
+                caption: None,
+                alt_text: None,
+            };
+            let mut paths = vec![];
+            for (position, article_id) in [(0, "a1"), (1, "a2")] {
+                let saved_image = image_saver.save(image(), position, "Yahoo", article_id).await.unwrap();
+                paths.push(saved_image.path);
+            }
+            assert_eq!(paths, expected_paths);
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+
+use super::{
+    super::{
+        ports::repository::{PinDatasetInput, Repository},
+        workshop::Workshop,
+    },
+    LocalCase,
+};
+
+// Number of news added to the dataset, which may already have had some
+type PinDatasetCaseOutput = usize;
+
+struct PinDatasetCase {
+    repository: Arc<dyn Repository>,
+    input: PinDatasetInput,
+}
+
+impl Workshop {
+    pub(crate) async fn execute_pin_dataset_case(
+        &self,
+        name: String,
+        source_name: Option<String>,
+        created_from: Option<DateTime<Local>>,
+        created_to: Option<DateTime<Local>>,
+    ) -> Result<PinDatasetCaseOutput> {
+        let case = PinDatasetCase {
+            repository: Arc::clone(&self.repository),
+            input: PinDatasetInput {
+                name,
+                source_name,
+                created_from,
+                created_to,
+            },
+        };
+        self.run_local_case(case).await
+    }
+}
+
+#[async_trait(?Send)]
+impl LocalCase for PinDatasetCase {
+    type Output = PinDatasetCaseOutput;
+
+    async fn execute(self) -> Result<Self::Output> {
+        self.repository.pin_dataset(self.input).await
+    }
+}
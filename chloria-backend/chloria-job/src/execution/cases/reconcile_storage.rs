@@ -0,0 +1,178 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Local, TimeDelta};
+use log::{error, info};
+
+use super::{
+    super::{
+        ports::{
+            file_storage::{DeleteFilesInput, DownloadFileInput, FileObjectKind, FileStorage, ListFilesInput},
+            image_analyzer::ImageAnalyzer,
+            repository::{Repository, SelectFileReferencesInput, UpdateNewsImageMetadataInput},
+        },
+        workshop::Workshop,
+    },
+    LocalCase,
+};
+
+pub(crate) struct ReconcileStorageCaseOutput {
+    pub(crate) orphan_file_count: usize,     // Files which are not referenced by any row
+    pub(crate) missing_file_count: usize,    // Referenced files which do not exist in the file storage
+    pub(crate) mismatched_file_count: usize, // Files whose size differs from the one recorded in the database
+    pub(crate) fixed_file_count: usize,      // Always zero in dry-run mode
+}
+
+struct ReconcileStorageCase {
+    image_analyzer: Arc<dyn ImageAnalyzer>,
+    file_storage: Arc<dyn FileStorage>,
+    repository: Arc<dyn Repository>,
+    prefixes: Vec<String>,
+    fix: bool,
+    orphan_min_age: TimeDelta,
+}
+
+impl Workshop {
+    pub(crate) async fn execute_reconcile_storage_case(
+        &self,
+        prefixes: Vec<String>,
+        fix: bool,
+        orphan_min_age: TimeDelta,
+    ) -> Result<ReconcileStorageCaseOutput> {
+        let case = ReconcileStorageCase {
+            image_analyzer: Arc::clone(&self.image_analyzer),
+            file_storage: Arc::clone(&self.file_storage),
+            repository: Arc::clone(&self.repository),
+            prefixes,
+            fix,
+            orphan_min_age,
+        };
+        self.run_local_case(case).await
+    }
+}
+
+#[async_trait(?Send)]
+impl LocalCase for ReconcileStorageCase {
+    type Output = ReconcileStorageCaseOutput;
+
+    async fn execute(self) -> Result<Self::Output> {
+        let mut output = ReconcileStorageCaseOutput {
+            orphan_file_count: 0,
+            missing_file_count: 0,
+            mismatched_file_count: 0,
+            fixed_file_count: 0,
+        };
+        // Reconcile the whole bucket if no prefix is given
+        let prefixes = match self.prefixes.is_empty() {
+            true => vec![None],
+            false => self.prefixes.iter().cloned().map(Some).collect(),
+        };
+        for prefix in prefixes {
+            let files: HashMap<String, _> = self
+                .file_storage
+                .list_files(ListFilesInput {
+                    kind: FileObjectKind::Origin,
+                    prefix: prefix.clone(),
+                })
+                .await?
+                .into_iter()
+                .map(|f| (f.path.clone(), f))
+                .collect();
+            let references: HashMap<String, _> = self
+                .repository
+                .select_file_references(SelectFileReferencesInput { prefix })
+                .await?
+                .into_iter()
+                .map(|r| (r.path, r.byte_size))
+                .collect();
+            // Files are uploaded before their rows are inserted, so recent files may not be referenced yet
+            let orphan_max_modified_time = Local::now() - self.orphan_min_age;
+            let mut orphan_paths = vec![];
+            for (path, file) in &files {
+                if references.contains_key(path) {
+                    continue;
+                }
+                if file.modified_time.is_some_and(|t| t > orphan_max_modified_time) {
+                    continue;
+                }
+                info!("orphan_path={}, size={}", path, file.size);
+                orphan_paths.push(path.clone());
+            }
+            let mut missing_paths = vec![];
+            let mut mismatched_paths = vec![];
+            for (path, byte_size) in &references {
+                match (files.get(path), byte_size) {
+                    (None, _) => {
+                        info!("missing_path={}", path);
+                        missing_paths.push(path.clone());
+                    }
+                    (Some(file), Some(byte_size)) if file.size as i64 != *byte_size => {
+                        info!("mismatched_path={}, size={}, byte_size={}", path, file.size, byte_size);
+                        mismatched_paths.push(path.clone());
+                    }
+                    _ => {}
+                }
+            }
+            output.orphan_file_count += orphan_paths.len();
+            output.missing_file_count += missing_paths.len();
+            output.mismatched_file_count += mismatched_paths.len();
+            if !self.fix {
+                continue;
+            }
+            // Delete files which are not referenced anymore, one by one so that only deleted files are counted
+            for path in orphan_paths {
+                match self
+                    .file_storage
+                    .delete_files(DeleteFilesInput {
+                        kind: FileObjectKind::Origin,
+                        paths: vec![path.clone()],
+                    })
+                    .await
+                {
+                    Ok(()) => output.fixed_file_count += 1,
+                    Err(error) => error!("path={}, error={}", path, error),
+                }
+            }
+            // Drop references to files which do not exist, so that images can be backfilled again
+            // References are dropped in a single transaction, so either all or none of them are
+            let missing_file_count = missing_paths.len();
+            match self.repository.delete_file_references(missing_paths).await {
+                Ok(_) => output.fixed_file_count += missing_file_count,
+                Err(error) => error!("error={}", error),
+            }
+            // Trust the stored file and analyze it again
+            for path in mismatched_paths {
+                let metadata = match self
+                    .file_storage
+                    .download_file(DownloadFileInput {
+                        kind: FileObjectKind::Origin,
+                        path: path.clone(),
+                    })
+                    .await
+                    .and_then(|bytes| self.image_analyzer.analyze(&bytes))
+                {
+                    Ok(metadata) => metadata,
+                    Err(error) => {
+                        error!("path={}, error={}", path, error);
+                        continue;
+                    }
+                };
+                let input = UpdateNewsImageMetadataInput {
+                    path: path.clone(),
+                    width: metadata.width,
+                    height: metadata.height,
+                    mime_type: metadata.mime_type,
+                    byte_size: metadata.byte_size,
+                    sha256: metadata.sha256,
+                    perceptual_hash: metadata.perceptual_hash,
+                };
+                match self.repository.update_news_image_metadata(input).await {
+                    Ok(()) => output.fixed_file_count += 1,
+                    Err(error) => error!("path={}, error={}", path, error),
+                }
+            }
+        }
+        Ok(output)
+    }
+}
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+
+use super::{
+    super::{
+        ports::repository::{Repository, SelectCharacterCountsInput},
+        workshop::Workshop,
+    },
+    LocalCase,
+};
+use crate::domain::statistics::{CharacterCoverage, CharacterStatistics};
+
+pub(crate) struct ReportCharactersCaseOutput {
+    pub(crate) news_count: i64,                    // Number of news which have statistics
+    pub(crate) statistics: CharacterStatistics,    // Aggregated over all the news
+    pub(crate) coverage: CharacterCoverage,        // Distinct characters of each set which appear
+    pub(crate) top_characters: Vec<(char, usize)>, // The most frequent characters, the most frequent first
+}
+
+struct ReportCharactersCase {
+    repository: Arc<dyn Repository>,
+    source_name: Option<String>, // Report characters of news of every source if not given
+    created_from: Option<DateTime<Local>>,
+    created_to: Option<DateTime<Local>>,
+    top_characters_num: usize,
+}
+
+impl Workshop {
+    pub(crate) async fn execute_report_characters_case(
+        &self,
+        source_name: Option<String>,
+        created_from: Option<DateTime<Local>>,
+        created_to: Option<DateTime<Local>>,
+        top_characters_num: usize,
+    ) -> Result<ReportCharactersCaseOutput> {
+        let case = ReportCharactersCase {
+            repository: Arc::clone(&self.repository),
+            source_name,
+            created_from,
+            created_to,
+            top_characters_num,
+        };
+        self.run_local_case(case).await
+    }
+}
+
+#[async_trait(?Send)]
+impl LocalCase for ReportCharactersCase {
+    type Output = ReportCharactersCaseOutput;
+
+    async fn execute(self) -> Result<Self::Output> {
+        let output = self
+            .repository
+            .select_character_counts(SelectCharacterCountsInput {
+                source_name: self.source_name,
+                created_from: self.created_from,
+                created_to: self.created_to,
+            })
+            .await?;
+        let mut top_characters: Vec<(char, usize)> = output.character_counts.iter().map(|(c, n)| (*c, *n)).collect();
+        top_characters.sort_by_key(|(c, n)| (std::cmp::Reverse(*n), *c));
+        top_characters.truncate(self.top_characters_num);
+        let statistics = CharacterStatistics::from_character_counts(output.character_counts);
+        Ok(ReportCharactersCaseOutput {
+            news_count: output.news_count,
+            coverage: statistics.coverage(),
+            statistics,
+            top_characters,
+        })
+    }
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use log::error;
+
+use super::{
+    super::{
+        ports::{
+            article_extractor::{ArticleExtractor, ExtractArticleInput},
+            file_storage::{DownloadFileInput, FileObjectKind, FileStorage},
+            repository::{InsertNewsSentenceInput, Repository, SelectNewsRawHtmlPathsInput, UpdateNewsTextInput},
+        },
+        workshop::Workshop,
+    },
+    normalize_long_text, LocalCase,
+};
+use crate::domain::{
+    difficulty::DifficultyScorer, sentence::split_sentences, statistics::CharacterStatistics, text::TextNormalizer,
+};
+
+type ReprocessNewsCaseOutput = (usize, usize);
+
+struct ReprocessNewsCase {
+    article_extractor: Arc<dyn ArticleExtractor>,
+    text_normalizer: Arc<TextNormalizer>,
+    difficulty_scorer: Arc<DifficultyScorer>,
+    file_storage: Arc<dyn FileStorage>,
+    repository: Arc<dyn Repository>,
+    source_name: Option<String>, // Reprocess news of every source if not given
+    created_from: Option<DateTime<Local>>,
+    created_to: Option<DateTime<Local>>,
+    select_batch_size: i64,
+}
+
+impl Workshop {
+    pub(crate) async fn execute_reprocess_news_case(
+        &self,
+        source_name: Option<String>,
+        created_from: Option<DateTime<Local>>,
+        created_to: Option<DateTime<Local>>,
+        select_batch_size: i64,
+    ) -> Result<ReprocessNewsCaseOutput> {
+        let case = ReprocessNewsCase {
+            article_extractor: Arc::clone(&self.article_extractor),
+            text_normalizer: Arc::clone(&self.text_normalizer),
+            difficulty_scorer: Arc::clone(&self.difficulty_scorer),
+            file_storage: Arc::clone(&self.file_storage),
+            repository: Arc::clone(&self.repository),
+            source_name,
+            created_from,
+            created_to,
+            select_batch_size,
+        };
+        self.run_local_case(case).await
+    }
+}
+
+#[async_trait(?Send)]
+impl LocalCase for ReprocessNewsCase {
+    type Output = ReprocessNewsCaseOutput;
+
+    async fn execute(self) -> Result<Self::Output> {
+        let extractor_version = self.article_extractor.version();
+        let mut total_news_count = 0;
+        let mut reprocessed_news_count = 0;
+        let mut after_news_id = 0;
+        loop {
+            let outputs = self
+                .repository
+                .select_news_raw_html_paths(SelectNewsRawHtmlPathsInput {
+                    after_news_id,
+                    limit: self.select_batch_size,
+                    source_name: self.source_name.clone(),
+                    created_from: self.created_from,
+                    created_to: self.created_to,
+                    extractor_version,
+                })
+                .await?;
+            let Some(last_output) = outputs.last() else {
+                break;
+            };
+            after_news_id = last_output.news_id;
+            total_news_count += outputs.len();
+            for output in outputs {
+                let extract_output = match self
+                    .file_storage
+                    .download_file(DownloadFileInput {
+                        kind: FileObjectKind::RawHtml,
+                        path: output.raw_html_path,
+                    })
+                    .await
+                    .and_then(|bytes| Ok(String::from_utf8(bytes)?))
+                    .and_then(|html| {
+                        self.article_extractor.extract(ExtractArticleInput {
+                            source_name: output.source_name,
+                            html,
+                        })
+                    }) {
+                    Ok(extract_output) => extract_output,
+                    Err(error) => {
+                        error!("news_id={}, error={}", output.news_id, error);
+                        continue;
+                    }
+                };
+                // Keep the stored text rather than overwriting it with nothing
+                let Some(document) = extract_output.document else {
+                    error!("news_id={}, error=No document has been extracted.", output.news_id);
+                    continue;
+                };
+                let (long_text, original_long_text, document) =
+                    normalize_long_text(&self.text_normalizer, Some(document.plain_text()), Some(document));
+                let statistics = long_text.as_deref().map(CharacterStatistics::new);
+                let difficulty = long_text.as_deref().and_then(|t| self.difficulty_scorer.score(t));
+                let sentences = long_text
+                    .as_deref()
+                    .map(split_sentences)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|sentence| InsertNewsSentenceInput {
+                        difficulty: self.difficulty_scorer.score(&sentence.text),
+                        sentence,
+                    })
+                    .collect();
+                let input = UpdateNewsTextInput {
+                    news_id: output.news_id,
+                    long_text,
+                    document,
+                    extractor_version,
+                    original_long_text,
+                    statistics,
+                    difficulty,
+                    sentences,
+                };
+                match self.repository.update_news_text(input).await {
+                    Ok(()) => reprocessed_news_count += 1,
+                    Err(error) => error!("news_id={}, error={}", output.news_id, error),
+                }
+            }
+        }
+        Ok((total_news_count, reprocessed_news_count))
+    }
+}
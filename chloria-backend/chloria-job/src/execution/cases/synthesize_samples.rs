@@ -0,0 +1,246 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use log::{error, info};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use super::{
+    super::{
+        ports::{
+            file_storage::{DownloadFileInput, FileObjectKind, FileObjectPath, FileStorage, UploadFileInput},
+            image_analyzer::ImageAnalyzer,
+            image_augmenter::{AugmentImageInput, ImageAugmenter},
+            repository::{InsertSyntheticSampleInput, Repository, SelectSentencesInput},
+            text_renderer::{ComposeTextInput, RenderTextInput, TextRenderer},
+        },
+        workshop::Workshop,
+    },
+    LocalCase,
+};
+use crate::domain::{
+    augmentation::transform_annotation,
+    font::FontRegistry,
+    synthesis::{wrap_lines, SynthesisConfig},
+};
+
+pub(crate) struct SynthesizeSamplesCaseOutput {
+    pub(crate) sentence_count: usize,
+    pub(crate) sample_count: usize,
+    pub(crate) uncovered_sentence_count: usize, // Sentences skipped because no font covers all of their characters
+    pub(crate) missing_characters: BTreeMap<char, usize>, // Characters no font covers, with the sentences they are in
+}
+
+struct SynthesizeSamplesCase {
+    text_renderer: Arc<dyn TextRenderer>,
+    image_augmenter: Arc<dyn ImageAugmenter>,
+    image_analyzer: Arc<dyn ImageAnalyzer>,
+    file_storage: Arc<dyn FileStorage>,
+    repository: Arc<dyn Repository>,
+    synthesis_config: Arc<SynthesisConfig>,
+    samples_num: i64,
+    seed: u64, // Sentences and seeds of the samples are drawn from it, so that the run can be reproduced on the same data
+    min_sentence_length: usize,
+    max_sentence_length: usize,
+    scene: bool, // Put the text on news photos instead of plain backgrounds
+    insert_batch_size: usize,
+}
+
+impl Workshop {
+    pub(crate) async fn execute_synthesize_samples_case(
+        &self,
+        samples_num: i64,
+        seed: u64,
+        min_sentence_length: usize,
+        max_sentence_length: usize,
+        scene: bool,
+        insert_batch_size: usize,
+    ) -> Result<SynthesizeSamplesCaseOutput> {
+        let case = SynthesizeSamplesCase {
+            text_renderer: Arc::clone(&self.text_renderer),
+            image_augmenter: Arc::clone(&self.image_augmenter),
+            image_analyzer: Arc::clone(&self.image_analyzer),
+            file_storage: Arc::clone(&self.file_storage),
+            repository: Arc::clone(&self.repository),
+            synthesis_config: Arc::clone(&self.synthesis_config),
+            samples_num,
+            seed,
+            min_sentence_length,
+            max_sentence_length,
+            scene,
+            insert_batch_size,
+        };
+        self.run_local_case(case).await
+    }
+}
+
+#[async_trait(?Send)]
+impl LocalCase for SynthesizeSamplesCase {
+    type Output = SynthesizeSamplesCaseOutput;
+
+    async fn execute(self) -> Result<Self::Output> {
+        let font_registry = FontRegistry::new(self.text_renderer.fonts());
+        if font_registry.fonts().next().is_none() {
+            bail!("No fonts to render samples with.");
+        }
+        for font in font_registry.fonts() {
+            info!(
+                "font_name={}, family={}, weight={}, style_class={:?}, character_count={}",
+                font.metadata.name,
+                font.metadata.family,
+                font.metadata.weight,
+                font.metadata.style_class,
+                font.characters.len()
+            );
+        }
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut sentence_ids = self
+            .repository
+            .select_sentence_ids(SelectSentencesInput {
+                min_length: self.min_sentence_length,
+                max_length: self.max_sentence_length,
+            })
+            .await?;
+        let (sentence_ids, _) = sentence_ids.partial_shuffle(&mut rng, self.samples_num.max(0) as usize);
+        let sentences = self.repository.select_sentences(sentence_ids.to_vec()).await?;
+        let sentence_count = sentences.len();
+        let background_image_paths = match self.scene {
+            true => {
+                let mut paths = self.repository.select_background_image_paths().await?;
+                let (paths, _) = paths.partial_shuffle(&mut rng, sentences.len());
+                paths.to_vec()
+            }
+            false => vec![],
+        };
+        if self.scene && background_image_paths.is_empty() {
+            bail!("No news photos to put the text on.");
+        }
+        let mut inputs = vec![];
+        let mut sample_count = 0;
+        let mut uncovered_sentence_count = 0;
+        let mut missing_characters = BTreeMap::new();
+        for (i, sentence) in sentences.into_iter().enumerate() {
+            let seed: u64 = rng.random();
+            // Only fonts which have a glyph for every character are drawn from, so that no tofu gets a label
+            let font_names = font_registry.covering_font_names(&sentence.text);
+            if font_names.is_empty() {
+                let characters = font_registry.uncovered_characters(&sentence.text);
+                info!(
+                    "sentence_id={}, missing_characters={}",
+                    sentence.sentence_id,
+                    characters.iter().collect::<String>()
+                );
+                for character in characters {
+                    *missing_characters.entry(character).or_default() += 1;
+                }
+                uncovered_sentence_count += 1;
+                continue;
+            }
+            // Augmentations are drawn after render parameters, from the same generator
+            let mut sample_rng = StdRng::seed_from_u64(seed);
+            let Some(mut params) = self.synthesis_config.sample(&mut sample_rng, &font_names) else {
+                continue;
+            };
+            params.font = font_registry.metadata(&params.font_name);
+            let augmentations = self.synthesis_config.augmentation.sample(&mut sample_rng);
+            let lines = wrap_lines(&sentence.text, params.max_line_length);
+            let text = lines.join("\n");
+            // Photos are reused in turn if there are fewer of them than sentences
+            let background_image_path = background_image_paths
+                .get(i % background_image_paths.len().max(1))
+                .cloned();
+            let result = match &background_image_path {
+                Some(path) => match self
+                    .file_storage
+                    .download_file(DownloadFileInput {
+                        kind: FileObjectKind::Origin,
+                        path: path.clone(),
+                    })
+                    .await
+                {
+                    Ok(background) => self.text_renderer.compose(ComposeTextInput {
+                        background,
+                        lines,
+                        params,
+                        max_texture: self.synthesis_config.max_background_texture,
+                        min_contrast_ratio: self.synthesis_config.min_contrast_ratio,
+                    }),
+                    Err(error) => Err(error),
+                },
+                None => self.text_renderer.render(RenderTextInput { lines, params }),
+            };
+            let output = match result {
+                Ok(output) => output,
+                Err(error) => {
+                    error!("sentence_id={}, error={}", sentence.sentence_id, error);
+                    continue;
+                }
+            };
+            let mut annotation = output.annotation;
+            transform_annotation(&mut annotation, &augmentations, [output.width, output.height]);
+            let augmented_output = match self.image_augmenter.augment(AugmentImageInput {
+                bytes: output.bytes,
+                augmentations: augmentations.clone(),
+            }) {
+                Ok(augmented_output) => augmented_output,
+                Err(error) => {
+                    error!("sentence_id={}, error={}", sentence.sentence_id, error);
+                    continue;
+                }
+            };
+            let metadata = match self.image_analyzer.analyze(&augmented_output.bytes) {
+                Ok(metadata) => metadata,
+                Err(error) => {
+                    error!("sentence_id={}, error={}", sentence.sentence_id, error);
+                    continue;
+                }
+            };
+            let image_path = match self
+                .file_storage
+                .upload_file(UploadFileInput {
+                    kind: FileObjectKind::Synthetic,
+                    path: FileObjectPath::ContentAddressed {
+                        sha256: metadata.sha256,
+                        extension: metadata.extension,
+                    },
+                    bytes: augmented_output.bytes,
+                })
+                .await
+            {
+                Ok(image_path) => image_path,
+                Err(error) => {
+                    error!("sentence_id={}, error={}", sentence.sentence_id, error);
+                    continue;
+                }
+            };
+            inputs.push(InsertSyntheticSampleInput {
+                sentence_id: Some(sentence.sentence_id),
+                news_id: Some(sentence.news_id),
+                text,
+                image_path,
+                width: augmented_output.width,
+                height: augmented_output.height,
+                render_params: output.params,
+                seed,
+                background_image_path,
+                annotation,
+                augmentations,
+            });
+            // Inserted as they come, so that uploaded images are not left without samples if the run stops
+            if inputs.len() >= self.insert_batch_size {
+                sample_count += inputs.len();
+                self.repository
+                    .insert_synthetic_samples(std::mem::take(&mut inputs))
+                    .await?;
+            }
+        }
+        sample_count += inputs.len();
+        self.repository.insert_synthetic_samples(inputs).await?;
+        Ok(SynthesizeSamplesCaseOutput {
+            sentence_count,
+            sample_count,
+            uncovered_sentence_count,
+            missing_characters,
+        })
+    }
+}
@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+
+use super::{
+    super::{ports::repository::Repository, workshop::Workshop},
+    LocalCase,
+};
+
+struct UnpinDatasetCase {
+    repository: Arc<dyn Repository>,
+    name: String,
+}
+
+impl Workshop {
+    pub(crate) async fn execute_unpin_dataset_case(&self, name: String) -> Result<()> {
+        let case = UnpinDatasetCase {
+            repository: Arc::clone(&self.repository),
+            name,
+        };
+        self.run_local_case(case).await
+    }
+}
+
+#[async_trait(?Send)]
+impl LocalCase for UnpinDatasetCase {
+    type Output = ();
+
+    async fn execute(self) -> Result<Self::Output> {
+        if !self.repository.unpin_dataset(self.name.clone()).await? {
+            bail!("Unknown dataset: {}", self.name);
+        }
+        Ok(())
+    }
+}
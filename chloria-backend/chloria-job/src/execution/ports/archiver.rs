@@ -0,0 +1,13 @@
+use anyhow::Result;
+use mockall::automock;
+
+pub(crate) struct ArchiveFile {
+    pub(crate) name: String, // Path of the file in the archive
+    pub(crate) bytes: Vec<u8>,
+}
+
+#[automock]
+pub(crate) trait Archiver: Send + Sync {
+    // Files are packed in the given order, which readers streaming the archive rely on
+    fn pack(&self, files: Vec<ArchiveFile>) -> Result<Vec<u8>>;
+}
@@ -0,0 +1,20 @@
+use anyhow::Result;
+use mockall::automock;
+
+use crate::domain::document::DocumentEntity;
+
+pub(crate) struct ExtractArticleInput {
+    pub(crate) source_name: String, // Code name of the source the page was fetched from
+    pub(crate) html: String,        // Source document of the article page
+}
+
+pub(crate) struct ExtractArticleOutput {
+    pub(crate) document: Option<DocumentEntity>, // Body of the article, or `None` if no content was found
+}
+
+#[automock]
+pub(crate) trait ArticleExtractor: Send + Sync {
+    // Bumped whenever the extraction logic changes, so that older news can be reprocessed
+    fn version(&self) -> i32;
+    fn extract(&self, input: ExtractArticleInput) -> Result<ExtractArticleOutput>;
+}
@@ -3,20 +3,66 @@ use async_trait::async_trait;
 use chrono::{DateTime, Local};
 use mockall::automock;
 
+#[derive(Clone, Copy)]
 pub(crate) enum FileObjectKind {
-    Origin,
+    Origin,    // Images found in articles
+    RawHtml,   // Source documents of article pages, compressed in the file storage
+    RawFeed,   // Feed entries the articles were found in, compressed in the file storage
+    Synthetic, // Samples rendered from the text of news, stored apart from collected files
+    Dataset,   // Exports of samples along with their labels, ready to train models on
+}
+
+pub(crate) enum FileObjectPath {
+    // Grouped by source and creation date, one file per key
+    Dated {
+        source_name: String,           // Code name of the source used to fetch the news
+        key: String,                   // Unique key to differentiate this file from others
+        created_time: DateTime<Local>, // Date and time when the file was created
+    },
+    // Grouped by content, one file shared by everything that has the same content
+    ContentAddressed {
+        sha256: String,    // Hex-encoded SHA-256 digest of the content
+        extension: String, // File extension without the leading dot
+    },
+    // Chosen by the caller, replacing the file which has the same key if any
+    Keyed {
+        key: String,
+    },
 }
 
 pub(crate) struct UploadFileInput {
     pub(crate) kind: FileObjectKind,
-    pub(crate) source_name: String, // Code name of the source used to fetch the news
-    pub(crate) key: String,         // Unique key to differentiate this file from others
-    pub(crate) bytes: Vec<u8>,      // Content of the file
-    pub(crate) created_time: DateTime<Local>, // Date and time when the file was created
+    pub(crate) path: FileObjectPath,
+    pub(crate) bytes: Vec<u8>, // Content of the file
+}
+
+pub(crate) struct DownloadFileInput {
+    pub(crate) kind: FileObjectKind,
+    pub(crate) path: String, // Path returned when the file was uploaded
+}
+
+pub(crate) struct ListFilesInput {
+    pub(crate) kind: FileObjectKind,
+    pub(crate) prefix: Option<String>, // Only list files whose path starts with this prefix
+}
+
+pub(crate) struct ListFilesOutput {
+    pub(crate) path: String,
+    pub(crate) size: usize,                            // Size of the file in bytes
+    pub(crate) modified_time: Option<DateTime<Local>>, // Date and time when the file was last modified
+}
+
+pub(crate) struct DeleteFilesInput {
+    pub(crate) kind: FileObjectKind,
+    pub(crate) paths: Vec<String>,
 }
 
 #[async_trait(?Send)]
 #[automock] // See: https://github.com/asomers/mockall/issues/189#issuecomment-689145249
 pub(crate) trait FileStorage: Send + Sync {
+    // Content-addressed files which already exist are not uploaded again
     async fn upload_file(&self, input: UploadFileInput) -> Result<String>;
+    async fn download_file(&self, input: DownloadFileInput) -> Result<Vec<u8>>;
+    async fn list_files(&self, input: ListFilesInput) -> Result<Vec<ListFilesOutput>>;
+    async fn delete_files(&self, input: DeleteFilesInput) -> Result<()>;
 }
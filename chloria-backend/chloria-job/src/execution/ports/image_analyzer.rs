@@ -0,0 +1,18 @@
+use anyhow::Result;
+use mockall::automock;
+
+#[derive(Clone)]
+pub(crate) struct AnalyzeImageOutput {
+    pub(crate) width: u32,              // Width of the image in pixels
+    pub(crate) height: u32,             // Height of the image in pixels
+    pub(crate) mime_type: String,       // MIME type guessed from the content (not from the url)
+    pub(crate) extension: String,       // Usual file extension of the MIME type, without the leading dot
+    pub(crate) byte_size: usize,        // Size of the encoded image in bytes
+    pub(crate) sha256: String,          // Hex-encoded SHA-256 digest of the encoded image
+    pub(crate) perceptual_hash: String, // Hex-encoded perceptual hash, close for visually similar images
+}
+
+#[automock]
+pub(crate) trait ImageAnalyzer: Send + Sync {
+    fn analyze(&self, bytes: &[u8]) -> Result<AnalyzeImageOutput>;
+}
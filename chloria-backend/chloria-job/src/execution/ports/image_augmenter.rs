@@ -0,0 +1,27 @@
+use anyhow::Result;
+use mockall::automock;
+
+use crate::domain::{annotation::Polygon, augmentation::Augmentation};
+
+pub(crate) struct AugmentImageInput {
+    pub(crate) bytes: Vec<u8>,                   // Encoded image
+    pub(crate) augmentations: Vec<Augmentation>, // Applied in order
+}
+
+pub(crate) struct AugmentImageOutput {
+    pub(crate) bytes: Vec<u8>, // Encoded as PNG, keeping the artifacts of JPEG compression if any
+    pub(crate) width: u32,     // Width of the image in pixels
+    pub(crate) height: u32,    // Height of the image in pixels
+}
+
+pub(crate) struct CropImageInput {
+    pub(crate) bytes: Vec<u8>,   // Encoded image
+    pub(crate) polygon: Polygon, // Cut out along its bounding box, clamped to the image
+}
+
+#[automock]
+pub(crate) trait ImageAugmenter: Send + Sync {
+    fn augment(&self, input: AugmentImageInput) -> Result<AugmentImageOutput>;
+    // Returns the part of the image as PNG, e.g. a line of text to train recognition on
+    fn crop(&self, input: CropImageInput) -> Result<Vec<u8>>;
+}
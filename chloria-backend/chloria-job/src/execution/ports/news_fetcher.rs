@@ -7,14 +7,23 @@ use mockall::automock;
 use tokio::task::JoinHandle;
 
 pub(crate) struct FetchNewsArticle {
-    pub(crate) source_name: String,        // Code name of the source used to fetch the news
-    pub(crate) id: Option<String>,         // Unique ID for news from the same source
-    pub(crate) link: Option<String>,       // Link to the original content
-    pub(crate) title: Option<String>,      // Title of the content
-    pub(crate) short_text: Option<String>, // Short description or summary of the content
-    pub(crate) long_text: Option<String>,  // Full text or detailed content
-    pub(crate) image_url: Option<String>,  // Url of representative image in the original content
+    pub(crate) source_name: String,         // Code name of the source used to fetch the news
+    pub(crate) id: Option<String>,          // Unique ID for news from the same source
+    pub(crate) link: Option<String>,        // Link to the original content
+    pub(crate) title: Option<String>,       // Title of the content
+    pub(crate) short_text: Option<String>,  // Short description or summary of the content
+    pub(crate) long_text: Option<String>,   // Full text or detailed content, extracted from `raw_html` if not given
+    pub(crate) images: Vec<FetchNewsImage>, // Images in the original content, the representative one first
+    pub(crate) scrape_images: bool,         // Whether `images` are left to `NewsFetcher::fetch_images` on `link`
     pub(crate) published_time: Option<DateTime<Local>>, // Date and time when the news was published
+    pub(crate) raw_html: Option<String>,    // Source document of the article page, as seen by the extractor
+    pub(crate) raw_feed: Option<String>,    // Feed entry the article was found in, serialized as JSON
+}
+
+pub(crate) struct FetchNewsImage {
+    pub(crate) url: String,              // Url of the image
+    pub(crate) caption: Option<String>,  // Caption displayed with the image (e.g. `<figcaption>`)
+    pub(crate) alt_text: Option<String>, // Alternative text of the image (e.g. `alt` attribute)
 }
 
 pub(crate) type FetchNewsOutput = JoinHandle<Result<()>>;
@@ -23,5 +32,8 @@ pub(crate) type FetchNewsHandler = Arc<dyn Fn(FetchNewsArticle) -> FetchNewsOutp
 #[async_trait]
 #[automock] // See: https://github.com/asomers/mockall/issues/189#issuecomment-689145249
 pub(crate) trait NewsFetcher: Send + Sync {
+    fn source_name(&self) -> String;
     async fn fetch_news(self: Arc<Self>, handler: FetchNewsHandler) -> Vec<FetchNewsOutput>;
+    // Scrape the images of an article which has already been fetched
+    async fn fetch_images(&self, link: &str) -> Result<Vec<FetchNewsImage>>;
 }
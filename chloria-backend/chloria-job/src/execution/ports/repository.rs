@@ -1,8 +1,15 @@
+use std::collections::BTreeMap;
+
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Local};
 use mockall::automock;
 
+use crate::domain::{
+    annotation::TextAnnotation, augmentation::Augmentation, difficulty::Difficulty, document::DocumentEntity,
+    fingerprint::SimHash, sentence::Sentence, split::Split, statistics::CharacterStatistics, synthesis::RenderParams,
+};
+
 pub(crate) struct InsertNewsInput {
     pub(crate) source_name: String,        // Code name of the source used to fetch the news
     pub(crate) article_id: String,         // Unique ID of the article
@@ -10,12 +17,268 @@ pub(crate) struct InsertNewsInput {
     pub(crate) title: Option<String>,      // Title of the content
     pub(crate) short_text: Option<String>, // Short description or summary of the content
     pub(crate) long_text: Option<String>,  // Full text or detailed content
-    pub(crate) image_path: Option<String>, // Path of representative image saved in file storage
+    pub(crate) images: Vec<InsertNewsImageInput>, // Images saved in file storage, the representative one first
     pub(crate) published_time: Option<DateTime<Local>>, // Date and time when the news was published
+    pub(crate) raw_html_path: Option<String>, // Path of the archived source document of the article page
+    pub(crate) raw_feed_path: Option<String>, // Path of the archived feed entry
+    pub(crate) document: Option<DocumentEntity>, // Structured body which `long_text` is rendered from, if extracted by us
+    pub(crate) extractor_version: Option<i32>,   // Version of the extractor `document` comes from
+    pub(crate) original_title: Option<String>,   // Title before normalization, if normalization has changed it
+    pub(crate) original_short_text: Option<String>, // Short text before normalization, if normalization has changed it
+    pub(crate) original_long_text: Option<String>, // Long text before normalization, if normalization has changed it
+    pub(crate) quality_reasons: Vec<String>,     // Reasons why the news fails quality rules, empty if it passes
+    pub(crate) simhash: Option<SimHash>,         // Fingerprint of the text used to cluster near-duplicates
+    pub(crate) statistics: Option<CharacterStatistics>, // Statistics of the characters of `long_text`
+    pub(crate) difficulty: Option<Difficulty>,   // Difficulty of reading `long_text`
+    pub(crate) sentences: Vec<InsertNewsSentenceInput>, // Sentences of `long_text`
+}
+
+pub(crate) struct InsertNewsOutput {
+    pub(crate) source_name: String,
+    pub(crate) article_id: String,
+}
+
+pub(crate) struct InsertNewsSentenceInput {
+    pub(crate) sentence: Sentence,
+    pub(crate) difficulty: Option<Difficulty>,
+}
+
+pub(crate) struct InsertNewsImageInput {
+    pub(crate) position: i32,            // Order of the image in the original content
+    pub(crate) caption: Option<String>,  // Caption displayed with the image
+    pub(crate) alt_text: Option<String>, // Alternative text of the image
+    pub(crate) path: String,             // Path of the image saved in file storage
+    pub(crate) width: u32,               // Width of the image in pixels
+    pub(crate) height: u32,              // Height of the image in pixels
+    pub(crate) mime_type: String,        // MIME type of the image
+    pub(crate) byte_size: usize,         // Size of the encoded image in bytes
+    pub(crate) sha256: String,           // Hex-encoded SHA-256 digest of the encoded image
+    pub(crate) perceptual_hash: String,  // Hex-encoded perceptual hash of the image
+}
+
+pub(crate) struct SelectNewsImagePathsInput {
+    pub(crate) after_news_id: i32, // Only news with a greater ID are selected, used for paging
+    pub(crate) limit: i64,
+}
+
+pub(crate) struct SelectNewsImagePathOutput {
+    pub(crate) news_id: i32,
+    pub(crate) image_path: String,
+}
+
+pub(crate) struct SelectNewsLinksInput {
+    pub(crate) after_news_id: i32, // Only news with a greater ID are selected, used for paging
+    pub(crate) limit: i64,
+}
+
+pub(crate) struct SelectNewsLinkOutput {
+    pub(crate) news_id: i32,
+    pub(crate) source_name: String,
+    pub(crate) article_id: String,
+    pub(crate) link: String,
+}
+
+pub(crate) struct SelectFileReferencesInput {
+    pub(crate) prefix: Option<String>, // Only select paths which start with this prefix
+}
+
+pub(crate) struct SelectFileReferenceOutput {
+    pub(crate) path: String, // Path of a file in file storage referenced by at least one row
+    pub(crate) byte_size: Option<i64>, // Size recorded in the database, if any
+}
+
+pub(crate) struct UpdateNewsImageMetadataInput {
+    pub(crate) path: String, // Every image row with this path is updated
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) mime_type: String,
+    pub(crate) byte_size: usize,
+    pub(crate) sha256: String,
+    pub(crate) perceptual_hash: String,
+}
+
+pub(crate) struct SelectNewsRawHtmlPathsInput {
+    pub(crate) after_news_id: i32, // Only select news whose ID is greater than this value
+    pub(crate) limit: i64,
+    pub(crate) source_name: Option<String>,
+    pub(crate) created_from: Option<DateTime<Local>>, // Inclusive
+    pub(crate) created_to: Option<DateTime<Local>>,   // Exclusive
+    pub(crate) extractor_version: i32,                // Only select news extracted by an older version (or not by us)
+}
+
+pub(crate) struct SelectNewsRawHtmlPathOutput {
+    pub(crate) news_id: i32,
+    pub(crate) source_name: String,
+    pub(crate) raw_html_path: String,
+}
+
+pub(crate) struct UpdateNewsTextInput {
+    pub(crate) news_id: i32,
+    pub(crate) long_text: Option<String>,
+    pub(crate) document: Option<DocumentEntity>,
+    pub(crate) extractor_version: i32,
+    pub(crate) original_long_text: Option<String>,
+    pub(crate) statistics: Option<CharacterStatistics>,
+    pub(crate) difficulty: Option<Difficulty>,
+    pub(crate) sentences: Vec<InsertNewsSentenceInput>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum NewsFileKind {
+    Image,
+    RawHtml,
+    RawFeed,
+}
+
+// News referenced by insights or pinned datasets are never expired
+pub(crate) struct DeleteExpiredNewsInput {
+    pub(crate) source_name: Option<String>, // Expire news of every source if not given
+    pub(crate) created_before: DateTime<Local>,
+    pub(crate) limit: i64,
+}
+
+pub(crate) struct DeleteExpiredNewsOutput {
+    pub(crate) news_count: usize, // Number of news affected in this batch
+    pub(crate) unreferenced_paths: Vec<(NewsFileKind, String)>, // Files which are not referenced anymore and can be deleted
+}
+
+pub(crate) struct SelectCharacterCountsInput {
+    pub(crate) source_name: Option<String>, // Count characters of news of every source if not given
+    pub(crate) created_from: Option<DateTime<Local>>, // Only count characters of news collected at or after this time
+    pub(crate) created_to: Option<DateTime<Local>>, // Only count characters of news collected before this time
+}
+
+pub(crate) struct SelectCharacterCountsOutput {
+    pub(crate) news_count: i64, // Number of news which have statistics
+    pub(crate) character_counts: BTreeMap<char, usize>,
+}
+
+pub(crate) struct SelectSentencesInput {
+    pub(crate) min_length: usize, // In characters, inclusive
+    pub(crate) max_length: usize, // In characters, inclusive
+}
+
+pub(crate) struct SelectSentenceOutput {
+    pub(crate) sentence_id: i32,
+    pub(crate) news_id: i32,
+    pub(crate) text: String,
+}
+
+pub(crate) struct InsertSyntheticSampleInput {
+    pub(crate) sentence_id: Option<i32>, // Sentence the text comes from, if any
+    pub(crate) news_id: Option<i32>,     // News of the sentence, which the sample keeps if the sentence changes
+    pub(crate) text: String,             // Ground truth of the sample, with line breaks between rendered lines
+    pub(crate) image_path: String,       // Path of the rendered image saved in file storage
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) render_params: RenderParams,
+    pub(crate) seed: u64, // Seed of the random generator the render parameters were drawn with
+    pub(crate) background_image_path: Option<String>, // Path of the news photo the text is put on, for scene samples
+    pub(crate) annotation: TextAnnotation, // Polygons of the lines and characters on the image
+    pub(crate) augmentations: Vec<Augmentation>, // Applied in order after rendering
+}
+
+pub(crate) struct SelectExportSamplesInput {
+    pub(crate) after_id: i32, // Only select samples whose ID (of the news for real samples) is greater, used for paging
+    pub(crate) limit: i64,
+    pub(crate) source_name: Option<String>, // Source of the news the text comes from
+    pub(crate) created_from: Option<DateTime<Local>>, // Inclusive
+    pub(crate) created_to: Option<DateTime<Local>>, // Exclusive
+    pub(crate) split: Option<Split>, // Split of the news the text comes from, unassigned news being excluded if given
+}
+
+pub(crate) struct SelectSyntheticSampleOutput {
+    pub(crate) sample_id: i32,
+    pub(crate) image_path: String,
+    pub(crate) text: String,
+    pub(crate) annotation: TextAnnotation, // Empty for samples rendered before polygons were recorded
+    pub(crate) split: Option<Split>,
+}
+
+pub(crate) struct SelectInsightImageOutput {
+    pub(crate) news_id: i32,
+    pub(crate) image_path: String, // Path of the representative image of the news
+    pub(crate) fields: String,     // Fields of the insight as they were written
+    pub(crate) split: Option<Split>,
+}
+
+pub(crate) struct SelectUnassignedNewsInput {
+    pub(crate) after_news_id: i32, // Only select news whose ID is greater than this value
+    pub(crate) limit: i64,
+}
+
+pub(crate) struct SelectUnassignedNewsOutput {
+    pub(crate) news_id: i32,
+    pub(crate) source_name: String,
+    pub(crate) article_id: String,
+    pub(crate) cluster_id: Option<i32>, // ID of the first news among near-duplicates of this news
+}
+
+pub(crate) struct InsertNewsSplitInput {
+    pub(crate) news_id: i32,
+    pub(crate) split: Split,
+    pub(crate) group_key: String, // Key the split was drawn from, e.g. `cluster:42`
+}
+
+pub(crate) struct PinDatasetInput {
+    pub(crate) name: String, // Name of the dataset, which is created if it does not exist
+    pub(crate) source_name: Option<String>, // Only add news of this source
+    pub(crate) created_from: Option<DateTime<Local>>, // Only add news created at or after this time
+    pub(crate) created_to: Option<DateTime<Local>>, // Only add news created before this time
 }
 
 #[async_trait]
 #[automock] // See: https://github.com/asomers/mockall/issues/189#issuecomment-689145249
 pub(crate) trait Repository: Send + Sync {
-    async fn insert_news(&self, inputs: Vec<InsertNewsInput>) -> Result<Vec<i32>>;
+    // News which already exist are left out of the outputs
+    async fn insert_news(&self, inputs: Vec<InsertNewsInput>) -> Result<Vec<InsertNewsOutput>>;
+    // Also set the first image as the representative one if the news has none
+    async fn insert_news_images(&self, news_id: i32, inputs: Vec<InsertNewsImageInput>) -> Result<()>;
+    // Select news which have an image path but no image metadata yet
+    async fn select_news_image_paths(&self, input: SelectNewsImagePathsInput)
+        -> Result<Vec<SelectNewsImagePathOutput>>;
+    // Select news which have a link but no image at all, leaving out news whose images have expired
+    async fn select_news_links(&self, input: SelectNewsLinksInput) -> Result<Vec<SelectNewsLinkOutput>>;
+    async fn select_file_references(&self, input: SelectFileReferencesInput) -> Result<Vec<SelectFileReferenceOutput>>;
+    // Remove every reference to the given paths, returning the number of affected rows
+    async fn delete_file_references(&self, paths: Vec<String>) -> Result<usize>;
+    async fn update_news_image_metadata(&self, input: UpdateNewsImageMetadataInput) -> Result<()>;
+    // Delete expired news along with their images
+    async fn delete_expired_news(&self, input: DeleteExpiredNewsInput) -> Result<DeleteExpiredNewsOutput>;
+    // Only delete files of the given kind of expired news, keeping their text
+    async fn delete_expired_news_files(
+        &self,
+        input: DeleteExpiredNewsInput,
+        kind: NewsFileKind,
+    ) -> Result<DeleteExpiredNewsOutput>;
+    async fn select_news_raw_html_paths(
+        &self,
+        input: SelectNewsRawHtmlPathsInput,
+    ) -> Result<Vec<SelectNewsRawHtmlPathOutput>>;
+    async fn update_news_text(&self, input: UpdateNewsTextInput) -> Result<()>;
+    // Sum the character counts of news
+    async fn select_character_counts(&self, input: SelectCharacterCountsInput) -> Result<SelectCharacterCountsOutput>;
+    // Sample sentences at random
+    // Select ids in a stable order, so that drawing from them with the same seed gives the same sentences
+    async fn select_sentence_ids(&self, input: SelectSentencesInput) -> Result<Vec<i32>>;
+    // Select sentences in the order of the given ids
+    async fn select_sentences(&self, sentence_ids: Vec<i32>) -> Result<Vec<SelectSentenceOutput>>;
+    // Select paths in a stable order, for the same reason as sentence ids
+    async fn select_background_image_paths(&self) -> Result<Vec<String>>;
+    async fn insert_synthetic_samples(&self, inputs: Vec<InsertSyntheticSampleInput>) -> Result<()>;
+    async fn select_synthetic_samples(
+        &self,
+        input: SelectExportSamplesInput,
+    ) -> Result<Vec<SelectSyntheticSampleOutput>>;
+    // Select news which have both a representative image and an insight
+    async fn select_insight_images(&self, input: SelectExportSamplesInput) -> Result<Vec<SelectInsightImageOutput>>;
+    // Select news which have not been assigned to a split yet
+    async fn select_unassigned_news(&self, input: SelectUnassignedNewsInput)
+        -> Result<Vec<SelectUnassignedNewsOutput>>;
+    // News which already have a split keep it
+    async fn insert_news_splits(&self, inputs: Vec<InsertNewsSplitInput>) -> Result<()>;
+    // Pin the dataset and add the matching news to it, returning the number of newly added news
+    async fn pin_dataset(&self, input: PinDatasetInput) -> Result<usize>;
+    // Returns whether the dataset exists
+    async fn unpin_dataset(&self, name: String) -> Result<bool>;
 }
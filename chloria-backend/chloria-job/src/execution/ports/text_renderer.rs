@@ -0,0 +1,35 @@
+use anyhow::Result;
+use mockall::automock;
+
+use crate::domain::{annotation::TextAnnotation, font::IndexedFont, synthesis::RenderParams};
+
+pub(crate) struct RenderTextInput {
+    pub(crate) lines: Vec<String>, // Rendered from top to bottom
+    pub(crate) params: RenderParams,
+}
+
+pub(crate) struct ComposeTextInput {
+    pub(crate) background: Vec<u8>, // Encoded photo the text is put on
+    pub(crate) lines: Vec<String>,
+    pub(crate) params: RenderParams, // The background color is replaced by the one of the photo under the text
+    pub(crate) max_texture: f32,     // Text is only put on regions at most this textured, see `find_low_texture_region`
+    pub(crate) min_contrast_ratio: f32, // The text color is replaced if it does not contrast enough with the photo
+}
+
+pub(crate) struct RenderTextOutput {
+    pub(crate) bytes: Vec<u8>,       // Encoded as PNG
+    pub(crate) width: u32,           // Width of the image in pixels
+    pub(crate) height: u32,          // Height of the image in pixels
+    pub(crate) params: RenderParams, // Parameters actually used, including the colors and position chosen for the photo
+    pub(crate) annotation: TextAnnotation,
+}
+
+#[automock]
+pub(crate) trait TextRenderer: Send + Sync {
+    // Fonts which render parameters can refer to by name
+    fn fonts(&self) -> Vec<IndexedFont>;
+    // Fails rather than drawing missing glyphs
+    fn render(&self, input: RenderTextInput) -> Result<RenderTextOutput>;
+    // Puts the text on the least textured region of the photo large enough for it
+    fn compose(&self, input: ComposeTextInput) -> Result<RenderTextOutput>;
+}
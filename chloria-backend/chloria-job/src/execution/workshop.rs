@@ -1,39 +1,135 @@
 use std::sync::Arc;
 
 use anyhow::Result;
+use chrono::{DateTime, Local, TimeDelta};
 use tokio::{runtime::Handle, sync::Semaphore, task::LocalSet};
 
 use super::{
     cases::LocalCase,
-    ports::{file_storage::FileStorage, http_helper::HttpHelper, news_fetcher::NewsFetcher, repository::Repository},
+    ports::{
+        archiver::Archiver,
+        article_extractor::ArticleExtractor,
+        file_storage::{FileObjectKind, FileStorage},
+        http_helper::HttpHelper,
+        image_analyzer::ImageAnalyzer,
+        image_augmenter::ImageAugmenter,
+        news_fetcher::NewsFetcher,
+        repository::Repository,
+        text_renderer::TextRenderer,
+    },
 };
+use crate::domain::{
+    difficulty::DifficultyScorer,
+    quality::QualityRuleSet,
+    split::{Split, SplitAssigner},
+    synthesis::SynthesisConfig,
+    text::TextNormalizer,
+};
+
+#[derive(Clone, Copy)]
+pub(crate) enum ImageStorageLayout {
+    Dated,            // One file per article image, grouped by source and date
+    ContentAddressed, // One file per distinct image, named after its SHA-256 digest and shared by articles
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum RetentionTarget {
+    News,                 // News rows along with every file they reference
+    File(FileObjectKind), // Only files of this kind, keeping the news rows
+}
+
+pub(crate) enum ExportDestination {
+    Bucket { prefix: String },  // Prefix of the keys in the dataset bucket
+    Directory { path: String }, // Local directory, created if it does not exist
+}
+
+pub(crate) struct ExportFilter {
+    pub(crate) source_name: Option<String>, // Export samples of every source if not given
+    pub(crate) created_from: Option<DateTime<Local>>, // Inclusive
+    pub(crate) created_to: Option<DateTime<Local>>, // Exclusive
+    pub(crate) limit: Option<usize>,        // Export every matching sample if not given
+    pub(crate) split: Option<Split>,        // Export samples of every split, assigned or not, if not given
+}
+
+#[derive(Clone)]
+pub(crate) struct RetentionRule {
+    pub(crate) source_name: Option<String>, // Applies to news of every source if not given
+    pub(crate) target: RetentionTarget,
+    pub(crate) max_age: TimeDelta, // Counted from when the news was collected
+}
 
 pub(crate) struct Config {
     pub(crate) case_permits_num: usize,
+    pub(crate) image_storage_layout: ImageStorageLayout,
+    pub(crate) archive_raw_html: bool, // Whether to keep the source documents of article pages
+    pub(crate) archive_raw_feed: bool, // Whether to keep the feed entries articles were found in
+    pub(crate) retention_rules: Vec<RetentionRule>,
+    pub(crate) text_normalizer: TextNormalizer, // Applied to the text of news before saving them
+    pub(crate) quality_rule_set: QualityRuleSet, // Evaluated on news after normalization, before saving them
+    pub(crate) difficulty_scorer: DifficultyScorer,
+    pub(crate) synthesis_config: SynthesisConfig, // Ranges which render parameters of synthetic samples are drawn from
+    pub(crate) split_assigner: SplitAssigner,     // Decides which split unassigned news go to
 }
 
 pub(crate) struct Workshop {
     pub(super) news_fetchers: Vec<Arc<dyn NewsFetcher>>,
+    pub(super) article_extractor: Arc<dyn ArticleExtractor>,
     pub(super) http_helper: Arc<dyn HttpHelper>,
+    pub(super) image_analyzer: Arc<dyn ImageAnalyzer>,
     pub(super) file_storage: Arc<dyn FileStorage>,
     pub(super) repository: Arc<dyn Repository>,
+    pub(super) text_renderer: Arc<dyn TextRenderer>,
+    pub(super) image_augmenter: Arc<dyn ImageAugmenter>,
+    pub(super) local_file_storage: Arc<dyn FileStorage>, // Used instead of `file_storage` to export to a local directory
+    pub(super) archiver: Arc<dyn Archiver>,
+    pub(super) image_storage_layout: ImageStorageLayout,
+    pub(super) archive_raw_html: bool,
+    pub(super) archive_raw_feed: bool,
+    pub(super) retention_rules: Vec<RetentionRule>,
+    pub(super) text_normalizer: Arc<TextNormalizer>,
+    pub(super) quality_rule_set: Arc<QualityRuleSet>,
+    pub(super) difficulty_scorer: Arc<DifficultyScorer>,
+    pub(super) synthesis_config: Arc<SynthesisConfig>,
+    pub(super) split_assigner: Arc<SplitAssigner>,
     semaphore: Arc<Semaphore>,
 }
 
 impl Workshop {
+    #[allow(clippy::too_many_arguments)] // One per port
     pub(crate) fn new(
         news_fetchers: Vec<Arc<dyn NewsFetcher>>,
+        article_extractor: Arc<dyn ArticleExtractor>,
         http_helper: Arc<dyn HttpHelper>,
+        image_analyzer: Arc<dyn ImageAnalyzer>,
         file_storage: Arc<dyn FileStorage>,
         repository: Arc<dyn Repository>,
+        text_renderer: Arc<dyn TextRenderer>,
+        image_augmenter: Arc<dyn ImageAugmenter>,
+        local_file_storage: Arc<dyn FileStorage>,
+        archiver: Arc<dyn Archiver>,
         config: Config,
     ) -> Self {
         let semaphore = Arc::new(Semaphore::new(config.case_permits_num));
         Self {
             news_fetchers,
+            article_extractor,
             http_helper,
+            image_analyzer,
             file_storage,
             repository,
+            text_renderer,
+            image_augmenter,
+            local_file_storage,
+            archiver,
+            image_storage_layout: config.image_storage_layout,
+            archive_raw_html: config.archive_raw_html,
+            archive_raw_feed: config.archive_raw_feed,
+            retention_rules: config.retention_rules,
+            text_normalizer: Arc::new(config.text_normalizer),
+            quality_rule_set: Arc::new(config.quality_rule_set),
+            difficulty_scorer: Arc::new(config.difficulty_scorer),
+            synthesis_config: Arc::new(config.synthesis_config),
+            split_assigner: Arc::new(config.split_assigner),
             semaphore,
         }
     }
@@ -45,12 +141,12 @@ impl Workshop {
         // - https://docs.rs/tokio/1.43.0/tokio/task/struct.LocalSet.html#use-inside-tokiospawn
         tokio::task::spawn_blocking(move || {
             Handle::current().block_on(async {
-                Ok(LocalSet::new()
+                LocalSet::new()
                     .run_until(async {
                         let _permit = semaphore.acquire().await?;
                         case.execute().await
                     })
-                    .await?)
+                    .await
             })
         })
         .await?
@@ -0,0 +1,27 @@
+use anyhow::Result;
+use tar::{Builder, Header};
+
+use crate::execution::ports::archiver::{ArchiveFile, Archiver};
+
+pub(crate) struct TarTool {}
+
+impl TarTool {
+    pub(crate) fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Archiver for TarTool {
+    fn pack(&self, files: Vec<ArchiveFile>) -> Result<Vec<u8>> {
+        let mut builder = Builder::new(vec![]);
+        for file in files {
+            // Modification times are left out, so that packing the same files always gives the same archive
+            let mut header = Header::new_ustar();
+            header.set_size(file.bytes.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(0);
+            builder.append_data(&mut header, &file.name, &file.bytes[..])?;
+        }
+        Ok(builder.into_inner()?)
+    }
+}
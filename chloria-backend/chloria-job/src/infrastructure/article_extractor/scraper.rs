@@ -0,0 +1,459 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use regex::Regex;
+use scraper::{node::Node, ElementRef, Html, Selector};
+
+use crate::{
+    domain::document::{
+        DocumentBlock, DocumentBlockKind, DocumentEntity, DocumentRuby, DocumentSpan, DocumentSpanStyle,
+    },
+    execution::ports::article_extractor::{ArticleExtractor, ExtractArticleInput, ExtractArticleOutput},
+};
+
+// History of versions:
+// - 1: Body of the first page rendered by `html2text`, with trailing spaces removed and blank lines collapsed
+// - 2: Structured document of the body of the first page, rendered as plain text without any markup
+// - 3: Readings of `<ruby>` elements are kept apart from the text instead of being mixed into it
+// - 4: Boilerplate (related links, bylines, photo credits, ads, copyright notices) is stripped from the body
+// - 5: No space is left between Japanese characters of wrapped lines, and boilerplate patterns are narrowed
+const VERSION: i32 = 5;
+
+// Blocks with a larger share of characters inside links are taken for link lists
+const MAX_LINK_DENSITY: f64 = 0.5;
+
+struct BoilerplateRules {
+    selectors: Vec<Selector>,    // Elements dropped along with their descendants
+    line_patterns: Vec<Regex>,   // Lines of text dropped from blocks, matched after trimming
+    byline_patterns: Vec<Regex>, // Lines dropped from blocks other than headings, which are as short as bylines
+}
+
+impl BoilerplateRules {
+    fn new(selectors: &[&str], line_patterns: &[&str], byline_patterns: &[&str]) -> Self {
+        Self {
+            selectors: selectors.iter().map(|s| Selector::parse(s).unwrap()).collect(),
+            line_patterns: line_patterns.iter().map(|p| Regex::new(p).unwrap()).collect(),
+            byline_patterns: byline_patterns.iter().map(|p| Regex::new(p).unwrap()).collect(),
+        }
+    }
+
+    fn extend(mut self, rules: &Self) -> Self {
+        self.selectors.extend(rules.selectors.iter().cloned());
+        self.line_patterns.extend(rules.line_patterns.iter().cloned());
+        self.byline_patterns.extend(rules.byline_patterns.iter().cloned());
+        self
+    }
+
+    fn is_boilerplate_line(&self, line: &str, block_kind: DocumentBlockKind) -> bool {
+        self.line_patterns.iter().any(|p| p.is_match(line))
+            || (block_kind != DocumentBlockKind::Heading && self.byline_patterns.iter().any(|p| p.is_match(line)))
+    }
+}
+
+struct SourceRules {
+    content_selector: Selector,          // Selector of the article body
+    boilerplate_rules: BoilerplateRules, // Including the ones shared by every source
+}
+
+pub(crate) struct ScraperTool {
+    source_rules: HashMap<String, SourceRules>, // By source name
+}
+
+impl ScraperTool {
+    pub(crate) fn new() -> Self {
+        let common_boilerplate_rules = BoilerplateRules::new(
+            &["aside", "nav", "footer", "form"],
+            &[
+                r"^[\[【(（]?(PR|広告|スポンサーリンク)[\]】)）]?$",
+                r"^(©|Ⓒ|\([cC]\)|（[cC]）|Copyright)",
+                // Only notices on their own, so that articles about unauthorized copies are kept
+                r"^[^。]{0,20}無断(転載|複製|転用)(を)?(禁じます|禁止(します|です)?|お断りします)。?$",
+            ],
+            &[],
+        );
+        let mut source_rules = HashMap::new();
+        source_rules.insert(
+            "Yahoo".to_string(),
+            SourceRules {
+                content_selector: Selector::parse("article#uamods .article_body").unwrap(),
+                boilerplate_rules: BoilerplateRules::new(
+                    &["[id^=\"yads\"]", ".yads_ad", "[class*=\"relatedLink\"]"],
+                    &[
+                        r"^[【\[]?(関連記事|関連リンク|あわせて読みたい)[】\]]?$",
+                        r"^[(（]?(写真|撮影|画像|図表)[：:／/].*[)）]?$",
+                        r"^(文|取材・文|構成)[：:／/]",
+                    ],
+                    &[r"^[(（]?[^。、\s]{1,20}(記者|撮影)[)）]?$"],
+                )
+                .extend(&common_boilerplate_rules),
+            },
+        );
+        Self { source_rules }
+    }
+}
+
+impl ArticleExtractor for ScraperTool {
+    fn version(&self) -> i32 {
+        VERSION
+    }
+
+    fn extract(&self, input: ExtractArticleInput) -> Result<ExtractArticleOutput> {
+        let Some(source_rules) = self.source_rules.get(&input.source_name) else {
+            return Ok(ExtractArticleOutput { document: None });
+        };
+        let document_html = Html::parse_document(&input.html);
+        // TODO: Extract all content if there are multiple pages
+        let document = document_html
+            .select(&source_rules.content_selector)
+            .next()
+            .map(|content_element| {
+                let mut builder = DocumentBuilder::new(&source_rules.boilerplate_rules);
+                builder.visit(content_element, DocumentBlockKind::Paragraph);
+                builder.finish()
+            });
+        Ok(ExtractArticleOutput { document })
+    }
+}
+
+struct DocumentBuilder<'a> {
+    boilerplate_rules: &'a BoilerplateRules,
+    blocks: Vec<DocumentBlock>,
+    current_block: Option<DocumentBlock>,
+}
+
+impl<'a> DocumentBuilder<'a> {
+    fn new(boilerplate_rules: &'a BoilerplateRules) -> Self {
+        Self {
+            boilerplate_rules,
+            blocks: vec![],
+            current_block: None,
+        }
+    }
+
+    fn visit(&mut self, element: ElementRef, block_kind: DocumentBlockKind) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.push_text(text, block_kind),
+                Node::Element(child_element) => {
+                    let Some(child) = ElementRef::wrap(child) else {
+                        continue;
+                    };
+                    if self.boilerplate_rules.selectors.iter().any(|s| s.matches(&child)) {
+                        continue;
+                    }
+                    match child_element.name() {
+                        "script" | "style" | "noscript" | "iframe" | "img" | "svg" | "button" => {}
+                        "br" => self.push_line_break(block_kind),
+                        "ruby" => self.visit_ruby(child, block_kind),
+                        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => self.visit_block(child, DocumentBlockKind::Heading),
+                        "li" => self.visit_block(child, DocumentBlockKind::ListItem),
+                        "blockquote" => self.visit_block(child, DocumentBlockKind::Quote),
+                        "figcaption" => self.visit_block(child, DocumentBlockKind::Caption),
+                        // Paragraphs inside quotes are still quotes
+                        "p" => self.visit_block(
+                            child,
+                            match block_kind {
+                                DocumentBlockKind::Quote => DocumentBlockKind::Quote,
+                                _ => DocumentBlockKind::Paragraph,
+                            },
+                        ),
+                        "div" | "section" | "ul" | "ol" | "figure" => self.visit_block(child, block_kind),
+                        "strong" | "b" => self.visit_span(child, block_kind, DocumentSpanStyle::Bold),
+                        "em" | "i" => self.visit_span(child, block_kind, DocumentSpanStyle::Italic),
+                        "a" => match child_element.attr("href") {
+                            Some(href) => {
+                                self.visit_span(child, block_kind, DocumentSpanStyle::Link { href: href.to_string() })
+                            }
+                            None => self.visit(child, block_kind),
+                        },
+                        _ => self.visit(child, block_kind),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn visit_block(&mut self, element: ElementRef, block_kind: DocumentBlockKind) {
+        self.finish_block();
+        self.visit(element, block_kind);
+        self.finish_block();
+    }
+
+    fn visit_span(&mut self, element: ElementRef, block_kind: DocumentBlockKind, style: DocumentSpanStyle) {
+        let start_block_index = self.blocks.len();
+        let start = self.current_length();
+        self.visit(element, block_kind);
+        // Spans crossing blocks are dropped
+        if self.blocks.len() != start_block_index {
+            return;
+        }
+        let end = self.current_length();
+        if let Some(block) = self.current_block.as_mut().filter(|_| end > start) {
+            block.spans.push(DocumentSpan { style, start, end });
+        }
+    }
+
+    // Only the base text goes into the text, each reading is attached to the base text preceding it
+    // Ref: https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-ruby-element
+    fn visit_ruby(&mut self, element: ElementRef, block_kind: DocumentBlockKind) {
+        let mut base_text = String::new();
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => base_text.push_str(text),
+                Node::Element(child_element) => {
+                    let Some(child) = ElementRef::wrap(child) else {
+                        continue;
+                    };
+                    match child_element.name() {
+                        "rp" => {}
+                        "rt" => {
+                            let reading = child.text().collect::<String>().trim().to_string();
+                            self.push_ruby(&std::mem::take(&mut base_text), reading, block_kind);
+                        }
+                        _ => base_text.push_str(&child.text().collect::<String>()),
+                    }
+                }
+                _ => {}
+            }
+        }
+        // Base text without any reading
+        self.push_text(&base_text, block_kind);
+    }
+
+    fn push_ruby(&mut self, base_text: &str, reading: String, block_kind: DocumentBlockKind) {
+        let base_text = base_text.trim();
+        if base_text.is_empty() {
+            return;
+        }
+        self.current_block(block_kind).text.push_str(base_text);
+        let end = self.current_length();
+        let start = end - base_text.chars().count();
+        if let (Some(block), false) = (self.current_block.as_mut(), reading.is_empty()) {
+            block.rubies.push(DocumentRuby { start, end, reading });
+        }
+    }
+
+    fn push_text(&mut self, text: &str, block_kind: DocumentBlockKind) {
+        // Collapse whitespace as browsers do
+        for (index, word) in text.split_whitespace().enumerate() {
+            let block = self.current_block(block_kind);
+            let needs_space = (index > 0 || text.starts_with(char::is_whitespace))
+                && !block.text.is_empty()
+                && !block.text.ends_with([' ', '\n']);
+            if needs_space {
+                block.text.push(' ');
+            }
+            block.text.push_str(word);
+        }
+        if text.ends_with(char::is_whitespace) {
+            if let Some(block) = self.current_block.as_mut() {
+                if !block.text.is_empty() && !block.text.ends_with([' ', '\n']) {
+                    block.text.push(' ');
+                }
+            }
+        }
+    }
+
+    fn push_line_break(&mut self, block_kind: DocumentBlockKind) {
+        let block = self.current_block(block_kind);
+        if block.text.ends_with(' ') {
+            block.text.pop();
+        }
+        block.text.push('\n');
+    }
+
+    fn current_block(&mut self, block_kind: DocumentBlockKind) -> &mut DocumentBlock {
+        self.current_block.get_or_insert_with(|| DocumentBlock {
+            kind: block_kind,
+            text: String::new(),
+            spans: vec![],
+            rubies: vec![],
+        })
+    }
+
+    fn current_length(&self) -> usize {
+        self.current_block.as_ref().map_or(0, |b| b.text.chars().count())
+    }
+
+    fn finish_block(&mut self) {
+        let Some(mut block) = self.current_block.take() else {
+            return;
+        };
+        // Lines are trimmed and boilerplate ones dropped, keeping the original offset of each character
+        let chars: Vec<char> = block.text.chars().collect();
+        let mut kept_chars: Vec<(char, usize)> = vec![];
+        let mut line_start = 0;
+        for line_end in (0..chars.len()).filter(|i| chars[*i] == '\n').chain([chars.len()]) {
+            let line: String = chars[line_start..line_end].iter().collect();
+            let trimmed_line = line.trim();
+            if !self.boilerplate_rules.is_boilerplate_line(trimmed_line, block.kind) {
+                if !kept_chars.is_empty() {
+                    kept_chars.push(('\n', line_start - 1));
+                }
+                let trimmed_start = line_start + line.chars().take_while(|c| c.is_whitespace()).count();
+                kept_chars.extend(trimmed_line.chars().zip(trimmed_start..));
+            }
+            line_start = line_end + 1;
+        }
+        // Line wraps in the source collapse into spaces, which do not separate Japanese words
+        let mut kept_chars: Vec<(char, usize)> = kept_chars
+            .iter()
+            .enumerate()
+            .filter(|(index, (c, _))| {
+                *c != ' '
+                    || !(*index > 0 && is_cjk(kept_chars[index - 1].0))
+                    || !kept_chars.get(index + 1).is_some_and(|(c, _)| is_cjk(*c))
+            })
+            .map(|(_, kept_char)| *kept_char)
+            .collect();
+        while kept_chars.last().is_some_and(|(c, _)| c.is_whitespace()) {
+            kept_chars.pop();
+        }
+        if kept_chars.is_empty() {
+            return;
+        }
+        let text: String = kept_chars.iter().map(|(c, _)| *c).collect();
+        if text != block.text {
+            let mut offsets = Vec::with_capacity(chars.len() + 1);
+            let mut index = 0;
+            for original_index in 0..=chars.len() {
+                while index < kept_chars.len() && kept_chars[index].1 < original_index {
+                    index += 1;
+                }
+                offsets.push(index);
+            }
+            for span in &mut block.spans {
+                span.start = offsets[span.start];
+                span.end = offsets[span.end];
+            }
+            block.spans.retain(|s| s.end > s.start);
+            for ruby in &mut block.rubies {
+                ruby.start = offsets[ruby.start];
+                ruby.end = offsets[ruby.end];
+            }
+            block.rubies.retain(|r| r.end > r.start);
+        }
+        block.text = text;
+        // Link lists (e.g. related articles) are mostly made of link text
+        let link_length: usize = block
+            .spans
+            .iter()
+            .filter(|s| matches!(s.style, DocumentSpanStyle::Link { .. }))
+            .map(|s| s.end - s.start)
+            .sum();
+        if link_length as f64 > block.text.chars().count() as f64 * MAX_LINK_DENSITY {
+            return;
+        }
+        self.blocks.push(block);
+    }
+
+    fn finish(mut self) -> DocumentEntity {
+        self.finish_block();
+        DocumentEntity { blocks: self.blocks }
+    }
+}
+
+// Japanese scripts and full-width forms, which are written without spaces between words
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3000}'..='\u{30FF}' | '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}' | '\u{FF00}'..='\u{FFEF}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScraperTool;
+    use crate::{
+        domain::document::{DocumentBlockKind, DocumentEntity, DocumentSpanStyle},
+        execution::ports::article_extractor::{ArticleExtractor, ExtractArticleInput},
+    };
+
+    fn extract_yahoo_fixture() -> DocumentEntity {
+        let output = ScraperTool::new()
+            .extract(ExtractArticleInput {
+                source_name: "Yahoo".to_string(),
+                html: include_str!("fixtures/yahoo.html").to_string(),
+            })
+            .unwrap();
+        output.document.unwrap()
+    }
+
+    #[test]
+    fn extract_yahoo_document() {
+        let document = extract_yahoo_fixture();
+        // Lines wrapped in the source are joined without a space between Japanese characters
+        assert_eq!(
+            document.plain_text(),
+            "宇宙航空研究開発機構は種子島宇宙センターから、新型の観測衛星を予定通り打ち上げたと発表した。\n\
+             衛星は約20分後に軌道へ投入され、通信も確認された。\n\
+             観測の目的\n\
+             今回の衛星は、温室効果ガスの濃度を高い精度で観測する。\n\
+             データは来年から一般に公開される予定だ。\n\
+             打ち上げに関わった技術者は約三百人\n\
+             打ち上げは Global Observing Mission の一環だ。"
+        );
+        let block_kinds: Vec<_> = document.blocks.iter().map(|b| b.kind).collect();
+        assert!(matches!(
+            block_kinds[..],
+            [
+                DocumentBlockKind::Paragraph,
+                DocumentBlockKind::Heading,
+                DocumentBlockKind::Paragraph,
+                DocumentBlockKind::ListItem,
+                DocumentBlockKind::Quote,
+            ]
+        ));
+        let bold = &document.blocks[0].spans[0];
+        assert!(matches!(bold.style, DocumentSpanStyle::Bold));
+        assert_eq!((bold.start, bold.end), (31, 35));
+        let link = &document.blocks[2].spans[0];
+        assert!(matches!(&link.style, DocumentSpanStyle::Link { href } if href == "https://example.com/glossary"));
+        assert_eq!((link.start, link.end), (7, 13));
+        let italic = &document.blocks[4].spans[0];
+        assert!(matches!(italic.style, DocumentSpanStyle::Italic));
+        assert_eq!((italic.start, italic.end), (6, 30));
+    }
+
+    #[test]
+    fn extract_yahoo_rubies() {
+        let document = extract_yahoo_fixture();
+        // Readings and `<rp>` fallbacks are kept out of the text
+        let rubies: Vec<_> = document
+            .plain_text_rubies()
+            .into_iter()
+            .map(|r| (r.start, r.end, r.reading))
+            .collect();
+        assert_eq!(
+            rubies,
+            [
+                (11, 14, "たねがしま".to_string()),
+                (138, 141, "ぎじゅつしゃ".to_string()),
+                (143, 145, "さんびゃく".to_string()),
+            ]
+        );
+        let rubies = &document.blocks[3].rubies;
+        assert_eq!((rubies[0].start, rubies[0].end), (9, 12));
+    }
+
+    #[test]
+    fn keep_lines_resembling_boilerplate() {
+        let html = "<article id=\"uamods\"><div class=\"article_body\">\
+            <h2>若手記者</h2>\
+            <p>記事の無断転載が相次いでいる。<br>無断転載を禁じる法案が可決された。</p>\
+            <p>（山田太郎記者）</p>\
+            <p>無断転載を禁じます。</p>\
+            </div></article>";
+        let output = ScraperTool::new()
+            .extract(ExtractArticleInput {
+                source_name: "Yahoo".to_string(),
+                html: html.to_string(),
+            })
+            .unwrap();
+        assert_eq!(
+            output.document.unwrap().plain_text(),
+            "若手記者\n記事の無断転載が相次いでいる。\n無断転載を禁じる法案が可決された。"
+        );
+    }
+}
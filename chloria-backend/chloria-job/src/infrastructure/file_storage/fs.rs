@@ -0,0 +1,88 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+
+use crate::execution::ports::file_storage::{
+    DeleteFilesInput, DownloadFileInput, FileObjectPath, FileStorage, ListFilesInput, ListFilesOutput, UploadFileInput,
+};
+
+// Files in a local directory, e.g. to export datasets to a machine which trains models
+// Kinds are not kept apart, since the caller chooses the directory of every file with its key
+pub(crate) struct FsTool {}
+
+impl FsTool {
+    pub(crate) fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait(?Send)]
+impl FileStorage for FsTool {
+    async fn upload_file(&self, input: UploadFileInput) -> Result<String> {
+        let FileObjectPath::Keyed { key } = input.path else {
+            bail!("Only keyed files can be saved in a local directory.");
+        };
+        if let Some(parent) = Path::new(&key).parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&key, input.bytes)?;
+        Ok(key)
+    }
+
+    async fn download_file(&self, input: DownloadFileInput) -> Result<Vec<u8>> {
+        Ok(fs::read(input.path)?)
+    }
+
+    // The prefix is a path, whose last component is matched as a prefix of the names in its directory
+    async fn list_files(&self, input: ListFilesInput) -> Result<Vec<ListFilesOutput>> {
+        let prefix = input.prefix.unwrap_or_default();
+        let dir = match prefix.ends_with('/') || prefix.is_empty() {
+            true => PathBuf::from(&prefix),
+            false => Path::new(&prefix).parent().map(Path::to_path_buf).unwrap_or_default(),
+        };
+        let mut outputs = vec![];
+        let mut dirs = vec![dir];
+        while let Some(dir) = dirs.pop() {
+            // Paths are kept relative, as they were given, when listing the working directory
+            let read_dir = match dir.as_os_str().is_empty() {
+                true => fs::read_dir("."),
+                false => fs::read_dir(&dir),
+            };
+            let entries = match read_dir {
+                Ok(entries) => entries,
+                Err(_) => continue, // Nothing has been saved there yet
+            };
+            for entry in entries {
+                let entry = entry?;
+                let path = dir.join(entry.file_name());
+                let metadata = entry.metadata()?;
+                if metadata.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                let path = path.to_string_lossy().to_string();
+                if !path.starts_with(&prefix) {
+                    continue;
+                }
+                outputs.push(ListFilesOutput {
+                    path,
+                    size: metadata.len() as usize,
+                    modified_time: metadata.modified().ok().map(DateTime::<Local>::from),
+                });
+            }
+        }
+        Ok(outputs)
+    }
+
+    async fn delete_files(&self, input: DeleteFilesInput) -> Result<()> {
+        for path in &input.paths {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
@@ -1,10 +1,14 @@
-use std::{fs, io::Cursor};
+use std::{
+    fs,
+    io::{Cursor, Read, Write},
+};
 
 use anyhow::{bail, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Duration, FixedOffset, Local, TimeDelta};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use minio::s3::{
-    args::PutObjectArgs,
+    args::{GetObjectArgs, ListObjectsV2Args, PutObjectArgs, RemoveObjectArgs, StatObjectArgs},
     client::Client as S3Client,
     creds::{Credentials, StaticProvider},
     http::BaseUrl,
@@ -13,7 +17,10 @@ use reqwest::{Certificate, Client as HttpClient};
 use serde::Deserialize;
 use tokio::sync::RwLock;
 
-use crate::execution::ports::file_storage::{FileObjectKind, FileStorage, UploadFileInput};
+use crate::execution::ports::file_storage::{
+    DeleteFilesInput, DownloadFileInput, FileObjectKind, FileObjectPath, FileStorage, ListFilesInput, ListFilesOutput,
+    UploadFileInput,
+};
 
 // Doc: https://min.io/docs/minio/linux/developers/security-token-service/AssumeRoleWithWebIdentity.html#response-elements
 #[derive(Deserialize)]
@@ -45,6 +52,8 @@ pub(crate) struct MinioClient {
     expiration: RwLock<Option<DateTime<FixedOffset>>>,
     client: RwLock<Option<S3Client>>,
     origin_bucket_name: String,
+    synthetic_bucket_name: String,
+    dataset_bucket_name: String,
 }
 
 impl MinioClient {
@@ -54,6 +63,8 @@ impl MinioClient {
         web_identity_token_file: String,
         tenant_endpoint: String,
         origin_bucket_name: String,
+        synthetic_bucket_name: String,
+        dataset_bucket_name: String,
     ) -> Result<Self> {
         let operator_cacert = match operator_cacert_file {
             Some(cacert_file) => fs::read_to_string(cacert_file).ok(),
@@ -72,6 +83,8 @@ impl MinioClient {
             expiration: RwLock::new(None),
             client: RwLock::new(None),
             origin_bucket_name,
+            synthetic_bucket_name,
+            dataset_bucket_name,
         })
     }
 
@@ -99,6 +112,22 @@ impl MinioClient {
         Ok(())
     }
 
+    fn bucket_name(&self, kind: &FileObjectKind) -> &str {
+        match kind {
+            // Raw documents are stored next to the images of the same article
+            FileObjectKind::Origin | FileObjectKind::RawHtml | FileObjectKind::RawFeed => &self.origin_bucket_name,
+            FileObjectKind::Synthetic => &self.synthetic_bucket_name,
+            FileObjectKind::Dataset => &self.dataset_bucket_name,
+        }
+    }
+
+    fn is_compressed(kind: &FileObjectKind) -> bool {
+        match kind {
+            FileObjectKind::Origin | FileObjectKind::Synthetic | FileObjectKind::Dataset => false,
+            FileObjectKind::RawHtml | FileObjectKind::RawFeed => true,
+        }
+    }
+
     async fn is_expired(&self) -> bool {
         let is_expired = match *self.expiration.read().await {
             Some(expiration) => {
@@ -147,25 +176,117 @@ impl FileStorage for MinioClient {
             Some(client) => client,
             None => bail!("The client has not been initialized."),
         };
-        let bucket_name = match input.kind {
-            FileObjectKind::Origin => &self.origin_bucket_name,
+        let bucket_name = self.bucket_name(&input.kind);
+        let is_compressed = Self::is_compressed(&input.kind);
+        let object_name = match input.path {
+            FileObjectPath::Dated {
+                source_name,
+                key,
+                created_time,
+            } => match is_compressed {
+                true => format!("{}/{}/{}.gz", source_name, created_time.format("%Y/%m/%d"), key),
+                false => format!("{}/{}/{}", source_name, created_time.format("%Y/%m/%d"), key),
+            },
+            FileObjectPath::ContentAddressed { sha256, extension } => {
+                let object_name = format!("sha256/{}/{}.{}", &sha256[..2], sha256, extension);
+                if client
+                    .stat_object(&StatObjectArgs::new(bucket_name, &object_name)?)
+                    .await
+                    .is_ok()
+                {
+                    return Ok(object_name);
+                }
+                object_name
+            }
+            FileObjectPath::Keyed { key } => key,
         };
-        let object_name = format!(
-            "{}/{}/{}",
-            input.source_name,
-            input.created_time.format("%Y/%m/%d"),
-            input.key
-        );
-        let object_size = input.bytes.len();
+        let bytes = match is_compressed {
+            true => {
+                let mut encoder = GzEncoder::new(vec![], Compression::default());
+                encoder.write_all(&input.bytes)?;
+                encoder.finish()?
+            }
+            false => input.bytes,
+        };
+        let object_size = bytes.len();
         client
             .put_object(&mut PutObjectArgs::new(
                 bucket_name,
                 &object_name,
-                &mut Cursor::new(input.bytes),
+                &mut Cursor::new(bytes),
                 Some(object_size),
                 None,
             )?)
             .await?;
         Ok(object_name)
     }
+
+    async fn download_file(&self, input: DownloadFileInput) -> Result<Vec<u8>> {
+        self.reload().await?;
+        let client = self.client.read().await;
+        let client = match &*client {
+            Some(client) => client,
+            None => bail!("The client has not been initialized."),
+        };
+        let bucket_name = self.bucket_name(&input.kind);
+        let response = client
+            .get_object(&GetObjectArgs::new(bucket_name, &input.path)?)
+            .await?;
+        let bytes: Vec<u8> = response.bytes().await?.into();
+        match Self::is_compressed(&input.kind) {
+            true => {
+                let mut decompressed_bytes = vec![];
+                GzDecoder::new(&bytes[..]).read_to_end(&mut decompressed_bytes)?;
+                Ok(decompressed_bytes)
+            }
+            false => Ok(bytes),
+        }
+    }
+
+    async fn list_files(&self, input: ListFilesInput) -> Result<Vec<ListFilesOutput>> {
+        self.reload().await?;
+        let client = self.client.read().await;
+        let client = match &*client {
+            Some(client) => client,
+            None => bail!("The client has not been initialized."),
+        };
+        let bucket_name = self.bucket_name(&input.kind);
+        let mut outputs = vec![];
+        let mut continuation_token = None;
+        loop {
+            let mut args = ListObjectsV2Args::new(bucket_name)?;
+            args.prefix = input.prefix.as_deref();
+            args.continuation_token = continuation_token;
+            let response = client.list_objects_v2(&args).await?;
+            for item in response.contents {
+                if item.is_prefix {
+                    continue;
+                }
+                outputs.push(ListFilesOutput {
+                    path: item.name,
+                    size: item.size.unwrap_or_default(),
+                    modified_time: item.last_modified.map(|t| t.into()),
+                });
+            }
+            match (response.is_truncated, response.next_continuation_token) {
+                (true, Some(next_continuation_token)) => continuation_token = Some(next_continuation_token),
+                _ => break,
+            }
+        }
+        Ok(outputs)
+    }
+
+    async fn delete_files(&self, input: DeleteFilesInput) -> Result<()> {
+        self.reload().await?;
+        let client = self.client.read().await;
+        let client = match &*client {
+            Some(client) => client,
+            None => bail!("The client has not been initialized."),
+        };
+        let bucket_name = self.bucket_name(&input.kind);
+        for path in &input.paths {
+            client.remove_object(&RemoveObjectArgs::new(bucket_name, path)?).await?;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,79 @@
+use std::io::Cursor;
+
+use anyhow::{Context, Result};
+use image::{imageops::FilterType, ImageReader};
+use sha2::{Digest, Sha256};
+
+use crate::execution::ports::image_analyzer::{AnalyzeImageOutput, ImageAnalyzer};
+
+pub(crate) struct ImageTool {}
+
+impl ImageTool {
+    pub(crate) fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ImageAnalyzer for ImageTool {
+    fn analyze(&self, bytes: &[u8]) -> Result<AnalyzeImageOutput> {
+        let reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+        let format = reader.format().context("Unknown image format.")?;
+        let image = reader.decode()?;
+        // Difference hash (dHash): shrink to 9x8 grayscale and compare each pixel with its right neighbor
+        // Ref: https://www.hackerfactor.com/blog/index.php?/archives/529-Kind-of-Like-That.html
+        let thumbnail = image.resize_exact(9, 8, FilterType::Triangle).into_luma8();
+        let mut perceptual_hash: u64 = 0;
+        for y in 0..8 {
+            for x in 0..8 {
+                perceptual_hash <<= 1;
+                if thumbnail.get_pixel(x, y)[0] < thumbnail.get_pixel(x + 1, y)[0] {
+                    perceptual_hash |= 1;
+                }
+            }
+        }
+        Ok(AnalyzeImageOutput {
+            width: image.width(),
+            height: image.height(),
+            mime_type: format.to_mime_type().to_string(),
+            extension: format.extensions_str().first().unwrap_or(&"bin").to_string(),
+            byte_size: bytes.len(),
+            sha256: format!("{:x}", Sha256::digest(bytes)),
+            perceptual_hash: format!("{:016x}", perceptual_hash),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use image::{GrayImage, ImageFormat, Luma};
+
+    use super::ImageTool;
+    use crate::execution::ports::image_analyzer::ImageAnalyzer;
+
+    #[test]
+    fn analyze_image() {
+        let encode = |image: GrayImage| {
+            let mut bytes = Cursor::new(vec![]);
+            image.write_to(&mut bytes, ImageFormat::Png).unwrap();
+            bytes.into_inner()
+        };
+        // Every pixel is darker than its right neighbor, then brighter
+        let brightening = encode(GrayImage::from_fn(90, 40, |x, _| Luma([(x * 2) as u8])));
+        let darkening = encode(GrayImage::from_fn(90, 40, |x, _| Luma([255 - (x * 2) as u8])));
+        let image_tool = ImageTool::new();
+        let metadata = image_tool.analyze(&brightening).unwrap();
+        assert_eq!((metadata.width, metadata.height), (90, 40));
+        assert_eq!(
+            (metadata.mime_type.as_str(), metadata.extension.as_str()),
+            ("image/png", "png")
+        );
+        assert_eq!(metadata.perceptual_hash, "ffffffffffffffff");
+        assert_eq!(
+            image_tool.analyze(&darkening).unwrap().perceptual_hash,
+            "0000000000000000"
+        );
+        assert!(image_tool.analyze(b"<html></html>").is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use std::{f32::consts::PI, io::Cursor};
+
+use anyhow::{bail, Result};
+use image::{codecs::jpeg::JpegEncoder, imageops, ImageFormat, Rgb, RgbImage};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::super::image_analyzer::image::ImageTool;
+use crate::{
+    domain::{annotation::bounds, augmentation::Augmentation},
+    execution::ports::image_augmenter::{AugmentImageInput, AugmentImageOutput, CropImageInput, ImageAugmenter},
+};
+
+impl ImageAugmenter for ImageTool {
+    fn augment(&self, input: AugmentImageInput) -> Result<AugmentImageOutput> {
+        let mut image = image::load_from_memory(&input.bytes)?.into_rgb8();
+        for augmentation in &input.augmentations {
+            image = match augmentation {
+                Augmentation::Perspective { .. } | Augmentation::Rotation { .. } | Augmentation::Curve { .. } => {
+                    warp(&image, augmentation)
+                }
+                Augmentation::Occlusion { rectangle, color } => occlude(image, *rectangle, *color),
+                Augmentation::Lighting { angle, strength } => light(image, *angle, *strength),
+                Augmentation::GaussianBlur { sigma } => imageops::blur(&image, *sigma),
+                Augmentation::MotionBlur { length, angle } => blur_motion(&image, *length, *angle),
+                Augmentation::Noise { sigma, seed } => add_noise(image, *sigma, *seed),
+                Augmentation::Jpeg { quality } => compress(&image, *quality)?,
+            };
+        }
+        let mut bytes = vec![];
+        image.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)?;
+        Ok(AugmentImageOutput {
+            bytes,
+            width: image.width(),
+            height: image.height(),
+        })
+    }
+
+    fn crop(&self, input: CropImageInput) -> Result<Vec<u8>> {
+        let image = image::load_from_memory(&input.bytes)?.into_rgb8();
+        let [x_min, y_min, x_max, y_max] = bounds(&input.polygon);
+        let (x_min, y_min) = (x_min.floor().max(0.0) as u32, y_min.floor().max(0.0) as u32);
+        let (x_max, y_max) = (
+            (x_max.ceil().max(0.0) as u32).min(image.width()),
+            (y_max.ceil().max(0.0) as u32).min(image.height()),
+        );
+        if x_min >= x_max || y_min >= y_max {
+            bail!("The polygon is outside of the image.");
+        }
+        let cropped = imageops::crop_imm(&image, x_min, y_min, x_max - x_min, y_max - y_min).to_image();
+        let mut bytes = vec![];
+        cropped.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)?;
+        Ok(bytes)
+    }
+}
+
+// Pixels are taken from where the augmentation maps them back to, repeating the edges of the image beyond it
+fn warp(image: &RgbImage, augmentation: &Augmentation) -> RgbImage {
+    let size = [image.width(), image.height()];
+    let [width, height] = augmentation.output_size(size);
+    RgbImage::from_fn(width, height, |x, y| {
+        let [sx, sy] = augmentation.inverse_map_point([x as f32 + 0.5, y as f32 + 0.5], size);
+        sample(image, sx - 0.5, sy - 0.5)
+    })
+}
+
+// Bilinear interpolation between the 4 pixels around the point
+fn sample(image: &RgbImage, x: f32, y: f32) -> Rgb<u8> {
+    let (max_x, max_y) = (image.width() as f32 - 1.0, image.height() as f32 - 1.0);
+    let (x, y) = (x.clamp(0.0, max_x), y.clamp(0.0, max_y));
+    let (x0, y0) = (x.floor(), y.floor());
+    let (x1, y1) = ((x0 + 1.0).min(max_x), (y0 + 1.0).min(max_y));
+    let (tx, ty) = (x - x0, y - y0);
+    let [p00, p10, p01, p11] =
+        [(x0, y0), (x1, y0), (x0, y1), (x1, y1)].map(|(x, y)| image.get_pixel(x as u32, y as u32));
+    Rgb(std::array::from_fn(|c| {
+        let top = p00[c] as f32 * (1.0 - tx) + p10[c] as f32 * tx;
+        let bottom = p01[c] as f32 * (1.0 - tx) + p11[c] as f32 * tx;
+        (top * (1.0 - ty) + bottom * ty).round() as u8
+    }))
+}
+
+fn occlude(mut image: RgbImage, rectangle: [f32; 4], color: [u8; 3]) -> RgbImage {
+    let (width, height) = (image.width() as f32, image.height() as f32);
+    let (x_min, y_min) = ((rectangle[0] * width) as u32, (rectangle[1] * height) as u32);
+    let (x_max, y_max) = (
+        (((rectangle[0] + rectangle[2]) * width) as u32).min(image.width()),
+        (((rectangle[1] + rectangle[3]) * height) as u32).min(image.height()),
+    );
+    for y in y_min..y_max {
+        for x in x_min..x_max {
+            image.put_pixel(x, y, Rgb(color));
+        }
+    }
+    image
+}
+
+fn light(mut image: RgbImage, angle: f32, strength: f32) -> RgbImage {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let (center_x, center_y) = (image.width() as f32 / 2.0, image.height() as f32 / 2.0);
+    // Distance from the center to the farthest corner along the direction
+    let reach = (center_x * cos.abs() + center_y * sin.abs()).max(1.0);
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let t = ((x as f32 - center_x) * cos + (y as f32 - center_y) * sin) / reach;
+        let factor = 1.0 + strength * t;
+        pixel.0 = pixel.0.map(|c| (c as f32 * factor).round().clamp(0.0, 255.0) as u8);
+    }
+    image
+}
+
+// Averages pixels along a segment of the given length centered on each pixel
+fn blur_motion(image: &RgbImage, length: f32, angle: f32) -> RgbImage {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let samples_num = length.ceil().max(1.0) as usize + 1;
+    RgbImage::from_fn(image.width(), image.height(), |x, y| {
+        let mut sums = [0.0f32; 3];
+        for i in 0..samples_num {
+            let t = length * (i as f32 / (samples_num - 1).max(1) as f32 - 0.5);
+            let pixel = sample(image, x as f32 + t * cos, y as f32 + t * sin);
+            for (sum, channel) in sums.iter_mut().zip(pixel.0) {
+                *sum += channel as f32;
+            }
+        }
+        Rgb(sums.map(|s| (s / samples_num as f32).round() as u8))
+    })
+}
+
+// Gaussian noise drawn with the Box-Muller transform
+// Ref: https://en.wikipedia.org/wiki/Box%E2%80%93Muller_transform
+fn add_noise(mut image: RgbImage, sigma: f32, seed: u64) -> RgbImage {
+    let mut rng = StdRng::seed_from_u64(seed);
+    for pixel in image.pixels_mut() {
+        pixel.0 = pixel.0.map(|c| {
+            let (u1, u2) = (rng.random::<f32>().max(f32::MIN_POSITIVE), rng.random::<f32>());
+            let noise = (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos() * sigma;
+            (c as f32 + noise).round().clamp(0.0, 255.0) as u8
+        });
+    }
+    image
+}
+
+fn compress(image: &RgbImage, quality: u8) -> Result<RgbImage> {
+    let mut bytes = vec![];
+    image.write_with_encoder(JpegEncoder::new_with_quality(&mut bytes, quality))?;
+    Ok(image::load_from_memory_with_format(&bytes, ImageFormat::Jpeg)?.into_rgb8())
+}
@@ -1,4 +1,9 @@
+pub(crate) mod archiver;
+pub(crate) mod article_extractor;
 pub(crate) mod file_storage;
 pub(crate) mod http_helper;
+pub(crate) mod image_analyzer;
+pub(crate) mod image_augmenter;
 pub(crate) mod news_fetcher;
 pub(crate) mod repository;
+pub(crate) mod text_renderer;
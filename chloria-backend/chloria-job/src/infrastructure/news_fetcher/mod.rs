@@ -1,2 +1,3 @@
 pub(crate) mod newsdata;
+mod page;
 pub(crate) mod yahoo;
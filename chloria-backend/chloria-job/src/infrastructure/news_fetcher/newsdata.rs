@@ -5,17 +5,21 @@ use async_trait::async_trait;
 use chrono::NaiveDateTime;
 use chrono_tz::Tz;
 use log::{error, info};
-use reqwest::Client;
+use reqwest::{Client, Url};
+use scraper::Html;
 use serde::Deserialize;
 
-use crate::execution::ports::news_fetcher::{FetchNewsArticle, FetchNewsHandler, FetchNewsOutput, NewsFetcher};
+use super::page::{extract_meta_image, validate_image_url};
+use crate::execution::ports::news_fetcher::{
+    FetchNewsArticle, FetchNewsHandler, FetchNewsImage, FetchNewsOutput, NewsFetcher,
+};
 
 // Doc: https://newsdata.io/documentation/#http_response
 #[derive(Deserialize)]
 struct NewsResponse {
     #[serde(rename = "totalResults")]
     total_results: u16,
-    results: Vec<NewsResponseResult>,
+    results: Vec<serde_json::Value>, // Kept as is to be archived along with the parsed fields
     #[serde(rename = "nextPage")]
     next_page: Option<String>,
 }
@@ -33,6 +37,8 @@ struct NewsResponseResult {
     pub_date_tz: Option<String>,
 }
 
+const SOURCE_NAME: &str = "NewsData";
+
 pub(crate) struct NewsdataClient {
     api_key: String,
     pages_num_limit: Option<u16>,
@@ -56,30 +62,44 @@ impl NewsdataClient {
         let news_response: NewsResponse = serde_json::from_str(&response_text)
             .with_context(|| format!("Deserializing the response text: {}", response_text))?;
         let mut articles = vec![];
-        for result in news_response.results.into_iter() {
+        for raw_result in news_response.results.into_iter() {
+            let raw_feed = Some(raw_result.to_string());
+            let result: NewsResponseResult = serde_json::from_value(raw_result)?;
             let published_time = match (result.pub_date, result.pub_date_tz) {
                 (Some(pub_date), Some(pub_date_tz)) => {
                     // TODO: Handle errors instead of returning early
                     let timezone: Tz = pub_date_tz.parse()?;
-                    match NaiveDateTime::parse_from_str(&pub_date, "%Y-%m-%d %H:%M:%S")?
+                    NaiveDateTime::parse_from_str(&pub_date, "%Y-%m-%d %H:%M:%S")?
                         .and_local_timezone(timezone)
                         .single()
-                    {
-                        Some(published_time) => Some(published_time.to_utc().into()),
-                        None => None,
-                    }
+                        .map(|published_time| published_time.to_utc().into())
                 }
                 _ => None,
             };
+            let images: Vec<FetchNewsImage> = result
+                .image_url
+                .and_then(|u| validate_image_url(None, &u))
+                .into_iter()
+                .map(|url| FetchNewsImage {
+                    url,
+                    caption: None,
+                    alt_text: None,
+                })
+                .collect();
+            // Fall back to the image advertised by the article page, which is scraped along with the other tasks
+            let scrape_images = images.is_empty() && result.link.is_some();
             articles.push(FetchNewsArticle {
-                source_name: "NewsData".to_string(),
+                source_name: SOURCE_NAME.to_string(),
                 id: Some(result.article_id),
                 link: result.link,
                 title: result.title,
                 short_text: result.description,
                 long_text: None, // `content` field is only available in paid plans
-                image_url: result.image_url,
+                images,
+                scrape_images,
                 published_time,
+                raw_html: None, // Article pages are only fetched when looking for images
+                raw_feed,
             });
         }
         Ok((news_response.total_results, articles, news_response.next_page))
@@ -88,6 +108,10 @@ impl NewsdataClient {
 
 #[async_trait]
 impl NewsFetcher for NewsdataClient {
+    fn source_name(&self) -> String {
+        SOURCE_NAME.to_string()
+    }
+
     async fn fetch_news(self: Arc<Self>, handler: FetchNewsHandler) -> Vec<FetchNewsOutput> {
         let mut outputs = vec![];
         let mut remaining_results_num = None;
@@ -135,4 +159,11 @@ impl NewsFetcher for NewsdataClient {
         }
         outputs
     }
+
+    async fn fetch_images(&self, link: &str) -> Result<Vec<FetchNewsImage>> {
+        // Article pages come from many different publishers, so only meta tags can be relied on
+        let base_url = Url::parse(link)?;
+        let document_html = Html::parse_document(&Client::new().get(link).send().await?.text().await?);
+        Ok(extract_meta_image(&document_html, &base_url).into_iter().collect())
+    }
 }
@@ -0,0 +1,94 @@
+use reqwest::Url;
+use scraper::{Html, Selector};
+
+use crate::execution::ports::news_fetcher::FetchNewsImage;
+
+// Placeholders used by providers when an article has no image of its own
+const PLACEHOLDER_IMAGE_PATTERNS: [&str; 1] = ["default.jpg"];
+
+// Resolves the URL against the page it was found on, and checks that it is worth downloading
+pub(super) fn validate_image_url(base_url: Option<&Url>, raw_url: &str) -> Option<String> {
+    let raw_url = raw_url.trim();
+    let url = match base_url {
+        Some(base_url) => base_url.join(raw_url).ok()?,
+        None => Url::parse(raw_url).ok()?,
+    };
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    if PLACEHOLDER_IMAGE_PATTERNS.iter().any(|p| url.path().ends_with(p)) {
+        return None;
+    }
+    Some(url.to_string())
+}
+
+// Image advertised by the page for link previews, from `og:image` then `twitter:image`
+pub(super) fn extract_meta_image(document_html: &Html, base_url: &Url) -> Option<FetchNewsImage> {
+    for (url_selector, alt_selector) in [
+        (r#"meta[property="og:image"]"#, r#"meta[property="og:image:alt"]"#),
+        (r#"meta[name="twitter:image"]"#, r#"meta[name="twitter:image:alt"]"#),
+    ] {
+        let Some(url) = select_meta_content(document_html, url_selector) else {
+            continue;
+        };
+        let Some(url) = validate_image_url(Some(base_url), &url) else {
+            continue;
+        };
+        return Some(FetchNewsImage {
+            url,
+            caption: None,
+            alt_text: select_meta_content(document_html, alt_selector),
+        });
+    }
+    None
+}
+
+fn select_meta_content(document_html: &Html, selector: &str) -> Option<String> {
+    let selector = Selector::parse(selector).ok()?;
+    document_html
+        .select(&selector)
+        .filter_map(|e| e.attr("content"))
+        .map(|c| c.trim().to_string())
+        .find(|c| !c.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Url;
+    use scraper::Html;
+
+    use super::{extract_meta_image, validate_image_url};
+
+    #[test]
+    fn find_page_images() {
+        let base_url = Url::parse("https://example.com/news/1.html").unwrap();
+        assert_eq!(
+            validate_image_url(Some(&base_url), " ../img/a.jpg "),
+            Some("https://example.com/img/a.jpg".to_string())
+        );
+        assert_eq!(validate_image_url(None, "/img/a.jpg"), None);
+        assert_eq!(validate_image_url(Some(&base_url), "data:image/png;base64,AAAA"), None);
+        assert_eq!(validate_image_url(Some(&base_url), "/common/default.jpg"), None);
+        let document_html = Html::parse_document(
+            r#"<html><head>
+                <meta property="og:image" content=" ">
+                <meta property="og:image" content="/common/default.jpg">
+                <meta name="twitter:image" content="/img/card.png">
+                <meta name="twitter:image:alt" content=" 記者会見 ">
+            </head></html>"#,
+        );
+        let image = extract_meta_image(&document_html, &base_url).unwrap();
+        assert_eq!(
+            (image.url.as_str(), image.caption, image.alt_text.as_deref()),
+            ("https://example.com/img/card.png", None, Some("記者会見"))
+        );
+        let document_html =
+            Html::parse_document(r#"<meta property="og:image" content="https://cdn.example.com/og.jpg">"#);
+        let image = extract_meta_image(&document_html, &base_url).unwrap();
+        assert_eq!(
+            (image.url.as_str(), image.alt_text),
+            ("https://cdn.example.com/og.jpg", None)
+        );
+        assert!(extract_meta_image(&Html::parse_document("<p>本文</p>"), &base_url).is_none());
+    }
+}
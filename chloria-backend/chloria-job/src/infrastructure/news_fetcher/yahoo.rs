@@ -1,17 +1,20 @@
-use std::{sync::Arc, usize::MAX};
+use std::sync::Arc;
 
-use anyhow::{bail, Error, Result};
+use anyhow::{Error, Result};
 use async_trait::async_trait;
 use chrono::{offset::LocalResult, DateTime, Datelike, Local, TimeZone, Timelike};
-use html2text::render::RichDecorator;
+use futures::{stream, StreamExt};
 use log::{error, info};
 use regex::Regex;
-use reqwest::Client;
+use reqwest::{Client, Url};
 use scraper::{Html, Selector};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tokio::sync::{mpsc, Semaphore};
 
-use crate::execution::ports::news_fetcher::{FetchNewsArticle, FetchNewsHandler, FetchNewsOutput, NewsFetcher};
+use super::page::{extract_meta_image, validate_image_url};
+use crate::execution::ports::news_fetcher::{
+    FetchNewsArticle, FetchNewsHandler, FetchNewsImage, FetchNewsOutput, NewsFetcher,
+};
 
 // Doc: https://news.yahoo.co.jp/rss
 #[derive(Deserialize)]
@@ -26,7 +29,7 @@ struct NewsResponseChannel {
     items: Vec<NewsResponseChannelItem>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 struct NewsResponseChannelItem {
     title: Option<String>,
     link: Option<String>,
@@ -36,6 +39,38 @@ struct NewsResponseChannelItem {
     description: Option<String>,
 }
 
+const SOURCE_NAME: &str = "Yahoo";
+
+struct PageSelectors {
+    figure: Selector,
+    gallery_figure: Selector,
+    gallery_link: Selector,
+    image: Selector,
+    caption: Selector,
+    gallery_path_regex: Regex,
+}
+
+impl PageSelectors {
+    fn new() -> Self {
+        Self {
+            figure: Selector::parse("article#uamods figure").unwrap(),
+            gallery_figure: Selector::parse("figure").unwrap(),
+            gallery_link: Selector::parse("article#uamods a[href]").unwrap(),
+            image: Selector::parse("img").unwrap(),
+            caption: Selector::parse("figcaption").unwrap(),
+            // Articles with several images only show the first one, the others are on pages of their own
+            gallery_path_regex: Regex::new(r"^/articles/[^/]+/images/\d+$").unwrap(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Page {
+    raw_html: Option<String>, // Source document of the page, from which the text is extracted later
+    images: Vec<FetchNewsImage>, // Images found in `<figure>` elements, in order of appearance
+    meta_image: Option<FetchNewsImage>, // Image advertised by meta tags, used when there is no other image
+}
+
 pub(crate) struct YahooClient {
     providers: Vec<String>,
     interval: i64,
@@ -752,12 +787,14 @@ impl YahooClient {
     async fn fetch_provider(self: Arc<Self>, provider: &str) -> Result<Vec<FetchNewsArticle>> {
         let mut articles = vec![];
         let url = format!("https://news.yahoo.co.jp/rss/media/{}/all.xml", provider);
-        let response_text = Client::new().get(url).send().await?.text().await?;
+        // Shared by every page of the provider, to reuse its connections
+        let client = Client::new();
+        let response_text = client.get(url).send().await?.text().await?;
         let response: NewsResponse = serde_xml_rs::from_str(&response_text)?;
         // Links follow the format: `https://news.yahoo.co.jp/articles/${ID}?source=rss`
         // or `https://news.yahoo.co.jp/articles/${ID}/images/${IMAGE_INDEX}?source=rss`
         let link_regex = Regex::new(r"https://news.yahoo.co.jp/articles/(?<id>[^/?]+)")?;
-        let content_selector = Selector::parse("article#uamods .article_body").unwrap();
+        let page_selectors = PageSelectors::new();
         let now = Local::now();
         // We prevent fetching news multiple times by checking if the published time is older than `fetch_time`
         // adjusted by `self.interval` (see the `for` loop below for details).
@@ -778,6 +815,7 @@ impl YahooClient {
             _ => now, // Fallback in case of an error when rounding down
         };
         for item in response.channel.items.into_iter() {
+            let raw_feed = serde_json::to_string(&item).ok();
             let published_time = match item.pub_date {
                 Some(pub_date) => match DateTime::parse_from_rfc2822(&pub_date) {
                     Ok(published_time) => {
@@ -791,45 +829,180 @@ impl YahooClient {
                 },
                 None => None,
             };
-            let (id, content, link) = match item.link {
+            let (id, page, link) = match item.link {
                 Some(link) => match link_regex.captures(&link) {
                     Some(captures) => (
                         Some(captures["id"].to_string()),
-                        Self::extract_content(&content_selector, &link).await.ok(),
+                        Self::extract_page(&client, &page_selectors, &link).await.ok(),
                         Some(link),
                     ),
                     None => (None, None, None),
                 },
                 None => (None, None, None),
             };
+            let page = page.unwrap_or_default();
+            // The image from the feed is the representative one, followed by the ones found in the page
+            let mut images: Vec<FetchNewsImage> = item
+                .image
+                .and_then(|u| validate_image_url(None, &u))
+                .into_iter()
+                .map(|url| FetchNewsImage {
+                    url,
+                    caption: None,
+                    alt_text: None,
+                })
+                .collect();
+            for page_image in page.images {
+                match images.iter_mut().find(|i| i.url == page_image.url) {
+                    // Keep the position of the feed image but take the caption from the page
+                    Some(image) => *image = page_image,
+                    None => images.push(page_image),
+                }
+            }
+            if images.is_empty() {
+                images.extend(page.meta_image);
+            }
             articles.push(FetchNewsArticle {
-                source_name: "Yahoo".to_string(),
+                source_name: SOURCE_NAME.to_string(),
                 id,
                 link,
                 title: item.title,
                 short_text: item.description,
-                long_text: content,
-                image_url: item.image.filter(|u| !u.contains("default.jpg")),
+                long_text: None, // Extracted from `raw_html`
+                images,
+                scrape_images: false, // The page has already been scraped
                 published_time,
+                raw_html: page.raw_html,
+                raw_feed,
             });
         }
         Ok(articles)
     }
 
-    async fn extract_content(content_selector: &Selector, link: &str) -> Result<String> {
-        let document_html = Html::parse_document(&Client::new().get(link).send().await?.text().await?);
-        let Some(content_element) = document_html.select(content_selector).next() else {
-            bail!("No content found at {}.", link);
+    async fn extract_page(client: &Client, page_selectors: &PageSelectors, link: &str) -> Result<Page> {
+        const MAX_GALLERY_PAGES_NUM: usize = 20;
+        const GALLERY_PAGE_PERMITS_NUM: usize = 5;
+        let base_url = Url::parse(link)?;
+        let raw_html = client.get(link).send().await?.text().await?;
+        let (mut images, gallery_links, meta_image) = {
+            // `Html` is not `Send`, so it must be dropped before awaiting
+            let document_html = Html::parse_document(&raw_html);
+            (
+                Self::select_figure_images(page_selectors, &document_html, &page_selectors.figure, &base_url),
+                Self::select_gallery_links(page_selectors, &document_html, &base_url),
+                extract_meta_image(&document_html, &base_url),
+            )
         };
-        let content_html = content_element.html();
-        let content = html2text::from_read_with_decorator(content_html.as_bytes(), MAX, RichDecorator::new())?;
-        // TODO: Extract all content if there are multiple pages
-        Ok(content)
+        // Fetched a few at a time, but kept in the order of the gallery
+        let gallery_pages: Vec<_> = stream::iter(gallery_links.into_iter().take(MAX_GALLERY_PAGES_NUM))
+            .map(|gallery_link| async move {
+                let gallery_image = Self::extract_gallery_page(client, page_selectors, &gallery_link).await;
+                (gallery_link, gallery_image)
+            })
+            .buffered(GALLERY_PAGE_PERMITS_NUM)
+            .collect()
+            .await;
+        for (gallery_link, gallery_image) in gallery_pages {
+            let gallery_image = match gallery_image {
+                Ok(gallery_image) => gallery_image,
+                Err(error) => {
+                    error!("gallery_link={}, error={}", gallery_link, error);
+                    continue;
+                }
+            };
+            if let Some(gallery_image) = gallery_image.filter(|g| images.iter().all(|i| i.url != g.url)) {
+                images.push(gallery_image);
+            }
+        }
+        Ok(Page {
+            raw_html: Some(raw_html),
+            images,
+            meta_image,
+        })
+    }
+
+    // Each gallery page shows a single image, in a figure if it has a caption
+    async fn extract_gallery_page(
+        client: &Client,
+        page_selectors: &PageSelectors,
+        link: &str,
+    ) -> Result<Option<FetchNewsImage>> {
+        let base_url = Url::parse(link)?;
+        let raw_html = client.get(link).send().await?.text().await?;
+        let document_html = Html::parse_document(&raw_html);
+        let gallery_image = Self::select_figure_images(
+            page_selectors,
+            &document_html,
+            &page_selectors.gallery_figure,
+            &base_url,
+        )
+        .into_iter()
+        .next()
+        .or_else(|| extract_meta_image(&document_html, &base_url));
+        Ok(gallery_image)
+    }
+
+    // The first image of each figure with its caption, in order of appearance and without duplicates
+    fn select_figure_images(
+        page_selectors: &PageSelectors,
+        document_html: &Html,
+        figure_selector: &Selector,
+        base_url: &Url,
+    ) -> Vec<FetchNewsImage> {
+        let mut images: Vec<FetchNewsImage> = vec![];
+        for figure_element in document_html.select(figure_selector) {
+            let Some(image_element) = figure_element.select(&page_selectors.image).next() else {
+                continue;
+            };
+            let Some(url) = image_element
+                .attr("src")
+                .and_then(|u| validate_image_url(Some(base_url), u))
+            else {
+                continue;
+            };
+            if images.iter().any(|i| i.url == url) {
+                continue;
+            }
+            let caption = figure_element
+                .select(&page_selectors.caption)
+                .next()
+                .map(|e| e.text().collect::<String>().trim().to_string())
+                .filter(|c| !c.is_empty());
+            let alt_text = image_element
+                .attr("alt")
+                .map(|a| a.trim().to_string())
+                .filter(|a| !a.is_empty());
+            images.push(FetchNewsImage { url, caption, alt_text });
+        }
+        images
+    }
+
+    // Links to the gallery pages of the article, without their query so that each page is only listed once
+    fn select_gallery_links(page_selectors: &PageSelectors, document_html: &Html, base_url: &Url) -> Vec<String> {
+        let mut gallery_links: Vec<String> = vec![];
+        for link_element in document_html.select(&page_selectors.gallery_link) {
+            let Some(mut url) = link_element.attr("href").and_then(|h| base_url.join(h).ok()) else {
+                continue;
+            };
+            if url.host_str() != base_url.host_str() || !page_selectors.gallery_path_regex.is_match(url.path()) {
+                continue;
+            }
+            url.set_query(None);
+            url.set_fragment(None);
+            if !gallery_links.contains(&url.to_string()) {
+                gallery_links.push(url.to_string());
+            }
+        }
+        gallery_links
     }
 }
 
 #[async_trait]
 impl NewsFetcher for YahooClient {
+    fn source_name(&self) -> String {
+        SOURCE_NAME.to_string()
+    }
+
     async fn fetch_news(self: Arc<Self>, handler: FetchNewsHandler) -> Vec<FetchNewsOutput> {
         const CHANNEL_CAPACITY: usize = 100;
         let (sender, mut receiver) = mpsc::channel(CHANNEL_CAPACITY);
@@ -868,4 +1041,64 @@ impl NewsFetcher for YahooClient {
         let outputs = receiver_handle.await.unwrap_or(vec![]);
         outputs
     }
+
+    async fn fetch_images(&self, link: &str) -> Result<Vec<FetchNewsImage>> {
+        let page = Self::extract_page(&Client::new(), &PageSelectors::new(), link).await?;
+        match page.images.is_empty() {
+            true => Ok(page.meta_image.into_iter().collect()),
+            false => Ok(page.images),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Url;
+    use scraper::Html;
+
+    use super::{PageSelectors, YahooClient};
+
+    #[test]
+    fn select_page_images() {
+        let document_html = Html::parse_document(
+            r#"<html><body><article id="uamods">
+                <figure><img src="/img/a.jpg" alt=" 会見の様子 "><figcaption> 会見する知事 </figcaption></figure>
+                <figure><img src="https://example.com/img/a.jpg"></figure>
+                <figure><img src="/img/default.jpg"><figcaption>代替画像</figcaption></figure>
+                <figure><img src="/img/b.png" alt=""></figure>
+                <a href="/articles/abc123/images/001?source=rss">写真を見る</a>
+                <a href="https://news.yahoo.co.jp/articles/abc123/images/001">1/2</a>
+                <a href="/articles/abc123/images/002#top">2/2</a>
+                <a href="/articles/abc123/comments">コメント</a>
+                <a href="https://example.com/articles/abc123/images/003">外部</a>
+            </article><figure><img src="/img/outside.jpg"></figure></body></html>"#,
+        );
+        let page_selectors = PageSelectors::new();
+        let base_url = Url::parse("https://news.yahoo.co.jp/articles/abc123?source=rss").unwrap();
+        let images =
+            YahooClient::select_figure_images(&page_selectors, &document_html, &page_selectors.figure, &base_url);
+        let images: Vec<_> = images
+            .iter()
+            .map(|i| (i.url.as_str(), i.caption.as_deref(), i.alt_text.as_deref()))
+            .collect();
+        assert_eq!(
+            images,
+            [
+                (
+                    "https://news.yahoo.co.jp/img/a.jpg",
+                    Some("会見する知事"),
+                    Some("会見の様子")
+                ),
+                ("https://example.com/img/a.jpg", None, None),
+                ("https://news.yahoo.co.jp/img/b.png", None, None),
+            ]
+        );
+        assert_eq!(
+            YahooClient::select_gallery_links(&page_selectors, &document_html, &base_url),
+            [
+                "https://news.yahoo.co.jp/articles/abc123/images/001",
+                "https://news.yahoo.co.jp/articles/abc123/images/002",
+            ]
+        );
+    }
 }
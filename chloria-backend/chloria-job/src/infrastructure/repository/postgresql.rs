@@ -1,7 +1,11 @@
+use std::collections::{BTreeMap, HashMap};
+
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Local};
 use diesel::{
+    dsl::{exists, not},
+    pg::Pg,
     prelude::*,
     r2d2::{ConnectionManager, Pool},
     PgConnection,
@@ -9,10 +13,32 @@ use diesel::{
 use log::info;
 
 use crate::{
-    execution::ports::repository::{InsertNewsInput, Repository},
-    schema::news::{self, article_id},
+    domain::{
+        annotation::TextAnnotation, document::DocumentEntity, fingerprint::SimHash, split::Split,
+        statistics::CharacterStatistics,
+    },
+    execution::ports::repository::{
+        DeleteExpiredNewsInput, DeleteExpiredNewsOutput, InsertNewsImageInput, InsertNewsInput, InsertNewsOutput,
+        InsertNewsSentenceInput, InsertNewsSplitInput, InsertSyntheticSampleInput, NewsFileKind, PinDatasetInput,
+        Repository, SelectCharacterCountsInput, SelectCharacterCountsOutput, SelectExportSamplesInput,
+        SelectFileReferenceOutput, SelectFileReferencesInput, SelectInsightImageOutput, SelectNewsImagePathOutput,
+        SelectNewsImagePathsInput, SelectNewsLinkOutput, SelectNewsLinksInput, SelectNewsRawHtmlPathOutput,
+        SelectNewsRawHtmlPathsInput, SelectSentenceOutput, SelectSentencesInput, SelectSyntheticSampleOutput,
+        SelectUnassignedNewsInput, SelectUnassignedNewsOutput, UpdateNewsImageMetadataInput, UpdateNewsTextInput,
+    },
+    schema::{
+        dataset_news, datasets,
+        news::{self, article_id, source_name},
+        news_characters, news_images, news_insights, news_rubies, news_sentences, news_splits, news_statistics,
+        synthetic_samples,
+    },
 };
 
+// Rows of news characters have 3 bind parameters each, and PostgreSQL allows up to 65535 parameters per statement
+const CHARACTER_INSERT_CHUNK_SIZE: usize = 10000;
+// Rows of news sentences have 6 bind parameters each
+const SENTENCE_INSERT_CHUNK_SIZE: usize = 5000;
+
 pub(crate) struct PostgresqlClient {
     pool: Pool<ConnectionManager<PgConnection>>,
 }
@@ -24,6 +50,179 @@ impl PostgresqlClient {
             .build(ConnectionManager::<PgConnection>::new(database_url))?;
         Ok(Self { pool })
     }
+
+    // Sentences at the same place with the same text keep their rows, the others are deleted or inserted
+    fn replace_news_sentences(
+        connection: &mut PgConnection,
+        news_id: i32,
+        values: Vec<InsertNewsSentenceValue>,
+    ) -> QueryResult<()> {
+        let existing_sentences = news_sentences::table
+            .filter(news_sentences::news_id.eq(news_id))
+            .select((
+                news_sentences::id,
+                news_sentences::start_offset,
+                news_sentences::length,
+                news_sentences::text,
+            ))
+            .get_results::<(i32, i32, i32, String)>(connection)?;
+        let mut kept_sentence_ids = vec![];
+        let mut new_values = vec![];
+        for value in values {
+            let existing_sentence = existing_sentences.iter().find(|(_, start_offset, length, text)| {
+                (*start_offset, *length, text) == (value.start_offset, value.length, &value.text)
+            });
+            match existing_sentence {
+                Some((sentence_id, ..)) => {
+                    diesel::update(news_sentences::table.find(sentence_id))
+                        .set((
+                            news_sentences::difficulty.eq(value.difficulty),
+                            news_sentences::max_kanji_grade.eq(value.max_kanji_grade),
+                            news_sentences::updated_at.eq(Local::now()),
+                        ))
+                        .execute(connection)?;
+                    kept_sentence_ids.push(*sentence_id);
+                }
+                None => new_values.push(value),
+            }
+        }
+        // Deleted first, since new sentences may start where old ones did
+        diesel::delete(news_sentences::table)
+            .filter(news_sentences::news_id.eq(news_id))
+            .filter(news_sentences::id.ne_all(&kept_sentence_ids))
+            .execute(connection)?;
+        for values in new_values.chunks(SENTENCE_INSERT_CHUNK_SIZE) {
+            diesel::insert_into(news_sentences::table)
+                .values(values)
+                .execute(connection)?;
+        }
+        Ok(())
+    }
+
+    fn select_expired_news(input: &DeleteExpiredNewsInput) -> news::BoxedQuery<'static, Pg> {
+        let pinned_news_ids = dataset_news::table
+            .inner_join(datasets::table)
+            .filter(datasets::pinned.eq(true))
+            .select(dataset_news::news_id);
+        let mut query = news::table
+            .filter(news::created_at.lt(input.created_before))
+            .filter(not(news::id.eq_any(news_insights::table.select(news_insights::id))))
+            .filter(not(news::id.eq_any(pinned_news_ids)))
+            .into_boxed();
+        if let Some(source_name_value) = &input.source_name {
+            query = query.filter(source_name.eq(source_name_value.clone()));
+        }
+        query
+    }
+
+    fn select_counted_news(input: &SelectCharacterCountsInput) -> news::BoxedQuery<'static, Pg> {
+        let mut query = news::table.into_boxed();
+        if let Some(source_name_value) = &input.source_name {
+            query = query.filter(source_name.eq(source_name_value.clone()));
+        }
+        if let Some(created_from) = input.created_from {
+            query = query.filter(news::created_at.ge(created_from));
+        }
+        if let Some(created_to) = input.created_to {
+            query = query.filter(news::created_at.lt(created_to));
+        }
+        query
+    }
+
+    // Cluster of the closest near-duplicate inserted before the given news, if any
+    fn select_near_duplicate_cluster_id(
+        connection: &mut PgConnection,
+        news_id: i32,
+        simhash: SimHash,
+    ) -> QueryResult<Option<i32>> {
+        // Near-duplicates share at least one band
+        let [band_0, band_1, band_2, band_3] = simhash.bands().map(|b| b as i32);
+        let candidates = news::table
+            .filter(news::id.lt(news_id))
+            .filter(
+                news::simhash_band_0
+                    .eq(band_0)
+                    .or(news::simhash_band_1.eq(band_1))
+                    .or(news::simhash_band_2.eq(band_2))
+                    .or(news::simhash_band_3.eq(band_3)),
+            )
+            .select((news::id, news::simhash.assume_not_null(), news::cluster_id))
+            .get_results::<(i32, i64, Option<i32>)>(connection)?;
+        Ok(candidates
+            .into_iter()
+            .map(|(id, candidate_simhash, cluster_id)| (SimHash(candidate_simhash as u64), cluster_id.unwrap_or(id)))
+            .filter(|(candidate_simhash, _)| simhash.is_near_duplicate(candidate_simhash))
+            .min_by_key(|(candidate_simhash, cluster_id)| (simhash.distance(candidate_simhash), *cluster_id))
+            .map(|(_, cluster_id)| cluster_id))
+    }
+
+    // Paths of files of the given kinds referenced by the given news
+    fn select_news_file_paths(
+        connection: &mut PgConnection,
+        news_ids: &[i32],
+        kinds: &[NewsFileKind],
+    ) -> QueryResult<BTreeMap<String, NewsFileKind>> {
+        let mut paths = BTreeMap::new();
+        for (image_path, raw_html_path, raw_feed_path) in news::table
+            .filter(news::id.eq_any(news_ids))
+            .select((news::image_path, news::raw_html_path, news::raw_feed_path))
+            .get_results::<(Option<String>, Option<String>, Option<String>)>(connection)?
+        {
+            for (kind, path) in [
+                (NewsFileKind::Image, image_path),
+                (NewsFileKind::RawHtml, raw_html_path),
+                (NewsFileKind::RawFeed, raw_feed_path),
+            ] {
+                if let (true, Some(path)) = (kinds.contains(&kind), path) {
+                    paths.insert(path, kind);
+                }
+            }
+        }
+        if kinds.contains(&NewsFileKind::Image) {
+            for path in news_images::table
+                .filter(news_images::news_id.eq_any(news_ids))
+                .select(news_images::path)
+                .get_results::<String>(connection)?
+            {
+                paths.insert(path, NewsFileKind::Image);
+            }
+        }
+        Ok(paths)
+    }
+
+    // Content-addressed files can be shared by several news, so only files which are not referenced anymore are returned
+    fn filter_unreferenced_paths(
+        connection: &mut PgConnection,
+        mut paths: BTreeMap<String, NewsFileKind>,
+    ) -> QueryResult<Vec<(NewsFileKind, String)>> {
+        let path_values: Vec<&String> = paths.keys().collect();
+        let mut referenced_paths: Vec<String> = news::table
+            .filter(news::image_path.eq_any(&path_values))
+            .select(news::image_path.assume_not_null())
+            .get_results(connection)?;
+        referenced_paths.extend(
+            news::table
+                .filter(news::raw_html_path.eq_any(&path_values))
+                .select(news::raw_html_path.assume_not_null())
+                .get_results::<String>(connection)?,
+        );
+        referenced_paths.extend(
+            news::table
+                .filter(news::raw_feed_path.eq_any(&path_values))
+                .select(news::raw_feed_path.assume_not_null())
+                .get_results::<String>(connection)?,
+        );
+        referenced_paths.extend(
+            news_images::table
+                .filter(news_images::path.eq_any(&path_values))
+                .select(news_images::path)
+                .get_results::<String>(connection)?,
+        );
+        for path in referenced_paths {
+            paths.remove(&path);
+        }
+        Ok(paths.into_iter().map(|(path, kind)| (kind, path)).collect())
+    }
 }
 
 #[derive(Insertable)]
@@ -37,39 +236,1124 @@ struct InsertNewsValue {
     long_text: Option<String>,
     image_path: Option<String>,
     published_time: Option<DateTime<Local>>,
+    raw_html_path: Option<String>,
+    raw_feed_path: Option<String>,
+    document: Option<String>, // Serialized as JSON
+    extractor_version: Option<i32>,
+    original_title: Option<String>,
+    original_short_text: Option<String>,
+    original_long_text: Option<String>,
+    quality_reasons: Vec<String>,
+    simhash: Option<i64>,
+    simhash_band_0: Option<i32>,
+    simhash_band_1: Option<i32>,
+    simhash_band_2: Option<i32>,
+    simhash_band_3: Option<i32>,
+    difficulty: Option<f32>,
+    max_kanji_grade: Option<i32>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = news_images)]
+struct InsertNewsImageValue {
+    news_id: i32,
+    position: i32,
+    caption: Option<String>,
+    alt_text: Option<String>,
+    path: String,
+    width: i32,
+    height: i32,
+    mime_type: String,
+    byte_size: i64,
+    sha256: String,
+    perceptual_hash: String,
+}
+
+impl InsertNewsImageValue {
+    fn new(news_id: i32, input: InsertNewsImageInput) -> Self {
+        Self {
+            news_id,
+            position: input.position,
+            caption: input.caption,
+            alt_text: input.alt_text,
+            path: input.path,
+            width: input.width as i32,
+            height: input.height as i32,
+            mime_type: input.mime_type,
+            byte_size: input.byte_size as i64,
+            sha256: input.sha256,
+            perceptual_hash: input.perceptual_hash,
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = news_rubies)]
+struct InsertNewsRubyValue {
+    news_id: i32,
+    start_offset: i32,
+    end_offset: i32,
+    reading: String,
+}
+
+impl InsertNewsRubyValue {
+    // Offsets are counted from the start of `long_text`
+    fn new(news_id: i32, document: &DocumentEntity) -> Vec<Self> {
+        document
+            .plain_text_rubies()
+            .into_iter()
+            .map(|ruby| Self {
+                news_id,
+                start_offset: ruby.start as i32,
+                end_offset: ruby.end as i32,
+                reading: ruby.reading,
+            })
+            .collect()
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = news_sentences)]
+struct InsertNewsSentenceValue {
+    news_id: i32,
+    start_offset: i32,
+    length: i32,
+    text: String,
+    difficulty: Option<f32>,
+    max_kanji_grade: Option<i32>,
+}
+
+impl InsertNewsSentenceValue {
+    // Offsets are counted from the start of `long_text`
+    fn new(news_id: i32, inputs: Vec<InsertNewsSentenceInput>) -> Vec<Self> {
+        inputs
+            .into_iter()
+            .map(|input| Self {
+                news_id,
+                start_offset: input.sentence.start as i32,
+                length: input.sentence.length as i32,
+                text: input.sentence.text,
+                difficulty: input.difficulty.as_ref().map(|d| d.score),
+                max_kanji_grade: input.difficulty.and_then(|d| d.max_kanji_grade),
+            })
+            .collect()
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = synthetic_samples)]
+struct InsertSyntheticSampleValue {
+    news_sentence_id: Option<i32>,
+    news_id: Option<i32>,
+    text: String,
+    image_path: String,
+    width: i32,
+    height: i32,
+    render_params: String, // Serialized as JSON
+    seed: i64,
+    background_image_path: Option<String>,
+    annotation: String,    // Serialized as JSON
+    augmentations: String, // Serialized as JSON
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = news_splits)]
+struct InsertNewsSplitValue {
+    id: i32,
+    split: String,
+    group_key: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = news_statistics)]
+struct InsertNewsStatisticsValue {
+    id: i32,
+    character_count: i32,
+    kanji_count: i32,
+    hiragana_count: i32,
+    katakana_count: i32,
+    latin_count: i32,
+    digit_count: i32,
+    other_count: i32,
+    joyo_kanji_count: i32,
+    jis_level_1_count: i32,
+    jis_level_2_count: i32,
+    non_jis_count: i32,
+    rare_characters: String,
+}
+
+impl InsertNewsStatisticsValue {
+    fn new(news_id: i32, statistics: &CharacterStatistics) -> Self {
+        Self {
+            id: news_id,
+            character_count: statistics.character_count as i32,
+            kanji_count: statistics.kanji_count as i32,
+            hiragana_count: statistics.hiragana_count as i32,
+            katakana_count: statistics.katakana_count as i32,
+            latin_count: statistics.latin_count as i32,
+            digit_count: statistics.digit_count as i32,
+            other_count: statistics.other_count as i32,
+            joyo_kanji_count: statistics.joyo_kanji_count as i32,
+            jis_level_1_count: statistics.jis_level_1_count as i32,
+            jis_level_2_count: statistics.jis_level_2_count as i32,
+            non_jis_count: statistics.non_jis_count as i32,
+            rare_characters: statistics.rare_characters.clone(),
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = news_characters)]
+struct InsertNewsCharacterValue {
+    news_id: i32,
+    character: String,
+    count: i32,
+}
+
+impl InsertNewsCharacterValue {
+    fn new(news_id: i32, statistics: &CharacterStatistics) -> Vec<Self> {
+        statistics
+            .character_counts
+            .iter()
+            .map(|(c, count)| Self {
+                news_id,
+                character: c.to_string(),
+                count: *count as i32,
+            })
+            .collect()
+    }
 }
 
 #[async_trait]
 impl Repository for PostgresqlClient {
-    async fn insert_news(&self, inputs: Vec<InsertNewsInput>) -> Result<Vec<i32>> {
+    async fn insert_news(&self, inputs: Vec<InsertNewsInput>) -> Result<Vec<InsertNewsOutput>> {
         let total_article_ids: Vec<String> = inputs.iter().map(|i| i.article_id.clone()).collect();
+        let mut images = vec![];
+        let mut documents = vec![];
+        let mut simhashes = vec![];
+        let mut statistics = vec![];
+        let mut sentences = vec![];
         let values: Vec<InsertNewsValue> = inputs
             .into_iter()
-            .map(|input| InsertNewsValue {
-                source_name: input.source_name,
-                article_id: input.article_id,
-                link: input.link,
-                title: input.title,
-                short_text: input.short_text,
-                long_text: input.long_text,
-                image_path: input.image_path,
-                published_time: input.published_time,
+            .map(|input| {
+                let image_path = input.images.first().map(|i| i.path.clone());
+                for image in input.images {
+                    images.push(((input.source_name.clone(), input.article_id.clone()), image));
+                }
+                if let Some(document) = &input.document {
+                    documents.push(((input.source_name.clone(), input.article_id.clone()), document.clone()));
+                }
+                if let Some(simhash) = input.simhash {
+                    simhashes.push(((input.source_name.clone(), input.article_id.clone()), simhash));
+                }
+                if let Some(s) = input.statistics {
+                    statistics.push(((input.source_name.clone(), input.article_id.clone()), s));
+                }
+                sentences.push(((input.source_name.clone(), input.article_id.clone()), input.sentences));
+                let bands = input.simhash.map(|s| s.bands().map(|b| b as i32));
+                InsertNewsValue {
+                    source_name: input.source_name,
+                    article_id: input.article_id,
+                    link: input.link,
+                    title: input.title,
+                    short_text: input.short_text,
+                    long_text: input.long_text,
+                    image_path,
+                    published_time: input.published_time,
+                    raw_html_path: input.raw_html_path,
+                    raw_feed_path: input.raw_feed_path,
+                    document: input.document.and_then(|d| serde_json::to_string(&d).ok()),
+                    extractor_version: input.extractor_version,
+                    original_title: input.original_title,
+                    original_short_text: input.original_short_text,
+                    original_long_text: input.original_long_text,
+                    quality_reasons: input.quality_reasons,
+                    simhash: input.simhash.map(|s| s.0 as i64),
+                    simhash_band_0: bands.map(|b| b[0]),
+                    simhash_band_1: bands.map(|b| b[1]),
+                    simhash_band_2: bands.map(|b| b[2]),
+                    simhash_band_3: bands.map(|b| b[3]),
+                    difficulty: input.difficulty.as_ref().map(|d| d.score),
+                    max_kanji_grade: input.difficulty.and_then(|d| d.max_kanji_grade),
+                }
             })
             .collect();
-        let (inserted_news_ids, inserted_article_ids): (Vec<i32>, Vec<String>) = diesel::insert_into(news::table)
-            .values(&values)
-            .on_conflict_do_nothing()
-            .returning((news::id, article_id))
-            .get_results::<(i32, String)>(&mut self.pool.get()?)?
-            .into_iter()
-            .unzip();
+        let inserted_news = self.pool.get()?.transaction(|connection| {
+            let inserted_news = diesel::insert_into(news::table)
+                .values(&values)
+                .on_conflict_do_nothing()
+                .returning((news::id, source_name, article_id))
+                .get_results::<(i32, String, String)>(connection)?;
+            // Image metadata is only saved for the news inserted just now
+            let image_values: Vec<InsertNewsImageValue> = images
+                .into_iter()
+                .filter_map(|(key, image)| {
+                    inserted_news
+                        .iter()
+                        .find(|(_, s, a)| (s, a) == (&key.0, &key.1))
+                        .map(|(news_id, _, _)| InsertNewsImageValue::new(*news_id, image))
+                })
+                .collect();
+            diesel::insert_into(news_images::table)
+                .values(&image_values)
+                .on_conflict_do_nothing()
+                .execute(connection)?;
+            let ruby_values: Vec<InsertNewsRubyValue> = documents
+                .iter()
+                .filter_map(|(key, document)| {
+                    inserted_news
+                        .iter()
+                        .find(|(_, s, a)| (s, a) == (&key.0, &key.1))
+                        .map(|(news_id, _, _)| InsertNewsRubyValue::new(*news_id, document))
+                })
+                .flatten()
+                .collect();
+            diesel::insert_into(news_rubies::table)
+                .values(&ruby_values)
+                .on_conflict_do_nothing()
+                .execute(connection)?;
+            let mut statistics_values = vec![];
+            let mut character_values = vec![];
+            for (key, s) in &statistics {
+                if let Some((news_id, _, _)) = inserted_news.iter().find(|(_, s, a)| (s, a) == (&key.0, &key.1)) {
+                    statistics_values.push(InsertNewsStatisticsValue::new(*news_id, s));
+                    character_values.extend(InsertNewsCharacterValue::new(*news_id, s));
+                }
+            }
+            diesel::insert_into(news_statistics::table)
+                .values(&statistics_values)
+                .on_conflict_do_nothing()
+                .execute(connection)?;
+            // Inserted in chunks to stay below the limit of bind parameters
+            for values in character_values.chunks(CHARACTER_INSERT_CHUNK_SIZE) {
+                diesel::insert_into(news_characters::table)
+                    .values(values)
+                    .on_conflict_do_nothing()
+                    .execute(connection)?;
+            }
+            let sentence_values: Vec<InsertNewsSentenceValue> = sentences
+                .into_iter()
+                .filter_map(|(key, sentences)| {
+                    inserted_news
+                        .iter()
+                        .find(|(_, s, a)| (s, a) == (&key.0, &key.1))
+                        .map(|(news_id, _, _)| InsertNewsSentenceValue::new(*news_id, sentences))
+                })
+                .flatten()
+                .collect();
+            for values in sentence_values.chunks(SENTENCE_INSERT_CHUNK_SIZE) {
+                diesel::insert_into(news_sentences::table)
+                    .values(values)
+                    .on_conflict_do_nothing()
+                    .execute(connection)?;
+            }
+            // News are clustered in the order of insertion, so that each cluster is named after its first news
+            let mut clustered_news: Vec<(i32, SimHash)> = simhashes
+                .iter()
+                .filter_map(|(key, simhash)| {
+                    inserted_news
+                        .iter()
+                        .find(|(_, s, a)| (s, a) == (&key.0, &key.1))
+                        .map(|(news_id, _, _)| (*news_id, *simhash))
+                })
+                .collect();
+            clustered_news.sort_by_key(|(news_id, _)| *news_id);
+            for (news_id, simhash) in clustered_news {
+                let cluster_id =
+                    Self::select_near_duplicate_cluster_id(connection, news_id, simhash)?.unwrap_or(news_id);
+                diesel::update(news::table.find(news_id))
+                    .set(news::cluster_id.eq(cluster_id))
+                    .execute(connection)?;
+            }
+            QueryResult::Ok(inserted_news)
+        })?;
         let ignored_article_ids: Vec<String> = total_article_ids
             .into_iter()
-            .filter(|i| !inserted_article_ids.contains(i))
+            .filter(|i| !inserted_news.iter().any(|(_, _, a)| a == i))
             .collect();
-        if ignored_article_ids.len() > 0 {
+        if !ignored_article_ids.is_empty() {
             info!("ignored_article_ids={:?}", ignored_article_ids);
         }
-        Ok(inserted_news_ids)
+        let outputs = inserted_news
+            .into_iter()
+            .map(|(_, source_name_value, article_id_value)| InsertNewsOutput {
+                source_name: source_name_value,
+                article_id: article_id_value,
+            })
+            .collect();
+        Ok(outputs)
+    }
+
+    async fn insert_news_images(&self, news_id: i32, inputs: Vec<InsertNewsImageInput>) -> Result<()> {
+        let Some(first_image_path) = inputs.first().map(|i| i.path.clone()) else {
+            return Ok(());
+        };
+        let values: Vec<InsertNewsImageValue> = inputs
+            .into_iter()
+            .map(|input| InsertNewsImageValue::new(news_id, input))
+            .collect();
+        self.pool.get()?.transaction(|connection| {
+            diesel::insert_into(news_images::table)
+                .values(&values)
+                .on_conflict_do_nothing()
+                .execute(connection)?;
+            diesel::update(news::table)
+                .filter(news::id.eq(news_id).and(news::image_path.is_null()))
+                .set((news::image_path.eq(first_image_path), news::updated_at.eq(Local::now())))
+                .execute(connection)?;
+            QueryResult::Ok(())
+        })?;
+        Ok(())
+    }
+
+    async fn select_news_image_paths(
+        &self,
+        input: SelectNewsImagePathsInput,
+    ) -> Result<Vec<SelectNewsImagePathOutput>> {
+        let outputs = news::table
+            .left_join(news_images::table.on(news_images::news_id.eq(news::id)))
+            .filter(news::id.gt(input.after_news_id))
+            .filter(news::image_path.is_not_null())
+            .filter(news_images::id.is_null())
+            .order(news::id)
+            .limit(input.limit)
+            .select((news::id, news::image_path.assume_not_null()))
+            .get_results::<(i32, String)>(&mut self.pool.get()?)?
+            .into_iter()
+            .map(|(news_id, image_path)| SelectNewsImagePathOutput { news_id, image_path })
+            .collect();
+        Ok(outputs)
+    }
+
+    async fn select_news_links(&self, input: SelectNewsLinksInput) -> Result<Vec<SelectNewsLinkOutput>> {
+        let outputs = news::table
+            .left_join(news_images::table.on(news_images::news_id.eq(news::id)))
+            .filter(news::id.gt(input.after_news_id))
+            .filter(news::link.is_not_null())
+            .filter(news::image_path.is_null())
+            .filter(news::images_expired_at.is_null())
+            .filter(news_images::id.is_null())
+            .order(news::id)
+            .limit(input.limit)
+            .select((news::id, source_name, article_id, news::link.assume_not_null()))
+            .get_results::<(i32, String, String, String)>(&mut self.pool.get()?)?
+            .into_iter()
+            .map(
+                |(news_id, source_name_value, article_id_value, link)| SelectNewsLinkOutput {
+                    news_id,
+                    source_name: source_name_value,
+                    article_id: article_id_value,
+                    link,
+                },
+            )
+            .collect();
+        Ok(outputs)
+    }
+
+    async fn select_file_references(&self, input: SelectFileReferencesInput) -> Result<Vec<SelectFileReferenceOutput>> {
+        // Wildcards of `LIKE` may appear in keys (e.g. `_` between an article ID and the image position)
+        let prefix = input.prefix.unwrap_or_default();
+        let pattern = format!(
+            "{}%",
+            prefix.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+        );
+        let connection = &mut self.pool.get()?;
+        let mut references = BTreeMap::new();
+        for path in news::table
+            .filter(news::image_path.like(&pattern))
+            .select(news::image_path.assume_not_null())
+            .distinct()
+            .get_results::<String>(connection)?
+        {
+            references.insert(path, None);
+        }
+        // Raw documents have no recorded size
+        for path in news::table
+            .filter(news::raw_html_path.like(&pattern))
+            .select(news::raw_html_path.assume_not_null())
+            .get_results::<String>(connection)?
+            .into_iter()
+            .chain(
+                news::table
+                    .filter(news::raw_feed_path.like(&pattern))
+                    .select(news::raw_feed_path.assume_not_null())
+                    .get_results::<String>(connection)?,
+            )
+        {
+            references.insert(path, None);
+        }
+        for (path, byte_size) in news_images::table
+            .filter(news_images::path.like(&pattern))
+            .select((news_images::path, news_images::byte_size))
+            .distinct()
+            .get_results::<(String, i64)>(connection)?
+        {
+            references.insert(path, Some(byte_size));
+        }
+        Ok(references
+            .into_iter()
+            .map(|(path, byte_size)| SelectFileReferenceOutput { path, byte_size })
+            .collect())
+    }
+
+    async fn delete_file_references(&self, paths: Vec<String>) -> Result<usize> {
+        let affected_rows_count = self.pool.get()?.transaction(|connection| {
+            let deleted_images_count = diesel::delete(news_images::table)
+                .filter(news_images::path.eq_any(&paths))
+                .execute(connection)?;
+            let updated_news_count = diesel::update(news::table)
+                .filter(news::image_path.eq_any(&paths))
+                .set((news::image_path.eq(None::<String>), news::updated_at.eq(Local::now())))
+                .execute(connection)?;
+            let updated_raw_html_count = diesel::update(news::table)
+                .filter(news::raw_html_path.eq_any(&paths))
+                .set((
+                    news::raw_html_path.eq(None::<String>),
+                    news::updated_at.eq(Local::now()),
+                ))
+                .execute(connection)?;
+            let updated_raw_feed_count = diesel::update(news::table)
+                .filter(news::raw_feed_path.eq_any(&paths))
+                .set((
+                    news::raw_feed_path.eq(None::<String>),
+                    news::updated_at.eq(Local::now()),
+                ))
+                .execute(connection)?;
+            QueryResult::Ok(deleted_images_count + updated_news_count + updated_raw_html_count + updated_raw_feed_count)
+        })?;
+        Ok(affected_rows_count)
+    }
+
+    async fn update_news_image_metadata(&self, input: UpdateNewsImageMetadataInput) -> Result<()> {
+        diesel::update(news_images::table)
+            .filter(news_images::path.eq(input.path))
+            .set((
+                news_images::width.eq(input.width as i32),
+                news_images::height.eq(input.height as i32),
+                news_images::mime_type.eq(input.mime_type),
+                news_images::byte_size.eq(input.byte_size as i64),
+                news_images::sha256.eq(input.sha256),
+                news_images::perceptual_hash.eq(input.perceptual_hash),
+                news_images::updated_at.eq(Local::now()),
+            ))
+            .execute(&mut self.pool.get()?)?;
+        Ok(())
+    }
+
+    async fn delete_expired_news(&self, input: DeleteExpiredNewsInput) -> Result<DeleteExpiredNewsOutput> {
+        let output = self.pool.get()?.transaction(|connection| {
+            let news_ids = Self::select_expired_news(&input)
+                .order(news::id)
+                .limit(input.limit)
+                .select(news::id)
+                .get_results::<i32>(connection)?;
+            let paths = Self::select_news_file_paths(
+                connection,
+                &news_ids,
+                &[NewsFileKind::Image, NewsFileKind::RawHtml, NewsFileKind::RawFeed],
+            )?;
+            diesel::delete(news_images::table)
+                .filter(news_images::news_id.eq_any(&news_ids))
+                .execute(connection)?;
+            diesel::delete(news_rubies::table)
+                .filter(news_rubies::news_id.eq_any(&news_ids))
+                .execute(connection)?;
+            diesel::delete(news_sentences::table)
+                .filter(news_sentences::news_id.eq_any(&news_ids))
+                .execute(connection)?;
+            diesel::delete(news_statistics::table)
+                .filter(news_statistics::id.eq_any(&news_ids))
+                .execute(connection)?;
+            diesel::delete(news_splits::table)
+                .filter(news_splits::id.eq_any(&news_ids))
+                .execute(connection)?;
+            diesel::delete(news_characters::table)
+                .filter(news_characters::news_id.eq_any(&news_ids))
+                .execute(connection)?;
+            // Memberships of datasets which are not pinned
+            diesel::delete(dataset_news::table)
+                .filter(dataset_news::news_id.eq_any(&news_ids))
+                .execute(connection)?;
+            let news_count = diesel::delete(news::table)
+                .filter(news::id.eq_any(&news_ids))
+                .execute(connection)?;
+            QueryResult::Ok(DeleteExpiredNewsOutput {
+                news_count,
+                unreferenced_paths: Self::filter_unreferenced_paths(connection, paths)?,
+            })
+        })?;
+        Ok(output)
+    }
+
+    async fn delete_expired_news_files(
+        &self,
+        input: DeleteExpiredNewsInput,
+        kind: NewsFileKind,
+    ) -> Result<DeleteExpiredNewsOutput> {
+        let output = self.pool.get()?.transaction(|connection| {
+            let query = Self::select_expired_news(&input);
+            let query = match kind {
+                NewsFileKind::Image => query.filter(
+                    news::image_path
+                        .is_not_null()
+                        .or(exists(news_images::table.filter(news_images::news_id.eq(news::id)))),
+                ),
+                NewsFileKind::RawHtml => query.filter(news::raw_html_path.is_not_null()),
+                NewsFileKind::RawFeed => query.filter(news::raw_feed_path.is_not_null()),
+            };
+            let news_ids = query
+                .order(news::id)
+                .limit(input.limit)
+                .select(news::id)
+                .get_results::<i32>(connection)?;
+            let paths = Self::select_news_file_paths(connection, &news_ids, &[kind])?;
+            let news_query = diesel::update(news::table).filter(news::id.eq_any(&news_ids));
+            let news_count = match kind {
+                NewsFileKind::Image => {
+                    diesel::delete(news_images::table)
+                        .filter(news_images::news_id.eq_any(&news_ids))
+                        .execute(connection)?;
+                    // Keep the news from being picked up again by the backfill of missing images
+                    news_query
+                        .set((
+                            news::image_path.eq(None::<String>),
+                            news::images_expired_at.eq(Some(Local::now())),
+                            news::updated_at.eq(Local::now()),
+                        ))
+                        .execute(connection)?
+                }
+                NewsFileKind::RawHtml => news_query
+                    .set((
+                        news::raw_html_path.eq(None::<String>),
+                        news::updated_at.eq(Local::now()),
+                    ))
+                    .execute(connection)?,
+                NewsFileKind::RawFeed => news_query
+                    .set((
+                        news::raw_feed_path.eq(None::<String>),
+                        news::updated_at.eq(Local::now()),
+                    ))
+                    .execute(connection)?,
+            };
+            QueryResult::Ok(DeleteExpiredNewsOutput {
+                news_count,
+                unreferenced_paths: Self::filter_unreferenced_paths(connection, paths)?,
+            })
+        })?;
+        Ok(output)
+    }
+
+    async fn select_news_raw_html_paths(
+        &self,
+        input: SelectNewsRawHtmlPathsInput,
+    ) -> Result<Vec<SelectNewsRawHtmlPathOutput>> {
+        let mut query = news::table
+            .filter(news::id.gt(input.after_news_id))
+            .filter(news::raw_html_path.is_not_null())
+            .filter(
+                news::extractor_version
+                    .is_null()
+                    .or(news::extractor_version.lt(input.extractor_version)),
+            )
+            .into_boxed();
+        if let Some(source_name_value) = input.source_name {
+            query = query.filter(source_name.eq(source_name_value));
+        }
+        if let Some(created_from) = input.created_from {
+            query = query.filter(news::created_at.ge(created_from));
+        }
+        if let Some(created_to) = input.created_to {
+            query = query.filter(news::created_at.lt(created_to));
+        }
+        let outputs = query
+            .order(news::id)
+            .limit(input.limit)
+            .select((news::id, source_name, news::raw_html_path.assume_not_null()))
+            .get_results::<(i32, String, String)>(&mut self.pool.get()?)?
+            .into_iter()
+            .map(
+                |(news_id, source_name_value, raw_html_path)| SelectNewsRawHtmlPathOutput {
+                    news_id,
+                    source_name: source_name_value,
+                    raw_html_path,
+                },
+            )
+            .collect();
+        Ok(outputs)
+    }
+
+    async fn update_news_text(&self, input: UpdateNewsTextInput) -> Result<()> {
+        let document = input.document.as_ref().map(serde_json::to_string).transpose()?;
+        let ruby_values = match &input.document {
+            Some(document) => InsertNewsRubyValue::new(input.news_id, document),
+            None => vec![],
+        };
+        let statistics_value = input
+            .statistics
+            .as_ref()
+            .map(|s| InsertNewsStatisticsValue::new(input.news_id, s));
+        let character_values = match &input.statistics {
+            Some(statistics) => InsertNewsCharacterValue::new(input.news_id, statistics),
+            None => vec![],
+        };
+        let sentence_values = InsertNewsSentenceValue::new(input.news_id, input.sentences);
+        self.pool.get()?.transaction(|connection| {
+            diesel::update(news::table)
+                .filter(news::id.eq(input.news_id))
+                .set((
+                    news::long_text.eq(input.long_text),
+                    news::document.eq(document),
+                    news::extractor_version.eq(input.extractor_version),
+                    news::original_long_text.eq(input.original_long_text),
+                    news::difficulty.eq(input.difficulty.as_ref().map(|d| d.score)),
+                    news::max_kanji_grade.eq(input.difficulty.as_ref().and_then(|d| d.max_kanji_grade)),
+                    news::updated_at.eq(Local::now()),
+                ))
+                .execute(connection)?;
+            // Readings are replaced since offsets may have changed along with the text
+            diesel::delete(news_rubies::table)
+                .filter(news_rubies::news_id.eq(input.news_id))
+                .execute(connection)?;
+            diesel::insert_into(news_rubies::table)
+                .values(&ruby_values)
+                .execute(connection)?;
+            // So are statistics
+            diesel::delete(news_statistics::table)
+                .filter(news_statistics::id.eq(input.news_id))
+                .execute(connection)?;
+            if let Some(statistics_value) = &statistics_value {
+                diesel::insert_into(news_statistics::table)
+                    .values(statistics_value)
+                    .execute(connection)?;
+            }
+            diesel::delete(news_characters::table)
+                .filter(news_characters::news_id.eq(input.news_id))
+                .execute(connection)?;
+            for values in character_values.chunks(CHARACTER_INSERT_CHUNK_SIZE) {
+                diesel::insert_into(news_characters::table)
+                    .values(values)
+                    .execute(connection)?;
+            }
+            // But sentences are only replaced where they have changed, since samples are rendered from them
+            Self::replace_news_sentences(connection, input.news_id, sentence_values)?;
+            QueryResult::Ok(())
+        })?;
+        Ok(())
+    }
+
+    async fn select_character_counts(&self, input: SelectCharacterCountsInput) -> Result<SelectCharacterCountsOutput> {
+        let connection = &mut self.pool.get()?;
+        let news_count = news_statistics::table
+            .filter(news_statistics::id.eq_any(Self::select_counted_news(&input).select(news::id)))
+            .count()
+            .get_result::<i64>(connection)?;
+        let character_counts = news_characters::table
+            .filter(news_characters::news_id.eq_any(Self::select_counted_news(&input).select(news::id)))
+            .group_by(news_characters::character)
+            .select((news_characters::character, diesel::dsl::sum(news_characters::count)))
+            .get_results::<(String, Option<i64>)>(connection)?
+            .into_iter()
+            .filter_map(|(character, count)| Some((character.chars().next()?, count.unwrap_or_default() as usize)))
+            .collect();
+        Ok(SelectCharacterCountsOutput {
+            news_count,
+            character_counts,
+        })
+    }
+
+    async fn select_sentence_ids(&self, input: SelectSentencesInput) -> Result<Vec<i32>> {
+        let sentence_ids = news_sentences::table
+            .filter(news_sentences::length.between(input.min_length as i32, input.max_length as i32))
+            .order(news_sentences::id)
+            .select(news_sentences::id)
+            .get_results::<i32>(&mut self.pool.get()?)?;
+        Ok(sentence_ids)
+    }
+
+    async fn select_sentences(&self, sentence_ids: Vec<i32>) -> Result<Vec<SelectSentenceOutput>> {
+        let mut sentences: HashMap<i32, (i32, String)> = news_sentences::table
+            .filter(news_sentences::id.eq_any(&sentence_ids))
+            .select((news_sentences::id, (news_sentences::news_id, news_sentences::text)))
+            .get_results::<(i32, (i32, String))>(&mut self.pool.get()?)?
+            .into_iter()
+            .collect();
+        let outputs = sentence_ids
+            .into_iter()
+            .filter_map(|sentence_id| {
+                let (news_id, text) = sentences.remove(&sentence_id)?;
+                Some(SelectSentenceOutput {
+                    sentence_id,
+                    news_id,
+                    text,
+                })
+            })
+            .collect();
+        Ok(outputs)
+    }
+
+    async fn select_background_image_paths(&self) -> Result<Vec<String>> {
+        let paths = news::table
+            .filter(news::image_path.is_not_null())
+            .order(news::id)
+            .select(news::image_path.assume_not_null())
+            .get_results::<String>(&mut self.pool.get()?)?;
+        Ok(paths)
+    }
+
+    async fn insert_synthetic_samples(&self, inputs: Vec<InsertSyntheticSampleInput>) -> Result<()> {
+        let values = inputs
+            .into_iter()
+            .map(|input| {
+                Ok(InsertSyntheticSampleValue {
+                    news_sentence_id: input.sentence_id,
+                    news_id: input.news_id,
+                    text: input.text,
+                    image_path: input.image_path,
+                    width: input.width as i32,
+                    height: input.height as i32,
+                    render_params: serde_json::to_string(&input.render_params)?,
+                    // Stored with the same bits, since PostgreSQL has no unsigned integers
+                    seed: input.seed as i64,
+                    background_image_path: input.background_image_path,
+                    annotation: serde_json::to_string(&input.annotation)?,
+                    augmentations: serde_json::to_string(&input.augmentations)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        diesel::insert_into(synthetic_samples::table)
+            .values(&values)
+            .execute(&mut self.pool.get()?)?;
+        Ok(())
+    }
+
+    async fn select_synthetic_samples(
+        &self,
+        input: SelectExportSamplesInput,
+    ) -> Result<Vec<SelectSyntheticSampleOutput>> {
+        let mut query = synthetic_samples::table
+            .left_join(news_splits::table.on(news_splits::id.nullable().eq(synthetic_samples::news_id)))
+            .filter(synthetic_samples::id.gt(input.after_id))
+            .into_boxed();
+        if let Some(source_name_value) = input.source_name {
+            let news_ids = news::table.filter(source_name.eq(source_name_value)).select(news::id);
+            query = query.filter(synthetic_samples::news_id.eq_any(news_ids.nullable()));
+        }
+        if let Some(created_from) = input.created_from {
+            query = query.filter(synthetic_samples::created_at.ge(created_from));
+        }
+        if let Some(created_to) = input.created_to {
+            query = query.filter(synthetic_samples::created_at.lt(created_to));
+        }
+        if let Some(split) = input.split {
+            query = query.filter(news_splits::split.nullable().eq(split.as_str()));
+        }
+        let outputs = query
+            .order(synthetic_samples::id)
+            .limit(input.limit)
+            .select((
+                synthetic_samples::id,
+                synthetic_samples::image_path,
+                synthetic_samples::text,
+                synthetic_samples::annotation,
+                news_splits::split.nullable(),
+            ))
+            .get_results::<(i32, String, String, Option<String>, Option<String>)>(&mut self.pool.get()?)?
+            .into_iter()
+            .map(|(sample_id, image_path, text, annotation, split)| {
+                Ok(SelectSyntheticSampleOutput {
+                    sample_id,
+                    image_path,
+                    text,
+                    annotation: match annotation {
+                        Some(annotation) => serde_json::from_str(&annotation)?,
+                        None => TextAnnotation::default(),
+                    },
+                    split: split.as_deref().and_then(Split::parse),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(outputs)
+    }
+
+    async fn select_insight_images(&self, input: SelectExportSamplesInput) -> Result<Vec<SelectInsightImageOutput>> {
+        let mut query = news::table
+            .inner_join(news_insights::table)
+            .left_join(news_splits::table)
+            .filter(news::id.gt(input.after_id))
+            .filter(news::image_path.is_not_null())
+            .filter(news_insights::fields.is_not_null())
+            .into_boxed();
+        if let Some(source_name_value) = input.source_name {
+            query = query.filter(source_name.eq(source_name_value));
+        }
+        if let Some(created_from) = input.created_from {
+            query = query.filter(news::created_at.ge(created_from));
+        }
+        if let Some(created_to) = input.created_to {
+            query = query.filter(news::created_at.lt(created_to));
+        }
+        if let Some(split) = input.split {
+            query = query.filter(news_splits::split.nullable().eq(split.as_str()));
+        }
+        let outputs = query
+            .order(news::id)
+            .limit(input.limit)
+            .select((
+                news::id,
+                news::image_path.assume_not_null(),
+                news_insights::fields.assume_not_null(),
+                news_splits::split.nullable(),
+            ))
+            .get_results::<(i32, String, String, Option<String>)>(&mut self.pool.get()?)?
+            .into_iter()
+            .map(|(news_id, image_path, fields, split)| SelectInsightImageOutput {
+                news_id,
+                image_path,
+                fields,
+                split: split.as_deref().and_then(Split::parse),
+            })
+            .collect();
+        Ok(outputs)
+    }
+
+    async fn select_unassigned_news(
+        &self,
+        input: SelectUnassignedNewsInput,
+    ) -> Result<Vec<SelectUnassignedNewsOutput>> {
+        let outputs = news::table
+            .filter(news::id.gt(input.after_news_id))
+            .filter(not(news::id.eq_any(news_splits::table.select(news_splits::id))))
+            .order(news::id)
+            .limit(input.limit)
+            .select((news::id, source_name, article_id, news::cluster_id))
+            .get_results::<(i32, String, String, Option<i32>)>(&mut self.pool.get()?)?
+            .into_iter()
+            .map(
+                |(news_id, source_name_value, article_id_value, cluster_id)| SelectUnassignedNewsOutput {
+                    news_id,
+                    source_name: source_name_value,
+                    article_id: article_id_value,
+                    cluster_id,
+                },
+            )
+            .collect();
+        Ok(outputs)
+    }
+
+    async fn pin_dataset(&self, input: PinDatasetInput) -> Result<usize> {
+        let news_count = self.pool.get()?.transaction(|connection| {
+            let dataset_id = diesel::insert_into(datasets::table)
+                .values((datasets::name.eq(&input.name), datasets::pinned.eq(true)))
+                .on_conflict(datasets::name)
+                .do_update()
+                .set((datasets::pinned.eq(true), datasets::updated_at.eq(Local::now())))
+                .returning(datasets::id)
+                .get_result::<i32>(connection)?;
+            let mut query = news::table.into_boxed();
+            if let Some(source_name_value) = &input.source_name {
+                query = query.filter(source_name.eq(source_name_value.clone()));
+            }
+            if let Some(created_from) = input.created_from {
+                query = query.filter(news::created_at.ge(created_from));
+            }
+            if let Some(created_to) = input.created_to {
+                query = query.filter(news::created_at.lt(created_to));
+            }
+            diesel::insert_into(dataset_news::table)
+                .values(query.select((dataset_id.into_sql::<diesel::sql_types::Integer>(), news::id)))
+                .into_columns((dataset_news::dataset_id, dataset_news::news_id))
+                .on_conflict_do_nothing()
+                .execute(connection)
+        })?;
+        Ok(news_count)
+    }
+
+    async fn unpin_dataset(&self, name: String) -> Result<bool> {
+        let dataset_count = diesel::update(datasets::table)
+            .filter(datasets::name.eq(name))
+            .set((datasets::pinned.eq(false), datasets::updated_at.eq(Local::now())))
+            .execute(&mut self.pool.get()?)?;
+        Ok(dataset_count > 0)
+    }
+
+    async fn insert_news_splits(&self, inputs: Vec<InsertNewsSplitInput>) -> Result<()> {
+        let values: Vec<InsertNewsSplitValue> = inputs
+            .into_iter()
+            .map(|input| InsertNewsSplitValue {
+                id: input.news_id,
+                split: input.split.as_str().to_string(),
+                group_key: input.group_key,
+            })
+            .collect();
+        diesel::insert_into(news_splits::table)
+            .values(&values)
+            .on_conflict_do_nothing()
+            .execute(&mut self.pool.get()?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use chrono::Local;
+    use diesel::prelude::*;
+
+    use super::PostgresqlClient;
+    use crate::{
+        domain::{
+            annotation::TextAnnotation,
+            sentence::Sentence,
+            split::Split,
+            synthesis::{LineAlignment, RenderParams, TextEffect, WritingMode},
+        },
+        execution::ports::repository::{
+            InsertNewsInput, InsertNewsSentenceInput, InsertNewsSplitInput, InsertSyntheticSampleInput, Repository,
+            SelectExportSamplesInput, UpdateNewsTextInput,
+        },
+        schema::{news, news_sentences},
+    };
+
+    fn sentences(texts: &[&str]) -> Vec<InsertNewsSentenceInput> {
+        let mut start = 0;
+        texts
+            .iter()
+            .map(|text| {
+                let length = text.chars().count();
+                start += length;
+                InsertNewsSentenceInput {
+                    sentence: Sentence {
+                        start: start - length,
+                        length,
+                        text: text.to_string(),
+                    },
+                    difficulty: None,
+                }
+            })
+            .collect()
+    }
+
+    // Needs a database with every migration run, given by `CHLORIA_TEST_DATABASE_URL`, and is skipped without one
+    #[tokio::test]
+    async fn keep_samples_of_reprocessed_news() {
+        let Ok(database_url) = env::var("CHLORIA_TEST_DATABASE_URL") else {
+            return;
+        };
+        let client = PostgresqlClient::new(database_url).unwrap();
+        // A source of its own, so that runs do not see each other
+        let source_name = format!("Test{}", Local::now().timestamp_micros());
+        client
+            .insert_news(vec![InsertNewsInput {
+                source_name: source_name.clone(),
+                article_id: "a1".to_string(),
+                link: None,
+                title: None,
+                short_text: None,
+                long_text: Some("今日は晴れ。明日は雨。".to_string()),
+                images: vec![],
+                published_time: None,
+                raw_html_path: None,
+                raw_feed_path: None,
+                document: None,
+                extractor_version: None,
+                original_title: None,
+                original_short_text: None,
+                original_long_text: None,
+                quality_reasons: vec![],
+                simhash: None,
+                statistics: None,
+                difficulty: None,
+                sentences: sentences(&["今日は晴れ。", "明日は雨。"]),
+            }])
+            .await
+            .unwrap();
+        let connection = &mut client.pool.get().unwrap();
+        let news_id = news::table
+            .filter(news::source_name.eq(&source_name))
+            .select(news::id)
+            .get_result::<i32>(connection)
+            .unwrap();
+        let sentence_ids = || {
+            news_sentences::table
+                .filter(news_sentences::news_id.eq(news_id))
+                .order(news_sentences::start_offset)
+                .select(news_sentences::id)
+                .get_results::<i32>(&mut client.pool.get().unwrap())
+                .unwrap()
+        };
+        let old_sentence_ids = sentence_ids();
+        client
+            .insert_news_splits(vec![InsertNewsSplitInput {
+                news_id,
+                split: Split::Validation,
+                group_key: format!("cluster:{}", news_id),
+            }])
+            .await
+            .unwrap();
+        let sample = |sentence_id: i32, text: &str| InsertSyntheticSampleInput {
+            sentence_id: Some(sentence_id),
+            news_id: Some(news_id),
+            text: text.to_string(),
+            image_path: "sha256/ab.png".to_string(),
+            width: 1,
+            height: 1,
+            render_params: RenderParams {
+                font_name: "a".to_string(),
+                font: None,
+                font_size: 16.0,
+                text_color: [0, 0, 0],
+                background_color: [255, 255, 255],
+                max_line_length: 8,
+                line_spacing: 1.5,
+                alignment: LineAlignment::Start,
+                padding: 4,
+                effect: TextEffect::None,
+                position: None,
+                writing_mode: WritingMode::Horizontal,
+            },
+            seed: 1,
+            background_image_path: None,
+            annotation: TextAnnotation::default(),
+            augmentations: vec![],
+        };
+        client
+            .insert_synthetic_samples(vec![
+                sample(old_sentence_ids[0], "今日は晴れ。"),
+                sample(old_sentence_ids[1], "明日は雨。"),
+            ])
+            .await
+            .unwrap();
+        // Only the second sentence changes
+        client
+            .update_news_text(UpdateNewsTextInput {
+                news_id,
+                long_text: Some("今日は晴れ。明日は曇り。".to_string()),
+                document: None,
+                extractor_version: 1,
+                original_long_text: None,
+                statistics: None,
+                difficulty: None,
+                sentences: sentences(&["今日は晴れ。", "明日は曇り。"]),
+            })
+            .await
+            .unwrap();
+        let new_sentence_ids = sentence_ids();
+        assert_eq!(new_sentence_ids[0], old_sentence_ids[0]);
+        assert_ne!(new_sentence_ids[1], old_sentence_ids[1]);
+        let samples = client
+            .select_synthetic_samples(SelectExportSamplesInput {
+                after_id: 0,
+                limit: 10,
+                source_name: Some(source_name),
+                created_from: None,
+                created_to: None,
+                split: Some(Split::Validation),
+            })
+            .await
+            .unwrap();
+        // Even the sample of the sentence which has changed keeps its news
+        assert_eq!(samples.len(), 2);
+        assert!(samples.iter().all(|s| s.split == Some(Split::Validation)));
     }
 }
@@ -0,0 +1,539 @@
+use std::{collections::BTreeMap, fs, io::Cursor, path::Path, sync::Arc};
+
+use ab_glyph::{Font, FontRef, Glyph, PxScale, ScaleFont};
+use anyhow::{bail, Context, Result};
+use image::{imageops, ImageFormat, Rgb, RgbImage};
+use log::error;
+use ttf_parser::{name_id, Face};
+
+use crate::{
+    domain::{
+        annotation::{rectangle, CharacterAnnotation, LineAnnotation, ReadingDirection, TextAnnotation},
+        font::{FontMetadata, IndexedFont, StyleClass},
+        synthesis::{
+            effect_color, ensure_contrast, find_low_texture_region, LineAlignment, RenderParams, TextEffect,
+            WritingMode,
+        },
+        vertical::{vertical_cells, VerticalCell},
+    },
+    execution::ports::text_renderer::{ComposeTextInput, RenderTextInput, RenderTextOutput, TextRenderer},
+};
+
+const FONT_EXTENSIONS: [&str; 4] = ["ttf", "otf", "ttc", "otc"];
+
+pub(crate) struct AbGlyphTool {
+    // Named after their file stems, with the index in collections, whose faces share the bytes of the file
+    // Faces are parsed again for each render, which only reads their table directory
+    fonts: BTreeMap<String, (Arc<[u8]>, u32, IndexedFont)>,
+}
+
+// Reads the metadata and the characters mapped to glyphs by the cmap of a face of the font file
+fn index_font(name: &str, data: &[u8], index: u32) -> Result<IndexedFont> {
+    let face = Face::parse(data, index)?;
+    // Typographic family names group every weight, unlike legacy ones, and only Unicode names can be decoded
+    let family = [name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY]
+        .iter()
+        .find_map(|id| {
+            face.names()
+                .into_iter()
+                .filter(|n| n.name_id == *id)
+                .find_map(|n| n.to_string())
+        })
+        .unwrap_or_else(|| name.to_string());
+    let metadata = FontMetadata {
+        name: name.to_string(),
+        style_class: StyleClass::guess(&family),
+        family,
+        weight: face.weight().to_number(),
+    };
+    let characters = FontRef::try_from_slice_and_index(data, index)?
+        .codepoint_ids()
+        .filter(|(id, _)| id.0 != 0)
+        .map(|(_, c)| c)
+        .collect();
+    Ok(IndexedFont { metadata, characters })
+}
+
+// Characters of a line with their pen positions and advances, along with the width of the line
+type LineAdvances = (Vec<(char, f32, f32)>, f32);
+
+// Coverage of the text and of its effect, from 0 to 1 for each pixel, before they are colored and drawn
+struct TextLayer {
+    width: u32,
+    height: u32,
+    text_mask: Vec<f32>,
+    effect_mask: Vec<f32>,
+    annotation: TextAnnotation, // Relative to the top left corner of the layer
+}
+
+impl AbGlyphTool {
+    // Fonts are searched for in subdirectories too, and files which cannot be parsed are skipped,
+    // but files named the same in different directories are refused since fonts are named after them
+    pub(crate) fn new(font_dir: Option<String>) -> Result<Self> {
+        let mut fonts = BTreeMap::new();
+        let mut dirs: Vec<_> = font_dir.into_iter().map(|d| Path::new(&d).to_path_buf()).collect();
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                let is_font_file = path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| FONT_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+                let Some(stem) = path.file_stem().and_then(|s| s.to_str()).filter(|_| is_font_file) else {
+                    continue;
+                };
+                let data: Arc<[u8]> = fs::read(&path)?.into();
+                let faces_num = ttf_parser::fonts_in_collection(&data).unwrap_or(1);
+                for index in 0..faces_num {
+                    let name = match faces_num {
+                        1 => stem.to_string(),
+                        _ => format!("{}-{}", stem, index),
+                    };
+                    if fonts.contains_key(&name) {
+                        bail!("Duplicate font name {}: {}", name, path.display());
+                    }
+                    match index_font(&name, &data, index) {
+                        Ok(font) => {
+                            fonts.insert(name, (Arc::clone(&data), index, font));
+                        }
+                        Err(error) => error!("path={}, index={}, error={}", path.display(), index, error),
+                    }
+                }
+            }
+        }
+        Ok(Self { fonts })
+    }
+
+    fn rasterize(&self, lines: &[String], params: &RenderParams) -> Result<TextLayer> {
+        let (data, index, indexed_font) = self
+            .fonts
+            .get(&params.font_name)
+            .context(format!("Unknown font: {}", params.font_name))?;
+        let font = &FontRef::try_from_slice_and_index(data, *index)?;
+        let missing_characters: String = lines
+            .iter()
+            .flat_map(|l| l.chars())
+            .filter(|c| !c.is_whitespace() && !indexed_font.characters.contains(c))
+            .collect();
+        if !missing_characters.is_empty() {
+            bail!("Font {} has no glyphs for {}", params.font_name, missing_characters);
+        }
+        // Effects are kept inside the layer
+        let margin = params.padding as f32
+            + match params.effect {
+                TextEffect::None => 0.0,
+                TextEffect::Shadow { offset, .. } => offset[0].abs().max(offset[1].abs()),
+                TextEffect::Outline { width, .. } => width,
+            }
+            .ceil();
+        let mut layer = match params.writing_mode {
+            WritingMode::Horizontal => rasterize_horizontal(font, lines, params, margin),
+            WritingMode::Vertical => rasterize_vertical(font, lines, params, margin),
+        };
+        layer.effect_mask = match params.effect {
+            TextEffect::None => vec![0.0; layer.text_mask.len()],
+            TextEffect::Shadow { offset, .. } => shift(&layer.text_mask, layer.width, layer.height, offset),
+            TextEffect::Outline { width, .. } => dilate(&layer.text_mask, layer.width, layer.height, width),
+        };
+        Ok(layer)
+    }
+}
+
+// The effect mask of the returned layer is left empty
+fn rasterize_horizontal(font: &FontRef, lines: &[String], params: &RenderParams, margin: f32) -> TextLayer {
+    let scaled_font = font.as_scaled(PxScale::from(params.font_size));
+    let line_advances: Vec<LineAdvances> = lines
+        .iter()
+        .map(|line| {
+            let mut advances = vec![];
+            let mut x = 0.0;
+            let mut previous_glyph_id = None;
+            for c in line.chars() {
+                let glyph_id = scaled_font.glyph_id(c);
+                if let Some(previous_glyph_id) = previous_glyph_id {
+                    x += scaled_font.kern(previous_glyph_id, glyph_id);
+                }
+                advances.push((c, x, scaled_font.h_advance(glyph_id)));
+                x += scaled_font.h_advance(glyph_id);
+                previous_glyph_id = Some(glyph_id);
+            }
+            (advances, x)
+        })
+        .collect();
+    let text_width = line_advances.iter().map(|(_, w)| *w).fold(0.0, f32::max);
+    let line_height = (params.font_size * params.line_spacing).max(scaled_font.height());
+    let text_height = scaled_font.height() + line_height * lines.len().saturating_sub(1) as f32;
+    let width = (text_width + margin * 2.0).ceil().max(1.0) as u32;
+    let height = (text_height + margin * 2.0).ceil().max(1.0) as u32;
+    let mut text_mask = vec![0.0; (width * height) as usize];
+    let mut annotation = TextAnnotation::default();
+    for (i, (line, (advances, line_width))) in lines.iter().zip(&line_advances).enumerate() {
+        let line_x = margin
+            + match params.alignment {
+                LineAlignment::Start => 0.0,
+                LineAlignment::Center => (text_width - line_width) / 2.0,
+                LineAlignment::End => text_width - line_width,
+            };
+        let baseline = margin + scaled_font.ascent() + line_height * i as f32;
+        let (top, bottom) = (baseline - scaled_font.ascent(), baseline - scaled_font.descent());
+        let mut characters = vec![];
+        for (c, x, advance) in advances {
+            let glyph = scaled_font
+                .scaled_glyph(*c)
+                .id
+                .with_scale_and_position(params.font_size, ab_glyph::point(line_x + x, baseline));
+            characters.push(CharacterAnnotation {
+                character: *c,
+                polygon: rectangle(line_x + x, top, line_x + x + advance, bottom),
+                occluded: false,
+            });
+            draw_glyph(font, glyph, &mut text_mask, width, height);
+        }
+        annotation.lines.push(LineAnnotation {
+            text: line.clone(),
+            polygon: rectangle(line_x, top, line_x + line_width, bottom),
+            characters,
+            direction: ReadingDirection::LeftToRight,
+            occluded: false,
+        });
+    }
+    TextLayer {
+        width,
+        height,
+        text_mask,
+        effect_mask: vec![],
+        annotation,
+    }
+}
+
+// Every character takes a square cell as wide as the font size, and lines are spaced like horizontal ones
+// The effect mask of the returned layer is left empty
+fn rasterize_vertical(font: &FontRef, lines: &[String], params: &RenderParams, margin: f32) -> TextLayer {
+    let em = params.font_size;
+    let line_cells: Vec<Vec<VerticalCell>> = lines.iter().map(|line| vertical_cells(line)).collect();
+    let line_pitch = (em * params.line_spacing).max(em);
+    let text_width = em + line_pitch * lines.len().saturating_sub(1) as f32;
+    let text_height = line_cells.iter().map(|cells| cells.len()).max().unwrap_or_default() as f32 * em;
+    let width = (text_width + margin * 2.0).ceil().max(1.0) as u32;
+    let height = (text_height + margin * 2.0).ceil().max(1.0) as u32;
+    let mut text_mask = vec![0.0; (width * height) as usize];
+    let mut annotation = TextAnnotation::default();
+    for (i, (line, cells)) in lines.iter().zip(&line_cells).enumerate() {
+        let line_x = margin + text_width - em - line_pitch * i as f32;
+        let line_height = cells.len() as f32 * em;
+        let line_y = margin
+            + match params.alignment {
+                LineAlignment::Start => 0.0,
+                LineAlignment::Center => (text_height - line_height) / 2.0,
+                LineAlignment::End => text_height - line_height,
+            };
+        let mut characters = vec![];
+        for (j, cell) in cells.iter().enumerate() {
+            let y = line_y + em * j as f32;
+            let text = cell.text();
+            let (offset, rotated) = match cell {
+                VerticalCell::Shifted { offset, .. } => (*offset, false),
+                VerticalCell::Rotated(_) => ([0.0, 0.0], true),
+                VerticalCell::Upright(_) | VerticalCell::Horizontal(_) => ([0.0, 0.0], false),
+            };
+            let (cell_size, cell_mask) = rasterize_cell(font, &text, em);
+            let position = (line_x + offset[0] * em, y + offset[1] * em);
+            for (v, row) in cell_mask.chunks(cell_size).enumerate() {
+                for (u, coverage) in row.iter().enumerate() {
+                    // Turned 90° clockwise around the center of the cell
+                    let (dx, dy) = match rotated {
+                        true => (cell_size - 1 - v, u),
+                        false => (u, v),
+                    };
+                    let (px, py) = (
+                        position.0.round() as i64 + dx as i64,
+                        position.1.round() as i64 + dy as i64,
+                    );
+                    if *coverage == 0.0 || px < 0 || py < 0 || px >= width as i64 || py >= height as i64 {
+                        continue;
+                    }
+                    let index = (py * width as i64 + px) as usize;
+                    text_mask[index] = (text_mask[index] + coverage).min(1.0);
+                }
+            }
+            // Characters written across the line share its cell, and start at the top right like the line
+            let character_width = em / text.chars().count() as f32;
+            for (k, c) in text.chars().enumerate() {
+                let x = line_x + character_width * k as f32;
+                let mut polygon = rectangle(x, y, x + character_width, y + em);
+                polygon.rotate_left(1);
+                characters.push(CharacterAnnotation {
+                    character: c,
+                    polygon,
+                    occluded: false,
+                });
+            }
+        }
+        let mut polygon = rectangle(line_x, line_y, line_x + em, line_y + line_height);
+        polygon.rotate_left(1);
+        annotation.lines.push(LineAnnotation {
+            text: line.clone(),
+            polygon,
+            characters,
+            direction: ReadingDirection::TopToBottom,
+            occluded: false,
+        });
+    }
+    TextLayer {
+        width,
+        height,
+        text_mask,
+        effect_mask: vec![],
+        annotation,
+    }
+}
+
+// Lays the text out horizontally at the center of a square cell as wide as the font size,
+// squeezing it horizontally if it is wider than the cell, and returns the size of the cell with its coverage
+fn rasterize_cell(font: &FontRef, text: &str, em: f32) -> (usize, Vec<f32>) {
+    let text_width: f32 = text
+        .chars()
+        .map(|c| font.as_scaled(PxScale::from(em)).h_advance(font.glyph_id(c)))
+        .sum();
+    let scale = PxScale {
+        x: em * (em / text_width.max(f32::EPSILON)).min(1.0),
+        y: em,
+    };
+    let scaled_font = font.as_scaled(scale);
+    let cell_size = em.ceil() as usize;
+    let mut cell_mask = vec![0.0; cell_size * cell_size];
+    let mut x = (em - text_width * scale.x / em) / 2.0;
+    let baseline = (em - scaled_font.height()) / 2.0 + scaled_font.ascent();
+    for c in text.chars() {
+        let glyph = scaled_font
+            .scaled_glyph(c)
+            .id
+            .with_scale_and_position(scale, ab_glyph::point(x, baseline));
+        x += scaled_font.h_advance(glyph.id);
+        draw_glyph(font, glyph, &mut cell_mask, cell_size as u32, cell_size as u32);
+    }
+    (cell_size, cell_mask)
+}
+
+// Adds the coverage of the glyph to the mask, clipping what falls outside of it
+fn draw_glyph(font: &FontRef, glyph: Glyph, mask: &mut [f32], width: u32, height: u32) {
+    let Some(outlined_glyph) = font.outline_glyph(glyph) else {
+        return; // Whitespace
+    };
+    let bounds = outlined_glyph.px_bounds();
+    outlined_glyph.draw(|gx, gy, coverage| {
+        let (px, py) = (bounds.min.x as i64 + gx as i64, bounds.min.y as i64 + gy as i64);
+        if px < 0 || py < 0 || px >= width as i64 || py >= height as i64 {
+            return;
+        }
+        let index = (py * width as i64 + px) as usize;
+        mask[index] = (mask[index] + coverage).min(1.0);
+    });
+}
+
+impl TextRenderer for AbGlyphTool {
+    fn fonts(&self) -> Vec<IndexedFont> {
+        self.fonts.values().map(|(_, _, f)| f.clone()).collect()
+    }
+
+    fn render(&self, input: RenderTextInput) -> Result<RenderTextOutput> {
+        let layer = self.rasterize(&input.lines, &input.params)?;
+        let mut image = RgbImage::from_pixel(layer.width, layer.height, Rgb(input.params.background_color));
+        draw(&mut image, &layer, (0, 0), &input.params);
+        Ok(RenderTextOutput {
+            bytes: encode(&image)?,
+            width: layer.width,
+            height: layer.height,
+            params: input.params,
+            annotation: layer.annotation,
+        })
+    }
+
+    fn compose(&self, input: ComposeTextInput) -> Result<RenderTextOutput> {
+        let mut image = image::load_from_memory(&input.background)?.into_rgb8();
+        let layer = self.rasterize(&input.lines, &input.params)?;
+        let luma = imageops::grayscale(&image);
+        let (x, y) = find_low_texture_region(
+            luma.as_raw(),
+            image.width() as usize,
+            image.height() as usize,
+            layer.width as usize,
+            layer.height as usize,
+            input.max_texture,
+        )
+        .context("No region of the photo is large and plain enough for the text.")?;
+        let (x, y) = (x as u32, y as u32);
+        let mut params = input.params;
+        params.background_color = mean_color(&image, (x, y), (layer.width, layer.height));
+        params.text_color = ensure_contrast(params.text_color, params.background_color, input.min_contrast_ratio);
+        params.effect = match params.effect {
+            TextEffect::None => TextEffect::None,
+            TextEffect::Shadow { offset, .. } => TextEffect::Shadow {
+                offset,
+                color: effect_color(params.text_color),
+            },
+            TextEffect::Outline { width, .. } => TextEffect::Outline {
+                width,
+                color: effect_color(params.text_color),
+            },
+        };
+        params.position = Some([x, y]);
+        draw(&mut image, &layer, (x, y), &params);
+        let mut annotation = layer.annotation;
+        annotation.map_points(|[px, py]| [px + x as f32, py + y as f32]);
+        Ok(RenderTextOutput {
+            bytes: encode(&image)?,
+            width: image.width(),
+            height: image.height(),
+            params,
+            annotation,
+        })
+    }
+}
+
+// Moves the mask by the offset rounded to whole pixels
+fn shift(mask: &[f32], width: u32, height: u32, offset: [f32; 2]) -> Vec<f32> {
+    let (dx, dy) = (offset[0].round() as i64, offset[1].round() as i64);
+    let mut shifted = vec![0.0; mask.len()];
+    for y in 0..height as i64 {
+        for x in 0..width as i64 {
+            let (sx, sy) = (x - dx, y - dy);
+            if sx >= 0 && sy >= 0 && sx < width as i64 && sy < height as i64 {
+                shifted[(y * width as i64 + x) as usize] = mask[(sy * width as i64 + sx) as usize];
+            }
+        }
+    }
+    shifted
+}
+
+// Grows the mask by the radius, taking the greatest coverage within it
+fn dilate(mask: &[f32], width: u32, height: u32, radius: f32) -> Vec<f32> {
+    let r = radius.ceil() as i64;
+    let mut dilated = vec![0.0; mask.len()];
+    for y in 0..height as i64 {
+        for x in 0..width as i64 {
+            let mut coverage: f32 = 0.0;
+            for dy in -r..=r {
+                for dx in -r..=r {
+                    let (sx, sy) = (x + dx, y + dy);
+                    if (dx * dx + dy * dy) as f32 > radius * radius
+                        || sx < 0
+                        || sy < 0
+                        || sx >= width as i64
+                        || sy >= height as i64
+                    {
+                        continue;
+                    }
+                    coverage = coverage.max(mask[(sy * width as i64 + sx) as usize]);
+                }
+            }
+            dilated[(y * width as i64 + x) as usize] = coverage;
+        }
+    }
+    dilated
+}
+
+// Draws the effect then the text of the layer with their colors, the top left corner of the layer at the position
+fn draw(image: &mut RgbImage, layer: &TextLayer, position: (u32, u32), params: &RenderParams) {
+    let effect_color = match params.effect {
+        TextEffect::None => None,
+        TextEffect::Shadow { color, .. } | TextEffect::Outline { color, .. } => Some(color),
+    };
+    for y in 0..layer.height.min(image.height().saturating_sub(position.1)) {
+        for x in 0..layer.width.min(image.width().saturating_sub(position.0)) {
+            let index = (y * layer.width + x) as usize;
+            let pixel = image.get_pixel_mut(position.0 + x, position.1 + y);
+            if let Some(effect_color) = effect_color {
+                blend(pixel, effect_color, layer.effect_mask[index]);
+            }
+            blend(pixel, params.text_color, layer.text_mask[index]);
+        }
+    }
+}
+
+fn blend(pixel: &mut Rgb<u8>, color: [u8; 3], coverage: f32) {
+    for (channel, color_channel) in pixel.0.iter_mut().zip(color) {
+        *channel = (*channel as f32 * (1.0 - coverage) + color_channel as f32 * coverage).round() as u8;
+    }
+}
+
+fn mean_color(image: &RgbImage, position: (u32, u32), size: (u32, u32)) -> [u8; 3] {
+    let mut sums = [0u64; 3];
+    for y in position.1..position.1 + size.1 {
+        for x in position.0..position.0 + size.0 {
+            for (sum, channel) in sums.iter_mut().zip(image.get_pixel(x, y).0) {
+                *sum += channel as u64;
+            }
+        }
+    }
+    sums.map(|s| (s / (size.0 as u64 * size.1 as u64).max(1)) as u8)
+}
+
+fn encode(image: &RgbImage) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+    image.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AbGlyphTool;
+    use crate::{
+        domain::{
+            annotation::{bounds, ReadingDirection},
+            synthesis::{LineAlignment, RenderParams, TextEffect, WritingMode},
+        },
+        execution::ports::text_renderer::{RenderTextInput, TextRenderer},
+    };
+
+    #[test]
+    fn render_vertical_lines() {
+        let font_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/src/infrastructure/text_renderer/fixtures");
+        let tool = AbGlyphTool::new(Some(font_dir.to_string())).unwrap();
+        let output = tool
+            .render(RenderTextInput {
+                lines: vec!["AB12".to_string(), "C".to_string()],
+                params: RenderParams {
+                    font_name: "DejaVuSansMono".to_string(),
+                    font: None,
+                    font_size: 20.0,
+                    text_color: [0, 0, 0],
+                    background_color: [255, 255, 255],
+                    max_line_length: 4,
+                    line_spacing: 1.5,
+                    alignment: LineAlignment::Start,
+                    padding: 4,
+                    effect: TextEffect::None,
+                    position: None,
+                    writing_mode: WritingMode::Vertical,
+                },
+            })
+            .unwrap();
+        // Two lines 30 pixels apart, the first one with three cells as the number takes a single one
+        assert_eq!((output.width, output.height), (58, 68));
+        let lines = &output.annotation.lines;
+        assert_eq!(lines.len(), 2);
+        assert!(bounds(&lines[0].polygon)[0] > bounds(&lines[1].polygon)[2]);
+        assert_eq!(
+            lines[0].characters.iter().map(|c| c.character).collect::<String>(),
+            "AB12"
+        );
+        assert_eq!(bounds(&lines[0].characters[2].polygon), [34.0, 44.0, 44.0, 64.0]);
+        assert_eq!(bounds(&lines[0].characters[3].polygon), [44.0, 44.0, 54.0, 64.0]);
+        // Every polygon starts at the top right of its box
+        for line in lines {
+            assert_eq!(line.direction, ReadingDirection::TopToBottom);
+            let polygons = [&line.polygon]
+                .into_iter()
+                .chain(line.characters.iter().map(|c| &c.polygon));
+            for polygon in polygons {
+                let [_, y_min, x_max, _] = bounds(polygon);
+                assert_eq!(polygon[0], [x_max, y_min]);
+            }
+        }
+    }
+}
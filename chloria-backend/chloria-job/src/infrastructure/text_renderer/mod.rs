@@ -0,0 +1 @@
+pub(crate) mod ab_glyph;
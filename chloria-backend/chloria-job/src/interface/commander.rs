@@ -1,7 +1,168 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeDelta};
+use clap::{Parser, Subcommand};
 use log::info;
 
-use crate::execution::workshop::Workshop;
+use crate::{
+    domain::{
+        export::{AnnotationFormat, SampleKind},
+        split::Split,
+    },
+    execution::workshop::{ExportDestination, ExportFilter, Workshop},
+};
+
+#[derive(Parser)]
+pub(crate) struct Cli {
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>, // Collect news if no command is given
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Command {
+    /// Collect news from the configured fetchers
+    CollectNews,
+    /// Analyze stored images that have no metadata yet
+    BackfillImageMetadata,
+    /// Scrape and save images for news that have a link but no image
+    BackfillMissingImages,
+    /// Delete news and files which are older than the configured retention rules
+    EnforceRetention,
+    /// Extract the text of news again from their archived pages with the current extractor
+    Reprocess {
+        /// Only reprocess news of this source
+        #[arg(long)]
+        source: Option<String>,
+        /// Only reprocess news collected on or after this date (e.g. 2025-05-01)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Only reprocess news collected before this date
+        #[arg(long)]
+        to: Option<NaiveDate>,
+    },
+    /// Report the frequency of characters and their coverage of the Jōyō kanji and JIS levels
+    ReportCharacters {
+        /// Only count characters of news of this source
+        #[arg(long)]
+        source: Option<String>,
+        /// Only count characters of news collected on or after this date (e.g. 2025-05-01)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Only count characters of news collected before this date
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// Number of the most frequent characters to list
+        #[arg(long, default_value_t = 100)]
+        top: usize,
+    },
+    /// Render sentences of news into synthetic samples
+    Synthesize {
+        /// Number of samples to render, one per sentence picked at random
+        #[arg(long, default_value_t = 100)]
+        count: i64,
+        /// Seed of the random generator, drawn at random if not given
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Only render sentences with at least this number of characters
+        #[arg(long, default_value_t = 4)]
+        min_length: usize,
+        /// Only render sentences with at most this number of characters
+        #[arg(long, default_value_t = 40)]
+        max_length: usize,
+        /// Put the text on the least textured regions of news photos instead of plain backgrounds
+        #[arg(long)]
+        scene: bool,
+    },
+    /// Export samples along with their labels in a format OCR models are trained with
+    Export {
+        /// One of `coco-text`, `icdar2015`, `paddle-detection`, `paddle-recognition` and `recognition-list`
+        #[arg(long)]
+        format: String,
+        /// Either `synthetic` samples, or `real` news images with the text provided by their insights
+        #[arg(long, default_value = "synthetic")]
+        kind: String,
+        /// Only export samples whose text comes from news of this source
+        #[arg(long)]
+        source: Option<String>,
+        /// Only export samples created on or after this date (e.g. 2025-05-01)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Only export samples created before this date
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// Maximum number of samples to export
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Only export samples whose text comes from news of this split (`train`, `validation` or `test`)
+        #[arg(long)]
+        split: Option<String>,
+        /// Write the export under this prefix of the dataset bucket
+        #[arg(long, required_unless_present = "dir", conflicts_with = "dir")]
+        prefix: Option<String>,
+        /// Write the export to this local directory instead of the dataset bucket
+        #[arg(long)]
+        dir: Option<String>,
+    },
+    /// Pack samples and their labels into WebDataset tar shards in the dataset bucket, resuming an interrupted export
+    ExportShards {
+        /// Either `synthetic` samples, or `real` news images with the text provided by their insights
+        #[arg(long, default_value = "synthetic")]
+        kind: String,
+        /// Only export samples whose text comes from news of this source
+        #[arg(long)]
+        source: Option<String>,
+        /// Only export samples created on or after this date (e.g. 2025-05-01)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Only export samples created before this date
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// Maximum number of samples to export
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Only export samples whose text comes from news of this split (`train`, `validation` or `test`)
+        #[arg(long)]
+        split: Option<String>,
+        /// Write the shards and their manifest under this prefix of the dataset bucket
+        #[arg(long)]
+        prefix: String,
+        /// Maximum size of a shard in megabytes, unless a single sample is larger
+        #[arg(long, default_value_t = 256)]
+        max_shard_size: usize,
+    },
+    /// Assign news without a split to train, validation or test, keeping news of the same group together
+    /// (collecting news does it too, so this is only needed for news collected before)
+    AssignSplits,
+    /// Compare stored files with the database and report orphan, missing and mismatched files
+    ReconcileStorage {
+        /// Delete orphan files, drop references to missing files and re-analyze mismatched files
+        #[arg(long)]
+        fix: bool,
+        /// Only reconcile files whose path starts with this prefix (can be repeated)
+        #[arg(long)]
+        prefix: Vec<String>,
+    },
+    /// Keep the matching news and their files from being deleted by the retention rules
+    PinDataset {
+        /// Name of the dataset, created if it does not exist yet
+        #[arg(long)]
+        name: String,
+        /// Only add news of this source
+        #[arg(long)]
+        source: Option<String>,
+        /// Only add news collected on or after this date (e.g. 2025-05-01)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Only add news collected before this date
+        #[arg(long)]
+        to: Option<NaiveDate>,
+    },
+    /// Let the retention rules delete news of the dataset again, unless another pinned dataset has them
+    UnpinDataset {
+        /// Name of the dataset
+        #[arg(long)]
+        name: String,
+    },
+}
 
 pub(crate) struct Commander<'c> {
     workshop: &'c Workshop,
@@ -12,7 +173,56 @@ impl<'c> Commander<'c> {
         Self { workshop }
     }
 
-    pub(crate) async fn collect_news(&self) -> Result<()> {
+    pub(crate) async fn run(&self, command: Command) -> Result<()> {
+        match command {
+            Command::CollectNews => self.collect_news().await,
+            Command::BackfillImageMetadata => self.backfill_image_metadata().await,
+            Command::BackfillMissingImages => self.backfill_missing_images().await,
+            Command::EnforceRetention => self.enforce_retention().await,
+            Command::Reprocess { source, from, to } => self.reprocess(source, from, to).await,
+            Command::ReportCharacters { source, from, to, top } => self.report_characters(source, from, to, top).await,
+            Command::Synthesize {
+                count,
+                seed,
+                min_length,
+                max_length,
+                scene,
+            } => self.synthesize(count, seed, min_length, max_length, scene).await,
+            Command::Export {
+                format,
+                kind,
+                source,
+                from,
+                to,
+                limit,
+                split,
+                prefix,
+                dir,
+            } => {
+                self.export(format, kind, source, from, to, limit, split, prefix, dir)
+                    .await
+            }
+            Command::ExportShards {
+                kind,
+                source,
+                from,
+                to,
+                limit,
+                split,
+                prefix,
+                max_shard_size,
+            } => {
+                self.export_shards(kind, source, from, to, limit, split, prefix, max_shard_size)
+                    .await
+            }
+            Command::AssignSplits => self.assign_splits().await,
+            Command::ReconcileStorage { fix, prefix } => self.reconcile_storage(prefix, fix).await,
+            Command::PinDataset { name, source, from, to } => self.pin_dataset(name, source, from, to).await,
+            Command::UnpinDataset { name } => self.unpin_dataset(name).await,
+        }
+    }
+
+    async fn collect_news(&self) -> Result<()> {
         const TASK_PERMITS_NUM: usize = 50;
         const INSERT_BATCH_SIZE: usize = 100;
         let (total_news_count, inserted_news_count) = self
@@ -23,6 +233,292 @@ impl<'c> Commander<'c> {
             "total_news_count={}, inserted_news_count={}",
             total_news_count, inserted_news_count
         );
+        // News are clustered as they are inserted, so they can be assigned to splits right away
+        self.assign_splits().await
+    }
+
+    async fn backfill_image_metadata(&self) -> Result<()> {
+        const SELECT_BATCH_SIZE: i64 = 100;
+        let (total_image_count, backfilled_image_count) = self
+            .workshop
+            .execute_backfill_image_metadata_case(SELECT_BATCH_SIZE)
+            .await?;
+        info!(
+            "total_image_count={}, backfilled_image_count={}",
+            total_image_count, backfilled_image_count
+        );
+        Ok(())
+    }
+
+    async fn backfill_missing_images(&self) -> Result<()> {
+        const SELECT_BATCH_SIZE: i64 = 100;
+        let (total_news_count, backfilled_news_count) = self
+            .workshop
+            .execute_backfill_missing_images_case(SELECT_BATCH_SIZE)
+            .await?;
+        info!(
+            "total_news_count={}, backfilled_news_count={}",
+            total_news_count, backfilled_news_count
+        );
+        Ok(())
+    }
+
+    async fn enforce_retention(&self) -> Result<()> {
+        const DELETE_BATCH_SIZE: i64 = 100;
+        let (expired_news_count, deleted_file_count) =
+            self.workshop.execute_enforce_retention_case(DELETE_BATCH_SIZE).await?;
+        info!(
+            "expired_news_count={}, deleted_file_count={}",
+            expired_news_count, deleted_file_count
+        );
+        Ok(())
+    }
+
+    async fn reprocess(&self, source: Option<String>, from: Option<NaiveDate>, to: Option<NaiveDate>) -> Result<()> {
+        const SELECT_BATCH_SIZE: i64 = 100;
+        let (total_news_count, reprocessed_news_count) = self
+            .workshop
+            .execute_reprocess_news_case(source, from.map(to_local), to.map(to_local), SELECT_BATCH_SIZE)
+            .await?;
+        info!(
+            "total_news_count={}, reprocessed_news_count={}",
+            total_news_count, reprocessed_news_count
+        );
+        Ok(())
+    }
+
+    async fn report_characters(
+        &self,
+        source: Option<String>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        top: usize,
+    ) -> Result<()> {
+        let output = self
+            .workshop
+            .execute_report_characters_case(source, from.map(to_local), to.map(to_local), top)
+            .await?;
+        let statistics = &output.statistics;
+        let ratio = |count: usize| match statistics.character_count {
+            0 => 0.0,
+            total => count as f64 / total as f64,
+        };
+        info!(
+            "news_count={}, character_count={}, distinct_character_count={}",
+            output.news_count,
+            statistics.character_count,
+            statistics.character_counts.len()
+        );
+        info!(
+            "kanji_ratio={:.4}, hiragana_ratio={:.4}, katakana_ratio={:.4}, latin_ratio={:.4}, digit_ratio={:.4}, other_ratio={:.4}",
+            ratio(statistics.kanji_count),
+            ratio(statistics.hiragana_count),
+            ratio(statistics.katakana_count),
+            ratio(statistics.latin_count),
+            ratio(statistics.digit_count),
+            ratio(statistics.other_count)
+        );
+        info!(
+            "joyo_kanji_ratio={:.4}, jis_level_1_ratio={:.4}, jis_level_2_ratio={:.4}, non_jis_ratio={:.4}",
+            ratio(statistics.joyo_kanji_count),
+            ratio(statistics.jis_level_1_count),
+            ratio(statistics.jis_level_2_count),
+            ratio(statistics.non_jis_count)
+        );
+        info!(
+            "joyo_kanji_coverage={:.4}, jis_level_1_coverage={:.4}, jis_level_2_coverage={:.4}",
+            output.coverage.joyo_kanji_ratio(),
+            output.coverage.jis_level_1_ratio(),
+            output.coverage.jis_level_2_ratio()
+        );
+        let top_characters: Vec<String> = output
+            .top_characters
+            .iter()
+            .map(|(c, n)| format!("{}:{}", c, n))
+            .collect();
+        info!("top_characters={}", top_characters.join(","));
+        info!("rare_characters={}", statistics.rare_characters);
+        Ok(())
+    }
+
+    async fn synthesize(
+        &self,
+        count: i64,
+        seed: Option<u64>,
+        min_length: usize,
+        max_length: usize,
+        scene: bool,
+    ) -> Result<()> {
+        const INSERT_BATCH_SIZE: usize = 100;
+        let seed = seed.unwrap_or_else(rand::random);
+        let output = self
+            .workshop
+            .execute_synthesize_samples_case(count, seed, min_length, max_length, scene, INSERT_BATCH_SIZE)
+            .await?;
+        info!(
+            "seed={}, scene={}, sentence_count={}, sample_count={}, uncovered_sentence_count={}",
+            seed, scene, output.sentence_count, output.sample_count, output.uncovered_sentence_count
+        );
+        // Most needed first, to help choose fonts to add
+        let mut missing_characters: Vec<_> = output.missing_characters.into_iter().collect();
+        missing_characters.sort_by(|(_, n1), (_, n2)| n2.cmp(n1));
+        let missing_characters: Vec<String> = missing_characters.iter().map(|(c, n)| format!("{}:{}", c, n)).collect();
+        info!("missing_characters={}", missing_characters.join(","));
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)] // One per option
+    async fn export(
+        &self,
+        format: String,
+        kind: String,
+        source: Option<String>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        limit: Option<usize>,
+        split: Option<String>,
+        prefix: Option<String>,
+        dir: Option<String>,
+    ) -> Result<()> {
+        const SELECT_BATCH_SIZE: i64 = 100;
+        let annotation_format = match format.as_str() {
+            "coco-text" => AnnotationFormat::CocoText,
+            "icdar2015" => AnnotationFormat::Icdar2015,
+            "paddle-detection" => AnnotationFormat::PaddleDetection,
+            "paddle-recognition" => AnnotationFormat::PaddleRecognition,
+            "recognition-list" => AnnotationFormat::RecognitionList,
+            _ => bail!("Invalid export format: {}", format),
+        };
+        let sample_kind = parse_sample_kind(&kind)?;
+        let split = split.as_deref().map(parse_split).transpose()?;
+        let destination = match (prefix, dir) {
+            (_, Some(path)) => ExportDestination::Directory { path },
+            (Some(prefix), None) => ExportDestination::Bucket { prefix },
+            (None, None) => bail!("Either a prefix or a directory is required."),
+        };
+        let filter = ExportFilter {
+            source_name: source,
+            created_from: from.map(to_local),
+            created_to: to.map(to_local),
+            limit,
+            split,
+        };
+        let output = self
+            .workshop
+            .execute_export_annotations_case(annotation_format, sample_kind, filter, destination, SELECT_BATCH_SIZE)
+            .await?;
+        info!(
+            "format={}, kind={}, sample_count={}, skipped_sample_count={}, file_count={}",
+            format, kind, output.sample_count, output.skipped_sample_count, output.file_count
+        );
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)] // One per option
+    async fn export_shards(
+        &self,
+        kind: String,
+        source: Option<String>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        limit: Option<usize>,
+        split: Option<String>,
+        prefix: String,
+        max_shard_size: usize,
+    ) -> Result<()> {
+        const SELECT_BATCH_SIZE: i64 = 100;
+        const BYTES_PER_MEGABYTE: usize = 1024 * 1024;
+        let sample_kind = parse_sample_kind(&kind)?;
+        let split = split.as_deref().map(parse_split).transpose()?;
+        let filter = ExportFilter {
+            source_name: source,
+            created_from: from.map(to_local),
+            created_to: to.map(to_local),
+            limit,
+            split,
+        };
+        let output = self
+            .workshop
+            .execute_export_shards_case(
+                sample_kind,
+                filter,
+                prefix.clone(),
+                max_shard_size * BYTES_PER_MEGABYTE,
+                SELECT_BATCH_SIZE,
+            )
+            .await?;
+        info!(
+            "prefix={}, kind={}, resumed={}, sample_count={}, shard_count={}, skipped_sample_count={}",
+            prefix, kind, output.resumed, output.sample_count, output.shard_count, output.skipped_sample_count
+        );
+        Ok(())
+    }
+
+    async fn assign_splits(&self) -> Result<()> {
+        const SELECT_BATCH_SIZE: i64 = 100;
+        let output = self.workshop.execute_assign_splits_case(SELECT_BATCH_SIZE).await?;
+        info!(
+            "train_count={}, validation_count={}, test_count={}",
+            output.train_count, output.validation_count, output.test_count
+        );
         Ok(())
     }
+
+    async fn reconcile_storage(&self, prefixes: Vec<String>, fix: bool) -> Result<()> {
+        const ORPHAN_MIN_AGE: TimeDelta = TimeDelta::days(1);
+        let output = self
+            .workshop
+            .execute_reconcile_storage_case(prefixes, fix, ORPHAN_MIN_AGE)
+            .await?;
+        info!(
+            "fix={}, orphan_file_count={}, missing_file_count={}, mismatched_file_count={}, fixed_file_count={}",
+            fix,
+            output.orphan_file_count,
+            output.missing_file_count,
+            output.mismatched_file_count,
+            output.fixed_file_count
+        );
+        Ok(())
+    }
+
+    async fn pin_dataset(
+        &self,
+        name: String,
+        source: Option<String>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<()> {
+        let added_news_count = self
+            .workshop
+            .execute_pin_dataset_case(name.clone(), source, from.map(to_local), to.map(to_local))
+            .await?;
+        info!("name={}, added_news_count={}", name, added_news_count);
+        Ok(())
+    }
+
+    async fn unpin_dataset(&self, name: String) -> Result<()> {
+        self.workshop.execute_unpin_dataset_case(name.clone()).await?;
+        info!("name={}", name);
+        Ok(())
+    }
+}
+
+// Dates are taken as UTC days, as the API does
+fn to_local(date: NaiveDate) -> DateTime<Local> {
+    DateTime::from(date.and_time(NaiveTime::MIN).and_utc())
+}
+
+fn parse_sample_kind(kind: &str) -> Result<SampleKind> {
+    match kind {
+        "synthetic" => Ok(SampleKind::Synthetic),
+        "real" => Ok(SampleKind::Real),
+        _ => bail!("Invalid sample kind: {}", kind),
+    }
+}
+
+fn parse_split(split: &str) -> Result<Split> {
+    match Split::parse(split) {
+        Some(split) => Ok(split),
+        None => bail!("Invalid split: {}", split),
+    }
 }
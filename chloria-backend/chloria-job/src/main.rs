@@ -4,26 +4,41 @@ mod infrastructure;
 mod interface;
 mod schema;
 
-use std::env;
+use std::env::{self, VarError};
+use std::fs;
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use chrono::TimeDelta;
+use clap::Parser;
 use env_logger::Env;
 
+use crate::domain::{
+    difficulty::DifficultyScorer,
+    quality::{QualityAction, QualityRuleSet},
+    split::{SplitAssigner, SplitGrouping},
+    synthesis::SynthesisConfig,
+    text::{NormalizationForm, TextNormalizer},
+};
 use crate::execution::{
-    ports::news_fetcher::NewsFetcher,
-    workshop::{Config, Workshop},
+    ports::{file_storage::FileObjectKind, news_fetcher::NewsFetcher},
+    workshop::{Config, ImageStorageLayout, RetentionRule, RetentionTarget, Workshop},
 };
 use crate::infrastructure::{
-    file_storage::minio::MinioClient,
+    archiver::tar::TarTool,
+    article_extractor::scraper::ScraperTool,
+    file_storage::{fs::FsTool, minio::MinioClient},
     http_helper::reqwest::ReqwestTool,
+    image_analyzer::image::ImageTool,
     news_fetcher::{newsdata::NewsdataClient, yahoo::YahooClient},
     repository::postgresql::PostgresqlClient,
+    text_renderer::ab_glyph::AbGlyphTool,
 };
-use crate::interface::commander::Commander;
+use crate::interface::commander::{Cli, Command, Commander};
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = Cli::parse();
     // Read env vars
     let newsdata_api_key = env::var("NEWSDATA_API_KEY").ok();
     let newsdata_pages_num_limit = env::var("NEWSDATA_PAGES_NUM_LIMIT")
@@ -37,12 +52,69 @@ async fn main() -> Result<()> {
     let database_url = env::var("DATABASE_URL")?;
     let chloria_news_fetchers: Vec<String> = env::var("CHLORIA_NEWS_FETCHERS")?
         .split(",")
-        .into_iter()
         .map(|f| f.to_string())
         .collect();
     let chloria_job_interval = env::var("CHLORIA_JOB_INTERVAL")?.parse()?; // In hours
     let chloria_origin_bucket_name = env::var("CHLORIA_ORIGIN_BUCKET_NAME")?;
+    let chloria_synthetic_bucket_name = env::var("CHLORIA_SYNTHETIC_BUCKET_NAME").unwrap_or("synthetic".to_string());
+    let chloria_dataset_bucket_name = env::var("CHLORIA_DATASET_BUCKET_NAME").unwrap_or("dataset".to_string());
     let chloria_case_permits_num = env::var("CHLORIA_CASE_PERMITS_NUM")?.parse().unwrap_or(10);
+    let chloria_image_storage_layout = match env::var("CHLORIA_IMAGE_STORAGE_LAYOUT").as_deref() {
+        Ok("dated") | Err(VarError::NotPresent) => ImageStorageLayout::Dated,
+        Ok("content_addressed") => ImageStorageLayout::ContentAddressed,
+        Ok(layout) => bail!("Invalid image storage layout: {}", layout),
+        Err(error) => bail!("Invalid image storage layout: {}", error),
+    };
+    let chloria_raw_archive_kinds: Vec<String> = env::var("CHLORIA_RAW_ARCHIVE_KINDS")
+        .unwrap_or_default()
+        .split(",")
+        .map(|k| k.to_string())
+        .collect();
+    let chloria_text_normalization: Vec<String> = env::var("CHLORIA_TEXT_NORMALIZATION")
+        .unwrap_or_default()
+        .split(",")
+        .filter(|n| !n.is_empty())
+        .map(|n| n.to_string())
+        .collect();
+    if let Some(normalization) = chloria_text_normalization
+        .iter()
+        .find(|n| !matches!(n.as_str(), "nfkc" | "whitespace" | "line_breaks" | "control_characters"))
+    {
+        bail!("Invalid text normalization: {}", normalization);
+    }
+    let mut chloria_text_normalization_forms = vec![];
+    if chloria_text_normalization.contains(&"nfkc".to_string()) {
+        chloria_text_normalization_forms.push(NormalizationForm::Nfkc);
+    }
+    // Applied after NFKC, so that the table can also adjust what NFKC produces
+    if let Ok(table_file) = env::var("CHLORIA_TEXT_NORMALIZATION_TABLE_FILE") {
+        chloria_text_normalization_forms.push(NormalizationForm::parse_table(&fs::read_to_string(table_file)?));
+    }
+    let chloria_jlpt_kanji_table_file = env::var("CHLORIA_JLPT_KANJI_TABLE_FILE").ok();
+    let chloria_quality_rules_file = env::var("CHLORIA_QUALITY_RULES_FILE").ok(); // JSON array of rules
+    let chloria_quality_action = match env::var("CHLORIA_QUALITY_ACTION").as_deref() {
+        Ok("flag") | Err(VarError::NotPresent) => QualityAction::Flag,
+        Ok("reject") => QualityAction::Reject,
+        Ok(action) => bail!("Invalid quality action: {}", action),
+        Err(error) => bail!("Invalid quality action: {}", error),
+    };
+    let chloria_synthesis_font_dir = env::var("CHLORIA_SYNTHESIS_FONT_DIR").ok(); // No fonts if not given
+    let chloria_synthesis_config_file = env::var("CHLORIA_SYNTHESIS_CONFIG_FILE").ok(); // JSON object of ranges
+    let chloria_split_grouping = match env::var("CHLORIA_SPLIT_GROUPING").as_deref() {
+        Ok("cluster") | Err(VarError::NotPresent) => SplitGrouping::Cluster,
+        Ok("article") => SplitGrouping::Article,
+        Ok("source") => SplitGrouping::Source,
+        Ok(grouping) => bail!("Invalid split grouping: {}", grouping),
+        Err(error) => bail!("Invalid split grouping: {}", error),
+    };
+    let chloria_split_ratios = match env::var("CHLORIA_SPLIT_RATIOS") {
+        Ok(ratios) => parse_split_ratios(&ratios)?,
+        Err(_) => SplitAssigner::default().ratios,
+    };
+    let chloria_retention_rules = match env::var("CHLORIA_RETENTION_RULES") {
+        Ok(rules) => parse_retention_rules(&rules)?,
+        Err(_) => vec![],
+    };
     env_logger::init_from_env(Env::new().filter("CHLORIA_LOG_LEVEL"));
     // Initialize infrastructure
     let mut news_fetchers: Vec<Arc<dyn NewsFetcher>> = vec![];
@@ -56,27 +128,145 @@ async fn main() -> Result<()> {
         let yahoo_client = YahooClient::new(chloria_job_interval);
         news_fetchers.push(Arc::new(yahoo_client));
     }
+    let scraper_tool = ScraperTool::new();
     let reqwest_tool = ReqwestTool::new();
+    let image_tool = Arc::new(ImageTool::new());
+    // Reading every font file takes a while, and only synthesis renders text
+    let ab_glyph_tool = match cli.command {
+        Some(Command::Synthesize { .. }) => AbGlyphTool::new(chloria_synthesis_font_dir)?,
+        _ => AbGlyphTool::new(None)?,
+    };
     let minio_client = MinioClient::new(
         minio_operator_sts_endpoint,
         minio_operator_cacert_file,
         minio_web_identity_token_file,
         minio_tenant_endpoint,
         chloria_origin_bucket_name,
+        chloria_synthetic_bucket_name,
+        chloria_dataset_bucket_name,
     )?;
+    let fs_tool = FsTool::new();
+    let tar_tool = TarTool::new();
     let postgresql_client = PostgresqlClient::new(database_url)?;
     // Initialize execution
     let workshop = Workshop::new(
         news_fetchers,
+        Arc::new(scraper_tool),
         Arc::new(reqwest_tool),
+        image_tool.clone(),
         Arc::new(minio_client),
         Arc::new(postgresql_client),
+        Arc::new(ab_glyph_tool),
+        image_tool,
+        Arc::new(fs_tool),
+        Arc::new(tar_tool),
         Config {
             case_permits_num: chloria_case_permits_num,
+            image_storage_layout: chloria_image_storage_layout,
+            archive_raw_html: chloria_raw_archive_kinds.contains(&"html".to_string()),
+            archive_raw_feed: chloria_raw_archive_kinds.contains(&"feed".to_string()),
+            retention_rules: chloria_retention_rules,
+            text_normalizer: TextNormalizer {
+                forms: chloria_text_normalization_forms,
+                whitespace: chloria_text_normalization.contains(&"whitespace".to_string()),
+                line_breaks: chloria_text_normalization.contains(&"line_breaks".to_string()),
+                control_characters: chloria_text_normalization.contains(&"control_characters".to_string()),
+            },
+            quality_rule_set: QualityRuleSet {
+                rules: match chloria_quality_rules_file {
+                    Some(rules_file) => serde_json::from_str(&fs::read_to_string(rules_file)?)?,
+                    None => vec![],
+                },
+                action: chloria_quality_action,
+            },
+            difficulty_scorer: match chloria_jlpt_kanji_table_file {
+                Some(table_file) => DifficultyScorer::with_jlpt_table(&fs::read_to_string(table_file)?)?,
+                None => DifficultyScorer::default(),
+            },
+            synthesis_config: match chloria_synthesis_config_file {
+                Some(config_file) => {
+                    let config: SynthesisConfig = serde_json::from_str(&fs::read_to_string(config_file)?)?;
+                    config.validate()?;
+                    config
+                }
+                None => SynthesisConfig::default(),
+            },
+            split_assigner: SplitAssigner {
+                grouping: chloria_split_grouping,
+                ratios: chloria_split_ratios,
+            },
         },
     );
     // Initialize interface
     let commander = Commander::new(&workshop);
-    commander.collect_news().await?;
+    commander.run(cli.command.unwrap_or(Command::CollectNews)).await?;
     Ok(())
 }
+
+// Comma-separated rules in the form of `<source_name>:<target>:<max_age_days>`, e.g. `Yahoo:origin:180,*:news:730`
+fn parse_retention_rules(rules: &str) -> Result<Vec<RetentionRule>> {
+    let mut retention_rules = vec![];
+    for rule in rules.split(",").filter(|r| !r.is_empty()) {
+        let [source_name, target, max_age_days] = rule.split(":").collect::<Vec<_>>()[..] else {
+            bail!("Invalid retention rule: {}", rule);
+        };
+        retention_rules.push(RetentionRule {
+            source_name: match source_name {
+                "*" => None,
+                _ => Some(source_name.to_string()),
+            },
+            target: match target {
+                "news" => RetentionTarget::News,
+                "origin" => RetentionTarget::File(FileObjectKind::Origin),
+                "raw_html" => RetentionTarget::File(FileObjectKind::RawHtml),
+                "raw_feed" => RetentionTarget::File(FileObjectKind::RawFeed),
+                _ => bail!("Invalid retention target: {}", target),
+            },
+            // A zero or negative age would put the cutoff in the future and expire everything
+            max_age: match max_age_days.parse::<u32>() {
+                Ok(0) | Err(_) => bail!("Invalid retention max age: {}", max_age_days),
+                Ok(days) => {
+                    TimeDelta::try_days(days.into()).context(format!("Invalid retention max age: {}", max_age_days))?
+                }
+            },
+        });
+    }
+    Ok(retention_rules)
+}
+
+// Comma-separated ratios of train, validation and test, e.g. `0.8,0.1,0.1`
+fn parse_split_ratios(ratios: &str) -> Result<[f64; 3]> {
+    let parsed: Vec<f64> = ratios.split(",").map(|r| r.trim().parse()).collect::<Result<_, _>>()?;
+    let Ok(split_ratios) = <[f64; 3]>::try_from(parsed) else {
+        bail!("Invalid split ratios: {}", ratios);
+    };
+    if split_ratios.iter().any(|r| *r < 0.0) || split_ratios.iter().sum::<f64>() <= 0.0 {
+        bail!("Invalid split ratios: {}", ratios);
+    }
+    Ok(split_ratios)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::parse_retention_rules;
+    use crate::execution::{ports::file_storage::FileObjectKind, workshop::RetentionTarget};
+
+    #[test]
+    fn parse_retention_rules_from_env() {
+        let rules = parse_retention_rules("Yahoo:origin:180,,*:news:730").unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].source_name.as_deref(), Some("Yahoo"));
+        assert!(matches!(rules[0].target, RetentionTarget::File(FileObjectKind::Origin)));
+        assert_eq!(rules[0].max_age, TimeDelta::days(180));
+        assert_eq!(rules[1].source_name, None);
+        assert!(matches!(rules[1].target, RetentionTarget::News));
+        assert!(parse_retention_rules("").unwrap().is_empty());
+        assert!(parse_retention_rules("Yahoo:images:180").is_err());
+        assert!(parse_retention_rules("Yahoo:180").is_err());
+        assert!(parse_retention_rules("Yahoo:news:forever").is_err());
+        assert!(parse_retention_rules("*:news:-1").is_err());
+        assert!(parse_retention_rules("*:news:0").is_err());
+    }
+}
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+CREATE TABLE news_images (
+    id SERIAL PRIMARY KEY,
+    news_id INT NOT NULL REFERENCES news,
+    path TEXT NOT NULL,
+    width INT NOT NULL,
+    height INT NOT NULL,
+    mime_type TEXT NOT NULL,
+    byte_size BIGINT NOT NULL,
+    sha256 TEXT NOT NULL,
+    perceptual_hash TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (news_id, path)
+);
@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE news_images
+    ADD CONSTRAINT news_images_news_id_path_key UNIQUE (news_id, path),
+    DROP CONSTRAINT news_images_news_id_position_key,
+    DROP COLUMN alt_text,
+    DROP COLUMN caption,
+    DROP COLUMN position;
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+
+ALTER TABLE news_images
+    ADD COLUMN position INT NOT NULL DEFAULT 0,
+    ADD COLUMN caption TEXT,
+    ADD COLUMN alt_text TEXT,
+    ADD CONSTRAINT news_images_news_id_position_key UNIQUE (news_id, position),
+    -- The same photo may appear twice in an article, e.g. in a figure and in the gallery
+    DROP CONSTRAINT news_images_news_id_path_key;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE news DROP COLUMN images_expired_at;
+DROP TABLE dataset_news;
+DROP TABLE datasets;
@@ -0,0 +1,21 @@
+-- Your SQL goes here
+
+CREATE TABLE datasets (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    pinned BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (name)
+);
+
+CREATE TABLE dataset_news (
+    dataset_id INT NOT NULL REFERENCES datasets,
+    news_id INT NOT NULL REFERENCES news,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (dataset_id, news_id)
+);
+
+-- Set when images are deleted by retention rules, so that they are not backfilled again
+ALTER TABLE news ADD COLUMN images_expired_at TIMESTAMPTZ;
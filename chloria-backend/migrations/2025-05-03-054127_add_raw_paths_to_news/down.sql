@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE news
+    DROP COLUMN raw_feed_path,
+    DROP COLUMN raw_html_path;
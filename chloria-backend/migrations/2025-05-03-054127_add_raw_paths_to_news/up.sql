@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+ALTER TABLE news
+    ADD COLUMN raw_html_path TEXT,
+    ADD COLUMN raw_feed_path TEXT;
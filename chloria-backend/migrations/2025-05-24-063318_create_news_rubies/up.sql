@@ -0,0 +1,12 @@
+-- Your SQL goes here
+
+CREATE TABLE news_rubies (
+    id SERIAL PRIMARY KEY,
+    news_id INT NOT NULL REFERENCES news,
+    start_offset INT NOT NULL,
+    end_offset INT NOT NULL,
+    reading TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (news_id, start_offset)
+);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE news
+    DROP COLUMN original_long_text,
+    DROP COLUMN original_short_text,
+    DROP COLUMN original_title;
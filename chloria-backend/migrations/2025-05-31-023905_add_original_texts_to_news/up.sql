@@ -0,0 +1,6 @@
+-- Your SQL goes here
+
+ALTER TABLE news
+    ADD COLUMN original_title TEXT,
+    ADD COLUMN original_short_text TEXT,
+    ADD COLUMN original_long_text TEXT;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+ALTER TABLE news
+    ADD COLUMN quality_reasons TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,15 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX news_cluster_id_idx;
+DROP INDEX news_simhash_band_3_idx;
+DROP INDEX news_simhash_band_2_idx;
+DROP INDEX news_simhash_band_1_idx;
+DROP INDEX news_simhash_band_0_idx;
+
+ALTER TABLE news
+    DROP COLUMN cluster_id,
+    DROP COLUMN simhash_band_3,
+    DROP COLUMN simhash_band_2,
+    DROP COLUMN simhash_band_1,
+    DROP COLUMN simhash_band_0,
+    DROP COLUMN simhash;
@@ -0,0 +1,15 @@
+-- Your SQL goes here
+
+ALTER TABLE news
+    ADD COLUMN simhash BIGINT,
+    ADD COLUMN simhash_band_0 INT,
+    ADD COLUMN simhash_band_1 INT,
+    ADD COLUMN simhash_band_2 INT,
+    ADD COLUMN simhash_band_3 INT,
+    ADD COLUMN cluster_id INT;
+
+CREATE INDEX news_simhash_band_0_idx ON news (simhash_band_0);
+CREATE INDEX news_simhash_band_1_idx ON news (simhash_band_1);
+CREATE INDEX news_simhash_band_2_idx ON news (simhash_band_2);
+CREATE INDEX news_simhash_band_3_idx ON news (simhash_band_3);
+CREATE INDEX news_cluster_id_idx ON news (cluster_id);
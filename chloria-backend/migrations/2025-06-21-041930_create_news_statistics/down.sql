@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE news_characters;
+DROP TABLE news_statistics;
@@ -0,0 +1,28 @@
+-- Your SQL goes here
+
+CREATE TABLE news_statistics (
+    id INT PRIMARY KEY REFERENCES news,
+    character_count INT NOT NULL,
+    kanji_count INT NOT NULL,
+    hiragana_count INT NOT NULL,
+    katakana_count INT NOT NULL,
+    latin_count INT NOT NULL,
+    digit_count INT NOT NULL,
+    other_count INT NOT NULL,
+    joyo_kanji_count INT NOT NULL,
+    jis_level_1_count INT NOT NULL,
+    jis_level_2_count INT NOT NULL,
+    non_jis_count INT NOT NULL,
+    rare_characters TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE news_characters (
+    news_id INT NOT NULL REFERENCES news,
+    character TEXT NOT NULL,
+    count INT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (news_id, character)
+);
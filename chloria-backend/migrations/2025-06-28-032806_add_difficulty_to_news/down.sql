@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX news_difficulty_idx;
+
+ALTER TABLE news
+    DROP COLUMN max_kanji_grade,
+    DROP COLUMN difficulty;
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+
+ALTER TABLE news
+    ADD COLUMN difficulty REAL,
+    ADD COLUMN max_kanji_grade INT;
+
+CREATE INDEX news_difficulty_idx ON news (difficulty);
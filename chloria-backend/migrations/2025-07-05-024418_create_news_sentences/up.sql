@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+CREATE TABLE news_sentences (
+    id SERIAL PRIMARY KEY,
+    news_id INT NOT NULL REFERENCES news,
+    start_offset INT NOT NULL,
+    length INT NOT NULL,
+    text TEXT NOT NULL,
+    difficulty REAL,
+    max_kanji_grade INT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (news_id, start_offset)
+);
+
+CREATE INDEX news_sentences_length_idx ON news_sentences (length);
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+CREATE TABLE synthetic_samples (
+    id SERIAL PRIMARY KEY,
+    news_sentence_id INT REFERENCES news_sentences ON DELETE SET NULL,
+    -- Kept when the sentence changes, since the source and split of samples are those of their news
+    news_id INT REFERENCES news ON DELETE SET NULL,
+    text TEXT NOT NULL,
+    image_path TEXT NOT NULL,
+    width INT NOT NULL,
+    height INT NOT NULL,
+    render_params TEXT NOT NULL,
+    seed BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE synthetic_samples
+    DROP COLUMN annotation,
+    DROP COLUMN background_image_path;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+ALTER TABLE synthetic_samples
+    ADD COLUMN background_image_path TEXT,
+    ADD COLUMN annotation TEXT;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE synthetic_samples
+    DROP COLUMN augmentations;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+ALTER TABLE synthetic_samples
+    ADD COLUMN augmentations TEXT;
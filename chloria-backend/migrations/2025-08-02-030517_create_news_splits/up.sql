@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+CREATE TABLE news_splits (
+    id INT PRIMARY KEY REFERENCES news,
+    split TEXT NOT NULL,
+    group_key TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX news_splits_split_idx ON news_splits (split);
@@ -18,7 +18,11 @@ sts_client = boto3.client(
 
 class FakeMinioStsOperator(BaseHTTPRequestHandler):
     def do_POST(self):
-        bucket_name = os.environ.get("CHLORIA_ORIGIN_BUCKET_NAME")
+        bucket_names = [
+            os.environ.get("CHLORIA_ORIGIN_BUCKET_NAME"),
+            os.environ.get("CHLORIA_SYNTHETIC_BUCKET_NAME"),
+            os.environ.get("CHLORIA_DATASET_BUCKET_NAME"),
+        ]
         # Ref: https://github.com/metalwhale/wave/blob/main/projects/chloria-minio/base/ocean/configmap.yaml
         policy = {
             "Version": "2012-10-17",
@@ -29,8 +33,10 @@ class FakeMinioStsOperator(BaseHTTPRequestHandler):
                         "s3:*",
                     ],
                     "Resource": [
-                        f"arn:aws:s3:::{bucket_name}",
-                        f"arn:aws:s3:::{bucket_name}/*",
+                        resource
+                        for bucket_name in bucket_names
+                        if bucket_name is not None
+                        for resource in [f"arn:aws:s3:::{bucket_name}", f"arn:aws:s3:::{bucket_name}/*"]
                     ],
                 },
             ],
@@ -1,2 +1,4 @@
 #!/bin/bash
 mc mb chloria/origin -p
+mc mb chloria/synthetic -p
+mc mb chloria/dataset -p